@@ -1,9 +1,34 @@
-use chrono::{DateTime, Datelike, Local, NaiveDate};
+use chrono::{DateTime, Datelike, Days, Duration, Local, Months, NaiveDate, TimeZone, Weekday};
+
+/// Earliest year the calendar navigates to.
+pub const MIN_YEAR: i32 = 1;
+/// Latest year the calendar navigates to.
+pub const MAX_YEAR: i32 = 9999;
+const FIRST_SUPPORTED_DATE: NaiveDate = NaiveDate::from_ymd_opt(MIN_YEAR, 1, 1).unwrap();
+const LAST_SUPPORTED_DATE: NaiveDate = NaiveDate::from_ymd_opt(MAX_YEAR, 12, 31).unwrap();
+
+/// A step of the selection within the month grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
 
 #[derive(Debug, Clone)]
 pub struct Calendar {
+    /// A moment in the displayed month; prefer [`Calendar::current_naive`].
     pub current_date: DateTime<Local>,
+    /// The selected day; prefer [`Calendar::selected_naive`].
     pub selected_date: DateTime<Local>,
+    pub week_start: Weekday,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Calendar {
@@ -12,67 +37,216 @@ impl Calendar {
         Self {
             current_date: now,
             selected_date: now,
+            week_start: Weekday::Sun,
         }
     }
 
+    /// The selected day.
+    pub fn selected_naive(&self) -> NaiveDate {
+        self.selected_date.date_naive()
+    }
+
+    /// The day `current_date` falls on, which anchors the displayed month.
+    pub fn current_naive(&self) -> NaiveDate {
+        self.current_date.date_naive()
+    }
+
+    pub fn set_week_start(&mut self, day: Weekday) {
+        self.week_start = day;
+    }
+
+    /// The seven weekdays in column order, beginning with `week_start`.
+    pub fn weekdays(&self) -> [Weekday; 7] {
+        let mut day = self.week_start;
+        [(); 7].map(|_| {
+            let current = day;
+            day = day.succ();
+            current
+        })
+    }
+
     pub fn next_month(&mut self) {
-        let naive_date = self.current_date.naive_local().date();
-        let next_month = if naive_date.month() == 12 {
-            NaiveDate::from_ymd_opt(naive_date.year() + 1, 1, 1).unwrap()
-        } else {
-            NaiveDate::from_ymd_opt(naive_date.year(), naive_date.month() + 1, 1).unwrap()
-        };
-        self.current_date = DateTime::from_naive_utc_and_offset(
-            next_month.and_hms_opt(0, 0, 0).unwrap(),
-            *self.current_date.offset(),
-        );
+        self.shift_months(1);
     }
 
     pub fn prev_month(&mut self) {
-        let naive_date = self.current_date.naive_local().date();
-        let prev_month = if naive_date.month() == 1 {
-            NaiveDate::from_ymd_opt(naive_date.year() - 1, 12, 1).unwrap()
+        self.shift_months(-1);
+    }
+
+    pub fn next_year(&mut self) {
+        self.shift_months(12);
+    }
+
+    pub fn prev_year(&mut self) {
+        self.shift_months(-12);
+    }
+
+    /// Displays the month `months` away, keeping the selected day of month as
+    /// far as the new month allows. Nothing changes if that month is outside
+    /// the supported years.
+    fn shift_months(&mut self, months: i32) {
+        let Some(first_day) = self.current_naive().with_day(1) else {
+            return;
+        };
+        let shifted = if months < 0 {
+            first_day.checked_sub_months(Months::new(months.unsigned_abs()))
         } else {
-            NaiveDate::from_ymd_opt(naive_date.year(), naive_date.month() - 1, 1).unwrap()
+            first_day.checked_add_months(Months::new(months.unsigned_abs()))
         };
-        self.current_date = DateTime::from_naive_utc_and_offset(
-            prev_month.and_hms_opt(0, 0, 0).unwrap(),
-            *self.current_date.offset(),
-        );
+        let Some(shifted) = shifted.filter(|date| is_supported(*date)) else {
+            return;
+        };
+        self.current_date = local_midnight(shifted);
+        self.selected_date = self.safe_with_day(self.selected_date.day());
     }
 
-    pub fn get_month_grid(&self) -> Vec<Vec<Option<u32>>> {
+    /// The given day of the displayed month, clamped to the month's last day so that
+    /// e.g. the 31st becomes the 28th/29th in February.
+    pub fn safe_with_day(&self, day: u32) -> DateTime<Local> {
         let naive_date = self.current_date.naive_local().date();
-        let first_day = NaiveDate::from_ymd_opt(naive_date.year(), naive_date.month(), 1).unwrap();
+        let (year, month) = (naive_date.year(), naive_date.month());
+        let day = day.clamp(1, days_in_month(year, month));
+        local_midnight(NaiveDate::from_ymd_opt(year, month, day).unwrap())
+    }
 
-        let days_in_month = if naive_date.month() == 12 {
+    /// Selects the first day of the displayed month.
+    pub fn select_first_day(&mut self) {
+        self.selected_date = self.safe_with_day(1);
+    }
+
+    /// Selects the last day of the displayed month, e.g. the 29th of a leap-year February.
+    pub fn select_last_day(&mut self) {
+        let naive_date = self.current_date.naive_local().date();
+        self.selected_date =
+            self.safe_with_day(days_in_month(naive_date.year(), naive_date.month()));
+    }
+
+    /// The seven dates of the week containing `selected_date`, starting on `week_start`.
+    pub fn selected_week(&self) -> [NaiveDate; 7] {
+        let selected = self.selected_naive();
+        let offset = (selected.weekday().num_days_from_sunday() + 7
+            - self.week_start.num_days_from_sunday())
+            % 7;
+        let first = selected - chrono::Duration::days(offset as i64);
+        std::array::from_fn(|day| first + chrono::Duration::days(day as i64))
+    }
+
+    /// Selects `weekday` within the week shown by [`Calendar::selected_week`].
+    pub fn select_weekday_in_current_week(&mut self, weekday: Weekday) {
+        let selected = self.selected_naive();
+        if let Some(date) = self
+            .selected_week()
+            .into_iter()
+            .find(|date| date.weekday() == weekday)
+        {
+            self.advance_days((date - selected).num_days());
+        }
+    }
+
+    /// Moves the selection by `n` days, following it into another month if
+    /// needed, but no further than the first or last supported day.
+    pub fn advance_days(&mut self, n: i64) {
+        self.selected_date = step_days(&self.selected_date, n);
+        if self.selected_date.year() != self.current_date.year()
+            || self.selected_date.month() != self.current_date.month()
+        {
+            self.current_date = self.selected_date;
+        }
+    }
+
+    pub fn go_to_today(&mut self) {
+        let now = Local::now();
+        self.current_date = now;
+        self.selected_date = now;
+    }
+
+    /// Selects and displays `date`, or the nearest supported day to it.
+    pub fn go_to_date(&mut self, date: NaiveDate) {
+        let date_time = local_midnight(clamp_date(date));
+        self.current_date = date_time;
+        self.selected_date = date_time;
+    }
+
+    /// The start of the displayed month and the start of the month after it.
+    pub fn month_bounds(&self) -> (DateTime<Local>, DateTime<Local>) {
+        let naive_date = self.current_date.naive_local().date();
+        let first_day = NaiveDate::from_ymd_opt(naive_date.year(), naive_date.month(), 1).unwrap();
+        let next_first_day = if naive_date.month() == 12 {
             NaiveDate::from_ymd_opt(naive_date.year() + 1, 1, 1)
         } else {
             NaiveDate::from_ymd_opt(naive_date.year(), naive_date.month() + 1, 1)
         }
-        .unwrap()
-        .signed_duration_since(first_day)
-        .num_days() as u32;
-
-        let first_weekday = first_day.weekday().num_days_from_sunday();
-        let mut grid = vec![vec![None; 7]; 6];
-        let mut current_day = 1;
-
-        for week in 0..6 {
-            for day in 0..7 {
-                if week == 0 && day < first_weekday {
-                    continue;
-                }
-                if current_day <= days_in_month {
-                    grid[week as usize][day as usize] = Some(current_day);
-                    current_day += 1;
-                }
-            }
+        .unwrap();
+        (local_midnight(first_day), local_midnight(next_first_day))
+    }
+
+    /// Number of rows in the displayed month's grid.
+    pub fn used_weeks(&self) -> usize {
+        self.get_month_grid().len()
+    }
+
+    /// The displayed month's weeks as dates, with `None` for days that fall
+    /// in the neighbouring months.
+    pub fn weeks(&self) -> impl Iterator<Item = [Option<NaiveDate>; 7]> {
+        let naive_date = self.current_date.naive_local().date();
+        self.weeks_for(naive_date.year(), naive_date.month())
+    }
+
+    /// One row per week the month touches, so between four and six rows, or
+    /// none for a month that does not exist.
+    pub fn weeks_for(&self, year: i32, month: u32) -> impl Iterator<Item = [Option<NaiveDate>; 7]> {
+        let first_day = NaiveDate::from_ymd_opt(year, month, 1);
+        let leading_days = first_day.map_or(0, |first_day| {
+            (first_day.weekday().num_days_from_sunday() + 7
+                - self.week_start.num_days_from_sunday())
+                % 7
+        });
+        let weeks = first_day.map_or(0, |_| {
+            (leading_days + days_in_month(year, month)).div_ceil(7)
+        });
+
+        (0..weeks).map(move |week| {
+            std::array::from_fn(|day| {
+                let offset = week * 7 + day as u32;
+                offset
+                    .checked_sub(leading_days)
+                    .and_then(|offset| first_day?.checked_add_days(Days::new(offset.into())))
+                    .filter(|date| date.month() == month)
+            })
+        })
+    }
+
+    pub fn get_month_grid(&self) -> Vec<Vec<Option<u32>>> {
+        let naive_date = self.current_date.naive_local().date();
+        self.get_month_grid_for(naive_date.year(), naive_date.month())
+    }
+
+    /// Day numbers of [`Calendar::weeks_for`], for callers that only need
+    /// to label a grid.
+    pub fn get_month_grid_for(&self, year: i32, month: u32) -> Vec<Vec<Option<u32>>> {
+        self.weeks_for(year, month)
+            .map(|week| {
+                week.iter()
+                    .map(|date| date.map(|date| date.day()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[deprecated(note = "use `move_selection` with a `Direction`")]
+    pub fn move_selection_str(&mut self, direction: &str) -> bool {
+        match direction {
+            "left" => self.move_selection(Direction::Left),
+            "right" => self.move_selection(Direction::Right),
+            "up" => self.move_selection(Direction::Up),
+            "down" => self.move_selection(Direction::Down),
+            _ => false,
         }
-        grid
     }
 
-    pub fn move_selection(&mut self, direction: &str) -> bool {
+    /// Moves the selection one cell within the displayed month's grid,
+    /// returning false when that would leave the month.
+    pub fn move_selection(&mut self, direction: Direction) -> bool {
         let current_grid = self.get_month_grid();
         let current_day = self.selected_date.day() as usize;
         let mut current_week = 0;
@@ -91,52 +265,40 @@ impl Calendar {
         }
 
         match direction {
-            "left" => {
-                if current_pos > 0 {
-                    if let Some(Some(new_day)) = current_grid
-                        .get(current_week)
-                        .map(|week| week.get(current_pos - 1))
-                        .flatten()
-                    {
-                        self.selected_date = self.selected_date.with_day(*new_day).unwrap();
-                        return true;
-                    }
+            Direction::Left if current_pos > 0 => {
+                if let Some(Some(new_day)) = current_grid
+                    .get(current_week)
+                    .and_then(|week| week.get(current_pos - 1))
+                {
+                    self.selected_date = self.safe_with_day(*new_day);
+                    return true;
                 }
             }
-            "right" => {
-                if current_pos < 6 {
-                    if let Some(Some(new_day)) = current_grid
-                        .get(current_week)
-                        .map(|week| week.get(current_pos + 1))
-                        .flatten()
-                    {
-                        self.selected_date = self.selected_date.with_day(*new_day).unwrap();
-                        return true;
-                    }
+            Direction::Right if current_pos < 6 => {
+                if let Some(Some(new_day)) = current_grid
+                    .get(current_week)
+                    .and_then(|week| week.get(current_pos + 1))
+                {
+                    self.selected_date = self.safe_with_day(*new_day);
+                    return true;
                 }
             }
-            "up" => {
-                if current_week > 0 {
-                    if let Some(Some(new_day)) = current_grid
-                        .get(current_week - 1)
-                        .map(|week| week.get(current_pos))
-                        .flatten()
-                    {
-                        self.selected_date = self.selected_date.with_day(*new_day).unwrap();
-                        return true;
-                    }
+            Direction::Up if current_week > 0 => {
+                if let Some(Some(new_day)) = current_grid
+                    .get(current_week - 1)
+                    .and_then(|week| week.get(current_pos))
+                {
+                    self.selected_date = self.safe_with_day(*new_day);
+                    return true;
                 }
             }
-            "down" => {
-                if current_week < 5 {
-                    if let Some(Some(new_day)) = current_grid
-                        .get(current_week + 1)
-                        .map(|week| week.get(current_pos))
-                        .flatten()
-                    {
-                        self.selected_date = self.selected_date.with_day(*new_day).unwrap();
-                        return true;
-                    }
+            Direction::Down if current_week + 1 < current_grid.len() => {
+                if let Some(Some(new_day)) = current_grid
+                    .get(current_week + 1)
+                    .and_then(|week| week.get(current_pos))
+                {
+                    self.selected_date = self.safe_with_day(*new_day);
+                    return true;
                 }
             }
             _ => {}
@@ -145,9 +307,121 @@ impl Calendar {
     }
 }
 
+/// Parses a date typed relative to `base`, falling back to `YYYY-MM-DD`:
+///
+/// - `today`, `tomorrow` and `yesterday`
+/// - a weekday such as `fri`, the first one on or after `base`
+/// - `next fri` and `last fri`, the first one strictly after or before
+///   `base`, so `next monday` on a Monday is a week later
+/// - `+3d`, `-1w` or `+2m`, a signed number of days, weeks or months
+pub fn parse_relative_date(input: &str, base: NaiveDate) -> Option<NaiveDate> {
+    let input = input.trim().to_ascii_lowercase();
+    match input.as_str() {
+        "today" => return Some(base),
+        "tomorrow" => return base.succ_opt(),
+        "yesterday" => return base.pred_opt(),
+        _ => {}
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        return offset_date(base, offset, false);
+    }
+    if let Some(offset) = input.strip_prefix('-') {
+        return offset_date(base, offset, true);
+    }
+
+    let (direction, weekday) = match input.split_once(' ') {
+        Some((direction, weekday)) => (Some(direction), weekday.trim()),
+        None => (None, input.as_str()),
+    };
+    if let Ok(weekday) = weekday.parse::<Weekday>() {
+        let ahead = i64::from(
+            (weekday.num_days_from_monday() + 7 - base.weekday().num_days_from_monday()) % 7,
+        );
+        return match direction {
+            None => Some(base + Duration::days(ahead)),
+            Some("next") => Some(base + Duration::days(if ahead == 0 { 7 } else { ahead })),
+            Some("last") => Some(base - Duration::days(if ahead == 0 { 7 } else { 7 - ahead })),
+            Some(_) => None,
+        };
+    }
+
+    NaiveDate::parse_from_str(&input, "%Y-%m-%d").ok()
+}
+
+/// Moves `base` by an offset such as `3d`, `1w` or `2m`.
+fn offset_date(base: NaiveDate, offset: &str, backwards: bool) -> Option<NaiveDate> {
+    let (unit_at, unit) = offset.char_indices().last()?;
+    let count: u32 = offset[..unit_at].parse().ok()?;
+    let days = match unit {
+        'd' => Duration::days(count.into()),
+        'w' => Duration::weeks(count.into()),
+        'm' if backwards => return base.checked_sub_months(Months::new(count)),
+        'm' => return base.checked_add_months(Months::new(count)),
+        _ => return None,
+    };
+    if backwards {
+        base.checked_sub_signed(days)
+    } else {
+        base.checked_add_signed(days)
+    }
+}
+
+/// Whether `date` falls within `MIN_YEAR..=MAX_YEAR`.
+fn is_supported(date: NaiveDate) -> bool {
+    (MIN_YEAR..=MAX_YEAR).contains(&date.year())
+}
+
+/// The nearest day to `date` within the supported years.
+fn clamp_date(date: NaiveDate) -> NaiveDate {
+    date.clamp(FIRST_SUPPORTED_DATE, LAST_SUPPORTED_DATE)
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+    let next_first_day = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .unwrap();
+    next_first_day.signed_duration_since(first_day).num_days() as u32
+}
+
+/// Midnight at the start of `date` in the local timezone.
+pub fn local_midnight(date: NaiveDate) -> DateTime<Local> {
+    midnight_in(date, &Local)
+}
+
+fn midnight_in<Tz: TimeZone>(date: NaiveDate, tz: &Tz) -> DateTime<Tz> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .unwrap_or_else(|| tz.from_utc_datetime(&midnight))
+}
+
+/// Midnight `n` calendar days after the day of `from`, in its zone, but no
+/// further than the first or last supported day. Going through the date
+/// rather than adding 24 hours keeps days that are 23 or 25 hours long
+/// around DST changes from being skipped or repeated.
+fn step_days<Tz: TimeZone>(from: &DateTime<Tz>, n: i64) -> DateTime<Tz> {
+    let target = Duration::try_days(n)
+        .and_then(|days| from.date_naive().checked_add_signed(days))
+        .map_or(
+            if n < 0 {
+                FIRST_SUPPORTED_DATE
+            } else {
+                LAST_SUPPORTED_DATE
+            },
+            clamp_date,
+        );
+    midnight_in(target, &from.timezone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{FixedOffset, NaiveTime};
 
     #[test]
     fn test_calendar_creation() {
@@ -173,4 +447,406 @@ mod tests {
         calendar.prev_month();
         assert_eq!(calendar.current_date.month(), initial_month);
     }
+
+    #[test]
+    fn test_go_to_today() {
+        let mut calendar = Calendar::new();
+        for _ in 0..3 {
+            calendar.next_month();
+        }
+        assert_ne!(calendar.current_date.month(), Local::now().month());
+
+        calendar.go_to_today();
+        let now = Local::now();
+        assert_eq!(calendar.current_date.month(), now.month());
+        assert_eq!(calendar.current_date.year(), now.year());
+        assert_eq!(calendar.selected_naive(), now.date_naive());
+    }
+
+    #[test]
+    fn test_go_to_date() {
+        let mut calendar = Calendar::new();
+        let target = NaiveDate::from_ymd_opt(2026, 12, 25).unwrap();
+
+        calendar.go_to_date(target);
+        assert_eq!(calendar.current_date.year(), 2026);
+        assert_eq!(calendar.current_date.month(), 12);
+        assert_eq!(calendar.selected_naive(), target);
+        assert_eq!(calendar.current_naive(), target);
+        assert_eq!(
+            calendar.selected_naive(),
+            calendar.selected_date.date_naive()
+        );
+    }
+
+    #[test]
+    fn test_select_first_and_last_day() {
+        let mut calendar = Calendar::new();
+        for (year, last) in [(2024, 29), (2025, 28), (2100, 28), (2000, 29)] {
+            calendar.go_to_date(NaiveDate::from_ymd_opt(year, 2, 10).unwrap());
+            calendar.select_last_day();
+            assert_eq!(
+                calendar.selected_naive(),
+                NaiveDate::from_ymd_opt(year, 2, last).unwrap()
+            );
+            calendar.select_first_day();
+            assert_eq!(
+                calendar.selected_naive(),
+                NaiveDate::from_ymd_opt(year, 2, 1).unwrap()
+            );
+            assert_eq!(calendar.current_date.month(), 2);
+        }
+
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 12, 3).unwrap());
+        calendar.select_last_day();
+        assert_eq!(calendar.selected_date.day(), 31);
+    }
+
+    #[test]
+    fn test_week_start() {
+        // January 2025 begins on a Wednesday
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+
+        let grid = calendar.get_month_grid();
+        assert_eq!(grid[0].iter().position(|d| *d == Some(1)), Some(3));
+        assert_eq!(calendar.weekdays()[0], Weekday::Sun);
+
+        calendar.set_week_start(Weekday::Mon);
+        let grid = calendar.get_month_grid();
+        assert_eq!(grid[0].iter().position(|d| *d == Some(1)), Some(2));
+        assert_eq!(calendar.weekdays()[0], Weekday::Mon);
+        assert_eq!(calendar.weekdays()[6], Weekday::Sun);
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2024, 12, 10).unwrap());
+
+        let (start, end) = calendar.month_bounds();
+        assert_eq!(
+            start.date_naive(),
+            NaiveDate::from_ymd_opt(2024, 12, 1).unwrap()
+        );
+        assert_eq!(
+            end.date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_year_navigation() {
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 6, 15).unwrap());
+
+        calendar.next_year();
+        assert_eq!(calendar.current_date.year(), 2026);
+        assert_eq!(calendar.current_date.month(), 6);
+
+        calendar.prev_year();
+        calendar.prev_year();
+        assert_eq!(calendar.current_date.year(), 2024);
+        assert_eq!(calendar.current_date.month(), 6);
+    }
+
+    #[test]
+    fn test_advance_days_across_months() {
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+        calendar.advance_days(1);
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()
+        );
+        assert_eq!(calendar.current_date.month(), 2);
+
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap());
+        calendar.advance_days(-1);
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+        );
+        assert_eq!(calendar.current_date.month(), 2);
+
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2024, 12, 28).unwrap());
+        calendar.advance_days(7);
+        assert_eq!(calendar.current_date.year(), 2025);
+        assert_eq!(calendar.current_date.month(), 1);
+    }
+
+    #[test]
+    fn test_month_navigation_clamps_selected_day() {
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 1, 31).unwrap());
+
+        calendar.next_month();
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+        );
+        assert!(calendar.move_selection(Direction::Up));
+        assert_eq!(calendar.selected_date.day(), 21);
+
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap());
+        calendar.next_month();
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+
+        // Feb 29 has no counterpart in the following year
+        calendar.next_year();
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 2, 28).unwrap()
+        );
+
+        assert_eq!(calendar.safe_with_day(0).day(), 1);
+    }
+
+    #[test]
+    fn test_move_selection_with_direction() {
+        // March 2025 starts on a Saturday, in the last column of a Sunday-first grid
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 3, 1).unwrap());
+
+        assert!(!calendar.move_selection(Direction::Left));
+        assert!(!calendar.move_selection(Direction::Right));
+        assert!(!calendar.move_selection(Direction::Up));
+        assert!(calendar.move_selection(Direction::Down));
+        assert_eq!(calendar.selected_date.day(), 8);
+        assert!(calendar.move_selection(Direction::Left));
+        assert_eq!(calendar.selected_date.day(), 7);
+        assert!(!calendar.move_selection(Direction::Up));
+        assert!(calendar.move_selection(Direction::Right));
+        assert_eq!(calendar.selected_date.day(), 8);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_move_selection_str_wrapper() {
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 3, 12).unwrap());
+
+        assert!(calendar.move_selection_str("down"));
+        assert_eq!(calendar.selected_date.day(), 19);
+        assert!(!calendar.move_selection_str("sideways"));
+        assert_eq!(calendar.selected_date.day(), 19);
+    }
+
+    #[test]
+    fn test_weeks_match_month_grid() {
+        let mut calendar = Calendar::new();
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2024, 2, 10).unwrap());
+
+        let weeks: Vec<_> = calendar.weeks().collect();
+        let grid = calendar.get_month_grid();
+        assert_eq!(weeks.len(), grid.len());
+        for (week, row) in weeks.iter().zip(&grid) {
+            for (date, day) in week.iter().zip(row) {
+                assert_eq!(date.map(|date| date.day()), *day);
+                assert!(date.is_none_or(|date| date.year() == 2024 && date.month() == 2));
+            }
+        }
+        // February 1st 2024 is a Thursday, the fourth column from Monday
+        assert_eq!(weeks[0][3], NaiveDate::from_ymd_opt(2024, 2, 1));
+        assert_eq!(weeks[4][3], NaiveDate::from_ymd_opt(2024, 2, 29));
+        assert_eq!(calendar.weeks_for(2024, 13).count(), 0);
+    }
+
+    #[test]
+    fn test_month_grid_has_only_needed_weeks() {
+        let calendar = Calendar::new();
+        // February 2015 starts on a Sunday and fills exactly four weeks
+        assert_eq!(calendar.get_month_grid_for(2015, 2).len(), 4);
+        // April 2025 starts on a Tuesday
+        assert_eq!(calendar.get_month_grid_for(2025, 4).len(), 5);
+        // March 2025 starts on a Saturday and spills into a sixth week
+        let grid = calendar.get_month_grid_for(2025, 3);
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid[5][1], Some(31));
+        assert!(grid.iter().all(|week| week.iter().any(Option::is_some)));
+
+        // The same month can need a different number of rows per week start
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2015, 2, 10).unwrap());
+        assert_eq!(calendar.used_weeks(), 4);
+        calendar.set_week_start(Weekday::Mon);
+        assert_eq!(calendar.used_weeks(), 5);
+    }
+
+    #[test]
+    fn test_step_days_by_calendar_day() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        // New York before and after the change on November 2nd 2025
+        let daylight = FixedOffset::west_opt(4 * 3600).unwrap();
+        let standard = FixedOffset::west_opt(5 * 3600).unwrap();
+        for zone in [daylight, standard] {
+            // Late in the evening, where adding 24 hours to a 25 hour day
+            // would land on the same date in the other offset
+            let evening = zone
+                .from_local_datetime(&date(2025, 11, 2).and_hms_opt(23, 30, 0).unwrap())
+                .unwrap();
+            for (n, expected) in [
+                (1, date(2025, 11, 3)),
+                (-1, date(2025, 11, 1)),
+                (0, date(2025, 11, 2)),
+            ] {
+                let stepped = step_days(&evening, n);
+                assert_eq!(stepped.date_naive(), expected, "{n}");
+                assert_eq!(stepped.time(), NaiveTime::MIN);
+                assert_eq!(*stepped.offset(), zone);
+            }
+        }
+
+        let morning = standard
+            .from_local_datetime(&date(2025, 3, 8).and_hms_opt(0, 0, 0).unwrap())
+            .unwrap();
+        assert_eq!(step_days(&morning, 2).date_naive(), date(2025, 3, 10));
+        assert_eq!(step_days(&morning, -7).date_naive(), date(2025, 3, 1));
+        assert_eq!(
+            step_days(&morning, i64::MAX).date_naive(),
+            LAST_SUPPORTED_DATE
+        );
+        assert_eq!(
+            step_days(&morning, i64::MIN).date_naive(),
+            FIRST_SUPPORTED_DATE
+        );
+    }
+
+    #[test]
+    fn test_select_weekday_in_current_week() {
+        let mut calendar = Calendar::new();
+        // Tuesday, April 1st 2025
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+        calendar.select_weekday_in_current_week(Weekday::Fri);
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 4, 4).unwrap()
+        );
+
+        // With weeks starting on Sunday, Sunday is behind the selection
+        calendar.select_weekday_in_current_week(Weekday::Sun);
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap()
+        );
+        assert_eq!(calendar.current_naive().month(), 3);
+
+        // and with weeks starting on Monday, it is ahead of it
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 4, 1).unwrap());
+        calendar.select_weekday_in_current_week(Weekday::Sun);
+        assert_eq!(
+            calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 4, 6).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_selected_week_spans_month_boundary() {
+        let mut calendar = Calendar::new();
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 4, 2).unwrap());
+
+        let week = calendar.selected_week();
+        assert_eq!(week[0], NaiveDate::from_ymd_opt(2025, 3, 31).unwrap());
+        assert_eq!(week[6], NaiveDate::from_ymd_opt(2025, 4, 6).unwrap());
+        assert!(week
+            .iter()
+            .all(|day| day.weekday() != Weekday::Mon || *day == week[0]));
+
+        calendar.set_week_start(Weekday::Sun);
+        assert_eq!(
+            calendar.selected_week()[0],
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_relative_date() {
+        // A Wednesday
+        let base = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day);
+        assert_eq!(parse_relative_date("today", base), Some(base));
+        assert_eq!(parse_relative_date(" Tomorrow ", base), date(3, 13));
+        assert_eq!(parse_relative_date("yesterday", base), date(3, 11));
+        assert_eq!(parse_relative_date("fri", base), date(3, 14));
+        assert_eq!(parse_relative_date("wednesday", base), Some(base));
+        assert_eq!(parse_relative_date("next friday", base), date(3, 14));
+        assert_eq!(parse_relative_date("next wed", base), date(3, 19));
+        assert_eq!(parse_relative_date("last monday", base), date(3, 10));
+        assert_eq!(parse_relative_date("last wed", base), date(3, 5));
+        assert_eq!(parse_relative_date("+3d", base), date(3, 15));
+        assert_eq!(parse_relative_date("-1w", base), date(3, 5));
+        assert_eq!(parse_relative_date("+2m", base), date(5, 12));
+        assert_eq!(
+            parse_relative_date("-3m", base),
+            NaiveDate::from_ymd_opt(2024, 12, 12)
+        );
+        assert_eq!(parse_relative_date("2025-07-04", base), date(7, 4));
+    }
+
+    #[test]
+    fn test_next_weekday_on_that_weekday_is_a_week_later() {
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        assert_eq!(
+            parse_relative_date("next monday", monday),
+            NaiveDate::from_ymd_opt(2025, 3, 17)
+        );
+        assert_eq!(parse_relative_date("monday", monday), Some(monday));
+    }
+
+    #[test]
+    fn test_parse_relative_date_rejects_nonsense() {
+        let base = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        for input in [
+            "",
+            "+",
+            "+d",
+            "+3x",
+            "-w",
+            "soon monday",
+            "someday",
+            "14/03/2025",
+            "+3é",
+            "+1日",
+            "-日",
+            "+é",
+        ] {
+            assert_eq!(parse_relative_date(input, base), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_navigation_stops_at_supported_years() {
+        let mut calendar = Calendar::new();
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+
+        calendar.go_to_date(date(MAX_YEAR, 12, 15));
+        calendar.next_month();
+        calendar.next_year();
+        assert_eq!(calendar.selected_naive(), date(MAX_YEAR, 12, 15));
+        calendar.advance_days(30);
+        assert_eq!(calendar.selected_naive(), date(MAX_YEAR, 12, 31));
+        calendar.advance_days(i64::MAX);
+        assert_eq!(calendar.selected_naive(), date(MAX_YEAR, 12, 31));
+        calendar.prev_year();
+        assert_eq!(calendar.selected_naive(), date(MAX_YEAR - 1, 12, 31));
+
+        calendar.go_to_date(date(0, 6, 1));
+        assert_eq!(calendar.selected_naive(), date(MIN_YEAR, 1, 1));
+        calendar.prev_month();
+        calendar.prev_year();
+        calendar.advance_days(-1);
+        calendar.advance_days(i64::MIN);
+        assert_eq!(calendar.selected_naive(), date(MIN_YEAR, 1, 1));
+        assert_eq!(calendar.current_naive(), date(MIN_YEAR, 1, 1));
+
+        calendar.go_to_date(NaiveDate::MAX);
+        assert_eq!(calendar.selected_naive(), date(MAX_YEAR, 12, 31));
+        assert!(calendar.get_month_grid_for(2025, 13).is_empty());
+    }
 }
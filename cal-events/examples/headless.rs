@@ -0,0 +1,35 @@
+//! Drives the calendar and event store without the TUI:
+//! `cargo run -p cal-events --example headless`
+
+use anyhow::Result;
+use cal_core::{local_midnight, Calendar, Direction};
+use cal_events::{parse_quick_event, Event, EventManager};
+use chrono::{Duration, NaiveDate};
+
+fn main() -> Result<()> {
+    let mut calendar = Calendar::new();
+    calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 3, 12).unwrap());
+    calendar.move_selection(Direction::Down);
+    let day = calendar.selected_naive();
+
+    let mut events = EventManager::new();
+    events.add_event(parse_quick_event("Dentist 15:00 1h", day)?)?;
+    let start = local_midnight(day) + Duration::hours(9);
+    events.add_event(Event::new(
+        "Standup".to_string(),
+        None,
+        start,
+        start + Duration::minutes(15),
+    )?)?;
+
+    println!("{}", day.format("%A, %B %d"));
+    for event in events.list_events_for_day(calendar.selected_date) {
+        println!(
+            "  {} - {}  {}",
+            event.start_time.format("%H:%M"),
+            event.end_time.format("%H:%M"),
+            event.title
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,270 @@
+use crate::{Event, EventStatus, Priority, Recurrence, DEFAULT_CALENDAR};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration, FixedOffset, TimeZone};
+use uuid::Uuid;
+
+/// Builds an [`Event`] one field at a time. Only the title and the start and
+/// end times are required; everything else defaults as in [`Event::new`].
+///
+/// ```
+/// use cal_events::EventBuilder;
+/// use chrono::{Duration, Local};
+///
+/// let start = Local::now();
+/// let event = EventBuilder::new()
+///     .title("Retro")
+///     .start(start)
+///     .end(start + Duration::hours(1))
+///     .category("work")
+///     .build()
+///     .unwrap();
+/// assert_eq!(event.category.as_deref(), Some("work"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    title: String,
+    description: Option<String>,
+    start_time: Option<DateTime<FixedOffset>>,
+    end_time: Option<DateTime<FixedOffset>>,
+    reminder: Option<Duration>,
+    priority: Priority,
+    status: EventStatus,
+    attendees: Vec<String>,
+    location: Option<String>,
+    category: Option<String>,
+    tags: Vec<String>,
+    calendar: String,
+    recurrence: Option<Recurrence>,
+}
+
+impl Default for EventBuilder {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            description: None,
+            start_time: None,
+            end_time: None,
+            reminder: None,
+            priority: Priority::default(),
+            status: EventStatus::default(),
+            attendees: Vec::new(),
+            location: None,
+            category: None,
+            tags: Vec::new(),
+            calendar: DEFAULT_CALENDAR.to_string(),
+            recurrence: None,
+        }
+    }
+}
+
+impl EventBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Accepts a time in any zone, pinned to its offset as in [`Event::new`].
+    pub fn start<Tz: TimeZone>(mut self, start_time: DateTime<Tz>) -> Self {
+        self.start_time = Some(start_time.fixed_offset());
+        self
+    }
+
+    pub fn end<Tz: TimeZone>(mut self, end_time: DateTime<Tz>) -> Self {
+        self.end_time = Some(end_time.fixed_offset());
+        self
+    }
+
+    pub fn reminder(mut self, before_start: Duration) -> Self {
+        self.reminder = Some(before_start);
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn status(mut self, status: EventStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn attendee(mut self, attendee: impl Into<String>) -> Self {
+        self.attendees.push(attendee.into());
+        self
+    }
+
+    pub fn location(mut self, location: impl Into<String>) -> Self {
+        self.location = Some(location.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn calendar(mut self, calendar: impl Into<String>) -> Self {
+        self.calendar = calendar.into();
+        self
+    }
+
+    pub fn recurrence(mut self, recurrence: Recurrence) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    /// Trims the title. Fails if either time is missing, the event does not
+    /// end after it starts or the title is blank.
+    pub fn build(self) -> Result<Event> {
+        let start_time = self
+            .start_time
+            .ok_or_else(|| anyhow!("Start time is required"))?;
+        let end_time = self
+            .end_time
+            .ok_or_else(|| anyhow!("End time is required"))?;
+        if end_time <= start_time {
+            return Err(anyhow!("End time must be after start time"));
+        }
+        let title = self.title.trim();
+        if title.is_empty() {
+            return Err(anyhow!("Title must not be empty"));
+        }
+
+        Ok(Event {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: self.description,
+            start_time,
+            end_time,
+            reminder: self.reminder,
+            priority: self.priority,
+            status: self.status,
+            attendees: self.attendees,
+            location: self.location,
+            category: self.category,
+            tags: self.tags,
+            calendar: self.calendar,
+            recurrence: self.recurrence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Frequency;
+    use chrono::Local;
+
+    #[test]
+    fn test_build_validates_times() {
+        let start = Local::now();
+        assert!(EventBuilder::new().title("No times").build().is_err());
+        assert!(EventBuilder::new().title("T").start(start).build().is_err());
+        assert!(EventBuilder::new().title("T").end(start).build().is_err());
+        assert_eq!(
+            EventBuilder::new()
+                .title("T")
+                .start(start)
+                .end(start)
+                .build()
+                .unwrap_err()
+                .to_string(),
+            "End time must be after start time"
+        );
+        assert!(EventBuilder::new()
+            .title("T")
+            .start(start)
+            .end(start - Duration::minutes(1))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_build_defaults_and_setters() {
+        let start = Local::now();
+        let end = start + Duration::hours(1);
+
+        let plain = EventBuilder::new()
+            .title("Standup")
+            .start(start)
+            .end(end)
+            .build()
+            .unwrap();
+        let expected = Event::new("Standup".to_string(), None, start, end).unwrap();
+        assert_eq!(plain.status, EventStatus::Confirmed);
+        assert_eq!(plain.calendar, DEFAULT_CALENDAR);
+        assert_eq!(
+            plain,
+            Event {
+                id: plain.id,
+                ..expected
+            }
+        );
+
+        let full = EventBuilder::new()
+            .title("Offsite")
+            .description("Planning")
+            .start(start)
+            .end(end)
+            .reminder(Duration::minutes(10))
+            .priority(Priority::High)
+            .status(EventStatus::Tentative)
+            .attendee("ana@example.com")
+            .attendee("bo@example.com")
+            .location("Lisbon")
+            .category("work")
+            .tag("offsite")
+            .tag("travel")
+            .recurrence(Recurrence::new(Frequency::Weekly))
+            .build()
+            .unwrap();
+        assert_eq!(full.description.as_deref(), Some("Planning"));
+        assert_eq!(full.reminder, Some(Duration::minutes(10)));
+        assert_eq!(full.priority, Priority::High);
+        assert_eq!(full.status, EventStatus::Tentative);
+        assert_eq!(full.attendees, ["ana@example.com", "bo@example.com"]);
+        assert_eq!(full.location.as_deref(), Some("Lisbon"));
+        assert_eq!(full.category.as_deref(), Some("work"));
+        assert_eq!(full.tags, ["offsite", "travel"]);
+        assert_eq!(full.recurrence, Some(Recurrence::new(Frequency::Weekly)));
+    }
+
+    #[test]
+    fn test_title_is_trimmed_and_required() {
+        let start = Local::now();
+        let end = start + Duration::hours(1);
+        for title in ["", "   ", "\t\n"] {
+            assert_eq!(
+                Event::new(title.to_string(), None, start, end)
+                    .unwrap_err()
+                    .to_string(),
+                "Title must not be empty",
+                "{title:?}"
+            );
+        }
+        let event = Event::new("  Meeting  ".to_string(), None, start, end).unwrap();
+        assert_eq!(event.title, "Meeting");
+        let event = EventBuilder::new()
+            .title(" Retro\t")
+            .start(start)
+            .end(end)
+            .build()
+            .unwrap();
+        assert_eq!(event.title, "Retro");
+    }
+}
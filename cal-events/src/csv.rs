@@ -0,0 +1,73 @@
+use crate::EventManager;
+
+impl EventManager {
+    pub fn export_csv(&self) -> String {
+        let events = self.list_events();
+
+        let mut csv = String::from("id,title,description,start,end,calendar\r\n");
+        for event in events {
+            let fields = [
+                event.id.to_string(),
+                event.title.clone(),
+                event.description.clone().unwrap_or_default(),
+                event.start_time.to_rfc3339(),
+                event.end_time.to_rfc3339(),
+                event.calendar.clone(),
+            ];
+            let row: Vec<String> = fields.iter().map(|field| quote_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push_str("\r\n");
+        }
+        csv
+    }
+}
+
+/// Quotes a field as described in RFC 4180 when it contains a delimiter,
+/// quote or line break.
+fn quote_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Event;
+    use chrono::{DateTime, Local};
+
+    #[test]
+    fn test_export_csv_roundtrip() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let end = start + chrono::Duration::hours(1);
+        let event = Event::new(
+            "Lunch, \"the usual\"".to_string(),
+            Some("Bring notes\nand laptop".to_string()),
+            start,
+            end,
+        )
+        .unwrap();
+        manager.add_event(event.clone()).unwrap();
+
+        let csv = manager.export_csv();
+        let mut reader = ::csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(
+            reader.headers().unwrap(),
+            vec!["id", "title", "description", "start", "end", "calendar"]
+        );
+
+        let records: Vec<::csv::StringRecord> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(&records[0][0], event.id.to_string());
+        assert_eq!(&records[0][1], "Lunch, \"the usual\"");
+        assert_eq!(&records[0][2], "Bring notes\nand laptop");
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&records[0][3]).unwrap(),
+            event.start_time
+        );
+        assert_eq!(&records[0][5], "personal");
+    }
+}
@@ -0,0 +1,1126 @@
+use crate::{Event, EventManager, EventStatus, Frequency, Operation, Priority, Recurrence};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use std::fmt;
+use uuid::Uuid;
+
+const ICS_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const ICS_LOCAL_DATETIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+/// Title given to imported events without a summary.
+const UNTITLED: &str = "Untitled";
+const ICS_DATE_FORMAT: &str = "%Y%m%d";
+/// Longest content line allowed by RFC 5545 section 3.1, in octets.
+const ICS_LINE_OCTETS: usize = 75;
+const ICS_WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+/// What [`EventManager::import_ics`] did with each VEVENT of a file,
+/// [`EventManager::import_plain`] with each line, or [`EventManager::merge`]
+/// with each event of another manager.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Events with a UID not seen before.
+    pub added: usize,
+    /// Events that changed the one with their UID. From .ics files only the
+    /// fields a VEVENT gives are changed.
+    pub updated: usize,
+    /// Events left alone because the one with their UID already matched.
+    pub skipped: usize,
+    /// Why each VEVENT or line that could not be read was left out.
+    pub errors: Vec<String>,
+}
+
+impl ImportReport {
+    /// Whether any event was added or changed.
+    pub fn changed(&self) -> bool {
+        self.added + self.updated > 0
+    }
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} updated, {} skipped, {} failed",
+            self.added,
+            self.updated,
+            self.skipped,
+            self.errors.len()
+        )
+    }
+}
+
+impl EventManager {
+    pub fn export_ics(&self) -> String {
+        let events = self.list_events();
+
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//cal-rs//cal-rs//EN".to_string(),
+        ];
+        let stamp = format_ics_datetime(Local::now());
+        for event in events {
+            lines.extend(vevent_lines(event, &stamp, &self.exceptions_for(event.id)));
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        // iCalendar requires CRLF line endings, including after the last line
+        lines
+            .iter()
+            .map(|line| format!("{}\r\n", fold_line(line)))
+            .collect()
+    }
+
+    /// Imports every VEVENT in `content` that can be read, reporting what
+    /// happened to each one. Each added or changed event can be undone on
+    /// its own.
+    ///
+    /// A VEVENT whose UID matches an existing event updates only the fields
+    /// it gives, keeping the rest such as the category, reminder and
+    /// recurrence, so importing a file twice changes nothing the second time.
+    /// Occurrences named by EXDATE are hidden as if deleted.
+    ///
+    /// Times with a TZID other than UTC are rejected, as they cannot be
+    /// converted without a time zone database.
+    pub fn import_ics(&mut self, content: &str) -> ImportReport {
+        let mut report = ImportReport::default();
+        for (index, properties) in parse_vevents(content).into_iter().enumerate() {
+            let read = properties.and_then(|properties| {
+                let existing = vevent_id(&properties).and_then(|id| self.events.get(&id));
+                vevent_from_properties(&properties, existing)
+                    .with_context(|| format!("Invalid VEVENT #{}", index + 1))
+            });
+            let (event, excluded) = match read {
+                Ok(read) => read,
+                Err(err) => {
+                    report.errors.push(format!("{err:#}"));
+                    continue;
+                }
+            };
+            let exceptions: Vec<(Uuid, NaiveDate)> = excluded
+                .into_iter()
+                .map(|date| (event.id, date))
+                .filter(|exception| !self.exceptions.contains(exception))
+                .collect();
+            self.exceptions.extend(exceptions.iter().copied());
+            match self.events.get(&event.id) {
+                Some(existing) if *existing == event && exceptions.is_empty() => {
+                    report.skipped += 1
+                }
+                Some(_) => {
+                    if let Some(previous) = self.events.insert(event.id, event) {
+                        self.record(Operation::Edit(previous));
+                    }
+                    report.updated += 1;
+                }
+                None => {
+                    self.record(Operation::Add(event.clone()));
+                    self.events.insert(event.id, event);
+                    report.added += 1;
+                }
+            }
+        }
+        report
+    }
+}
+
+/// The properties of every VEVENT in `content`, or why it could not be read.
+fn parse_vevents(content: &str) -> Vec<Result<Vec<(String, String)>>> {
+    let mut events = Vec::new();
+    let mut properties: Option<Vec<(String, String)>> = None;
+
+    // A time zone the current VEVENT uses that cannot be honoured
+    let mut unsupported_zone: Option<String> = None;
+
+    for line in unfold_lines(content) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Drop parameters such as `DTSTART;VALUE=DATE`
+        let mut parts = name.split(';');
+        let name = parts.next().unwrap_or(name).to_ascii_uppercase();
+        let zone = parts
+            .clone()
+            .find_map(|param| param.strip_prefix("TZID="))
+            .map(|zone| zone.trim_matches('"'));
+        let value = match zone {
+            _ if name == "ATTENDEE" => attendee_from_ics(parts, value),
+            Some("UTC" | "Etc/UTC" | "GMT" | "Etc/GMT") => times_in_utc(value),
+            Some(zone) => {
+                unsupported_zone.get_or_insert_with(|| zone.to_string());
+                value.to_string()
+            }
+            None => value.to_string(),
+        };
+
+        match (name.as_str(), properties.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                properties = Some(Vec::new());
+                unsupported_zone = None;
+            }
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let number = events.len() + 1;
+                let zone = unsupported_zone.take();
+                events.extend(properties.take().map(|props| match zone {
+                    Some(zone) => Err(anyhow!(
+                        "Invalid VEVENT #{number}: time zone '{zone}' is not supported, use UTC times"
+                    )),
+                    None => Ok(props),
+                }));
+            }
+            (_, Some(props)) => props.push((name, value)),
+            _ => {}
+        }
+    }
+
+    if properties.is_some() {
+        events.push(Err(anyhow!("Unterminated VEVENT #{}", events.len() + 1)));
+    }
+    events
+}
+
+/// The id in a VEVENT's UID. UIDs produced by other applications need not
+/// be UUIDs; those events get a fresh id.
+fn vevent_id(properties: &[(String, String)]) -> Option<Uuid> {
+    properties
+        .iter()
+        .find(|(key, _)| key == "UID")
+        .and_then(|(_, uid)| Uuid::parse_str(uid).ok())
+}
+
+/// A new event read from a VEVENT, or `existing` with the fields the
+/// VEVENT gives replaced, along with the dates its EXDATEs leave out.
+fn vevent_from_properties(
+    properties: &[(String, String)],
+    existing: Option<&Event>,
+) -> Result<(Event, Vec<NaiveDate>)> {
+    let get = |name: &str| {
+        properties
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let has = |name: &str| properties.iter().any(|(key, _)| key == name);
+
+    let start = parse_ics_datetime(get("DTSTART").ok_or_else(|| anyhow!("Missing DTSTART"))?)
+        .context("Invalid DTSTART")?;
+    let end = parse_ics_datetime(get("DTEND").ok_or_else(|| anyhow!("Missing DTEND"))?)
+        .context("Invalid DTEND")?;
+    // SUMMARY is optional in RFC 5545, but events need a title here
+    let title = get("SUMMARY")
+        .map(unescape_text)
+        .filter(|title| !title.trim().is_empty());
+    let description = get("DESCRIPTION").map(unescape_text);
+
+    let mut event = match existing {
+        Some(existing) => {
+            // Times stay in the event's own zone
+            let offset = *existing.start_time.offset();
+            let read = Event::new(
+                title.unwrap_or_else(|| existing.title.clone()),
+                description.or_else(|| existing.description.clone()),
+                start.with_timezone(&offset),
+                end.with_timezone(&offset),
+            )?;
+            Event {
+                title: read.title,
+                description: read.description,
+                start_time: read.start_time,
+                end_time: read.end_time,
+                ..existing.clone()
+            }
+        }
+        None => {
+            let mut event = Event::new(
+                title.unwrap_or_else(|| UNTITLED.to_string()),
+                description,
+                start,
+                end,
+            )?;
+            if let Some(id) = vevent_id(properties) {
+                event.id = id;
+            }
+            event
+        }
+    };
+    if let Some(location) = get("LOCATION") {
+        event.location = Some(unescape_text(location));
+    }
+    if let Some(calendar) = get("X-CAL-RS-CALENDAR") {
+        event.calendar = unescape_text(calendar);
+    }
+    if let Some(status) = get("STATUS") {
+        event.status = match status.trim() {
+            "TENTATIVE" => EventStatus::Tentative,
+            "CANCELLED" => EventStatus::Cancelled,
+            _ => EventStatus::Confirmed,
+        };
+    }
+    // RFC 5545 ranks 1-4 as high and 6-9 as low; 0 and 5 mean no particular priority
+    if let Some(priority) = get("PRIORITY") {
+        event.priority = match priority.trim().parse::<u8>() {
+            Ok(1..=4) => Priority::High,
+            Ok(6..=9) => Priority::Low,
+            _ => Priority::Normal,
+        };
+    }
+    if has("ATTENDEE") {
+        event.attendees = properties
+            .iter()
+            .filter(|(key, _)| key == "ATTENDEE")
+            .map(|(_, value)| value.clone())
+            .collect();
+    }
+    if has("CATEGORIES") {
+        event.tags = properties
+            .iter()
+            .filter(|(key, _)| key == "CATEGORIES")
+            .flat_map(|(_, value)| split_text_list(value))
+            .filter(|tag| !tag.trim().is_empty())
+            .collect();
+    }
+    if let Some(rule) = get("RRULE") {
+        event.recurrence = Some(parse_rrule(rule, &event).context("Invalid RRULE")?);
+    }
+    let offset = *event.start_time.offset();
+    let excluded = properties
+        .iter()
+        .filter(|(key, _)| key == "EXDATE")
+        .flat_map(|(_, value)| value.split(','))
+        .map(|value| ics_date_in(value, &offset))
+        .collect::<Result<Vec<_>>>()
+        .context("Invalid EXDATE")?;
+    Ok((event, excluded))
+}
+
+/// The recurrence an RRULE describes for `event`. Only the parts a
+/// [`Recurrence`] can hold are accepted; COUNT becomes the date of the last
+/// occurrence.
+fn parse_rrule(rule: &str, event: &Event) -> Result<Recurrence> {
+    let mut frequency = None;
+    let mut recurrence = Recurrence::new(Frequency::Daily);
+    let mut count = None;
+    for part in rule.trim().split(';') {
+        let (name, value) = part
+            .split_once('=')
+            .ok_or_else(|| anyhow!("'{}' is not NAME=VALUE", part))?;
+        match name.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    _ => bail!("FREQ={} is not supported", value),
+                })
+            }
+            "INTERVAL" => {
+                recurrence.interval = value
+                    .parse()
+                    .ok()
+                    .filter(|interval| *interval > 0)
+                    .ok_or_else(|| anyhow!("Invalid INTERVAL '{}'", value))?
+            }
+            "BYDAY" => {
+                recurrence.weekdays = value
+                    .split(',')
+                    .map(|day| {
+                        ICS_WEEKDAYS
+                            .iter()
+                            .find(|(name, _)| day.eq_ignore_ascii_case(name))
+                            .map(|(_, weekday)| *weekday)
+                            .ok_or_else(|| anyhow!("BYDAY={} is not supported", day))
+                    })
+                    .collect::<Result<_>>()?
+            }
+            "UNTIL" => {
+                recurrence.until =
+                    Some(ics_date_in(value, event.start_time.offset()).context("Invalid UNTIL")?)
+            }
+            "COUNT" => {
+                count = Some(
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|count| *count > 0)
+                        .ok_or_else(|| anyhow!("Invalid COUNT '{}'", value))?,
+                )
+            }
+            // Weeks are only counted from the first occurrence
+            "WKST" => {}
+            _ => bail!("{} is not supported", name),
+        }
+    }
+    recurrence.frequency = frequency.ok_or_else(|| anyhow!("Missing FREQ"))?;
+    if !recurrence.weekdays.is_empty() && recurrence.frequency != Frequency::Weekly {
+        bail!("BYDAY is only supported with FREQ=WEEKLY");
+    }
+    if let Some(count) = count {
+        let last = recurrence
+            .dates(event.start_time.date_naive())
+            .nth(count - 1);
+        recurrence.until = last;
+    }
+    Ok(recurrence)
+}
+
+/// The RRULE for `recurrence`. UNTIL is a UTC time like DTSTART, as RFC
+/// 5545 requires, so it names the last occurrence's start.
+fn rrule_line(event: &Event, recurrence: &Recurrence) -> String {
+    let frequency = match recurrence.frequency {
+        Frequency::Daily => "DAILY",
+        Frequency::Weekly => "WEEKLY",
+        Frequency::Monthly => "MONTHLY",
+    };
+    let mut rule = format!(
+        "RRULE:FREQ={};INTERVAL={}",
+        frequency,
+        recurrence.interval.max(1)
+    );
+    if recurrence.frequency == Frequency::Weekly && !recurrence.weekdays.is_empty() {
+        let days: Vec<&str> = recurrence
+            .weekdays
+            .iter()
+            .filter_map(|weekday| {
+                ICS_WEEKDAYS
+                    .iter()
+                    .find(|(_, day)| day == weekday)
+                    .map(|(name, _)| *name)
+            })
+            .collect();
+        rule.push_str(&format!(";BYDAY={}", days.join(",")));
+    }
+    if let Some(until) = recurrence
+        .until
+        .and_then(|until| occurrence_start(event, until))
+    {
+        rule.push_str(&format!(";UNTIL={}", format_ics_datetime(until)));
+    }
+    rule
+}
+
+/// When the occurrence of `event` on `date` starts, in the event's zone.
+fn occurrence_start(event: &Event, date: NaiveDate) -> Option<DateTime<FixedOffset>> {
+    event
+        .start_time
+        .offset()
+        .from_local_datetime(&date.and_time(event.start_time.time()))
+        .single()
+}
+
+/// The date a DATE or DATE-TIME value falls on in `offset`.
+fn ics_date_in(value: &str, offset: &FixedOffset) -> Result<NaiveDate> {
+    let value = value.trim();
+    match NaiveDate::parse_from_str(value, ICS_DATE_FORMAT) {
+        Ok(date) => Ok(date),
+        Err(_) => Ok(parse_ics_datetime(value)?
+            .with_timezone(offset)
+            .date_naive()),
+    }
+}
+
+/// Marks the DATE-TIME values of a property given with a UTC TZID as UTC.
+fn times_in_utc(value: &str) -> String {
+    value
+        .split(',')
+        .map(|time| {
+            if time.contains('T') && !time.ends_with('Z') {
+                format!("{time}Z")
+            } else {
+                time.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The attendee's email address, or their common name when there is none.
+fn attendee_from_ics<'a>(mut params: impl Iterator<Item = &'a str>, value: &str) -> String {
+    if let Some(address) = value
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("mailto:"))
+        .map(|_| &value[7..])
+    {
+        return address.to_string();
+    }
+    params
+        .find_map(|param| param.strip_prefix("CN="))
+        .map(|name| name.trim_matches('"').to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+fn attendee_to_ics(attendee: &str) -> String {
+    if attendee.contains('@') {
+        format!("ATTENDEE:mailto:{}", attendee)
+    } else {
+        // Attendees without an address are named with the RFC 5545 placeholder
+        format!(
+            "ATTENDEE;CN=\"{}\":invalid:nomail",
+            attendee.replace('"', "")
+        )
+    }
+}
+
+/// Splits a content line into lines of at most [`ICS_LINE_OCTETS`], each
+/// continuing with a leading space, without breaking a character apart.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / ICS_LINE_OCTETS * 3);
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > ICS_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            octets = 1;
+        }
+        folded.push(c);
+        octets += c.len_utf8();
+    }
+    folded
+}
+
+/// Joins folded content lines, which continue with a leading space or tab.
+fn unfold_lines(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn parse_ics_datetime(value: &str) -> Result<DateTime<Local>> {
+    let value = value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, ICS_LOCAL_DATETIME_FORMAT)?;
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+
+    let naive = match NaiveDateTime::parse_from_str(value, ICS_LOCAL_DATETIME_FORMAT) {
+        Ok(naive) => naive,
+        Err(_) => NaiveDate::parse_from_str(value, ICS_DATE_FORMAT)?
+            .and_hms_opt(0, 0, 0)
+            .unwrap(),
+    };
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| anyhow!("'{}' does not exist in the local timezone", value))
+}
+
+/// The VEVENT for `event`, leaving out the occurrences on `exceptions`.
+fn vevent_lines(event: &Event, stamp: &str, exceptions: &[NaiveDate]) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.id),
+        format!("DTSTAMP:{stamp}"),
+        format!("DTSTART:{}", format_ics_datetime(event.start_time)),
+        format!("DTEND:{}", format_ics_datetime(event.end_time)),
+    ];
+    if let Some(recurrence) = &event.recurrence {
+        lines.push(rrule_line(event, recurrence));
+        let excluded: Vec<String> = exceptions
+            .iter()
+            .filter_map(|date| occurrence_start(event, *date))
+            .map(format_ics_datetime)
+            .collect();
+        if !excluded.is_empty() {
+            lines.push(format!("EXDATE:{}", excluded.join(",")));
+        }
+    }
+    lines.push(format!("SUMMARY:{}", escape_text(&event.title)));
+    if let Some(description) = &event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(description)));
+    }
+    if let Some(location) = &event.location {
+        lines.push(format!("LOCATION:{}", escape_text(location)));
+    }
+    lines.extend(
+        event
+            .attendees
+            .iter()
+            .map(|attendee| attendee_to_ics(attendee)),
+    );
+    if !event.tags.is_empty() {
+        let tags: Vec<String> = event.tags.iter().map(|tag| escape_text(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    match event.priority {
+        Priority::High => lines.push("PRIORITY:1".to_string()),
+        Priority::Low => lines.push("PRIORITY:9".to_string()),
+        Priority::Normal => {}
+    }
+    lines.push(format!(
+        "X-CAL-RS-CALENDAR:{}",
+        escape_text(&event.calendar)
+    ));
+    lines.push(format!(
+        "STATUS:{}",
+        event.status.to_string().to_ascii_uppercase()
+    ));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+fn format_ics_datetime<Tz: TimeZone>(time: DateTime<Tz>) -> String {
+    time.with_timezone(&Utc)
+        .format(ICS_DATETIME_FORMAT)
+        .to_string()
+}
+
+/// Escapes a TEXT value as described in RFC 5545 section 3.3.11.
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Unescaped values of a comma-separated property such as CATEGORIES.
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                values.push(unescape_text(&value[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    values.push(unescape_text(&value[start..]));
+    values
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => unescaped.push('\n'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_ics() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let end = start + chrono::Duration::hours(1);
+        let mut event = Event::new(
+            "Lunch, then; review".to_string(),
+            Some("Bring notes\nand laptop".to_string()),
+            start,
+            end,
+        )
+        .unwrap();
+        event.location = Some("Room 3B, 2nd floor".to_string());
+        manager.add_event(event.clone()).unwrap();
+        manager
+            .add_event(Event::new("Standup".to_string(), None, start, end).unwrap())
+            .unwrap();
+
+        let ics = manager.export_ics();
+        let lines: Vec<&str> = ics.split_terminator("\r\n").collect();
+
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        assert_eq!(lines.last(), Some(&"END:VCALENDAR"));
+        assert_eq!(lines.iter().filter(|l| **l == "BEGIN:VEVENT").count(), 2);
+        assert_eq!(lines.iter().filter(|l| **l == "END:VEVENT").count(), 2);
+        assert!(lines.contains(&format!("UID:{}", event.id).as_str()));
+        assert!(lines.contains(&"SUMMARY:Lunch\\, then\\; review"));
+        assert!(lines.contains(&"DESCRIPTION:Bring notes\\nand laptop"));
+        assert!(lines.contains(&"LOCATION:Room 3B\\, 2nd floor"));
+        assert!(lines.contains(
+            &format!(
+                "DTSTART:{}",
+                start.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+            )
+            .as_str()
+        ));
+    }
+
+    #[test]
+    fn test_export_import_roundtrip() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let end = start + chrono::Duration::hours(1);
+        for title in ["One, two", "Three; four", "Five"] {
+            manager
+                .add_event(Event::new(title.to_string(), None, start, end).unwrap())
+                .unwrap();
+        }
+
+        let mut imported = EventManager::new();
+        assert_eq!(imported.import_ics(&manager.export_ics()).added, 3);
+        assert_eq!(imported.len(), 3);
+        assert_eq!(imported.search("two")[0].title, "One, two");
+
+        // Importing the same calendar again skips instead of duplicating
+        assert_eq!(imported.import_ics(&manager.export_ics()).skipped, 3);
+        assert_eq!(imported.len(), 3);
+    }
+
+    #[test]
+    fn test_import_without_summary_is_untitled() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       BEGIN:VEVENT\r\n\
+                       DTSTART:20250314T150000Z\r\n\
+                       DTEND:20250314T160000Z\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        let mut manager = EventManager::new();
+        assert_eq!(manager.import_ics(content).added, 1);
+        assert_eq!(manager.list_events()[0].title, UNTITLED);
+    }
+
+    #[test]
+    fn test_import_minimal_calendar() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       VERSION:2.0\r\n\
+                       BEGIN:VEVENT\r\n\
+                       UID:7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a\r\n\
+                       DTSTART:20250314T150000Z\r\n\
+                       DTEND:20250314T160000Z\r\n\
+                       SUMMARY:Planning\r\n\
+                       DESCRIPTION:Quarterly\\, with snacks\r\n\
+                       LOCATION:Room 4\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        let mut manager = EventManager::new();
+        assert_eq!(manager.import_ics(content).added, 1);
+
+        let id = Uuid::parse_str("7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a").unwrap();
+        let event = manager.get_event(id).unwrap();
+        assert_eq!(event.title, "Planning");
+        assert_eq!(event.description.as_deref(), Some("Quarterly, with snacks"));
+        assert_eq!(event.location.as_deref(), Some("Room 4"));
+        assert_eq!(
+            event.start_time.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 3, 14, 15, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_malformed_dtstart() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let existing = Event::new(
+            "Existing".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        manager.add_event(existing.clone()).unwrap();
+
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:20250314T150000Z\n\
+                       DTEND:20250314T160000Z\n\
+                       SUMMARY:Good\n\
+                       END:VEVENT\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:not-a-date\n\
+                       DTEND:20250314T160000Z\n\
+                       SUMMARY:Bad\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+
+        let report = manager.import_ics(content);
+        assert_eq!(report.added, 1);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("Invalid VEVENT #2: Invalid DTSTART"));
+        assert_eq!(manager.len(), 2);
+        assert_eq!(manager.get_event(existing.id).unwrap().title, "Existing");
+    }
+
+    #[test]
+    fn test_priority_survives_roundtrip() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let end = start + chrono::Duration::hours(1);
+        for (title, priority) in [
+            ("Urgent", Priority::High),
+            ("Routine", Priority::Normal),
+            ("Someday", Priority::Low),
+        ] {
+            let mut event = Event::new(title.to_string(), None, start, end).unwrap();
+            event.priority = priority;
+            manager.add_event(event).unwrap();
+        }
+
+        let mut imported = EventManager::new();
+        imported.import_ics(&manager.export_ics());
+        for original in manager.list_events() {
+            let event = imported.get_event(original.id).unwrap();
+            assert_eq!(event.priority, original.priority, "{}", event.title);
+        }
+    }
+
+    #[test]
+    fn test_status_exported_and_imported() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let mut event = Event::new(
+            "Offsite".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        event.status = EventStatus::Cancelled;
+        event.calendar = "work".to_string();
+        manager.add_event(event.clone()).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported.lines().any(|line| line == "STATUS:CANCELLED"));
+        assert!(exported
+            .lines()
+            .any(|line| line == "X-CAL-RS-CALENDAR:work"));
+        let mut imported = EventManager::new();
+        imported.import_ics(&exported);
+        let imported = imported.get_event(event.id).unwrap();
+        assert_eq!(imported.status, EventStatus::Cancelled);
+        assert_eq!(imported.calendar, "work");
+    }
+
+    #[test]
+    fn test_attendees_survive_roundtrip() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let mut event = Event::new(
+            "Sync".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        event.attendees = vec!["a@x.com".to_string(), "Bob Smith".to_string()];
+        let id = manager.add_event(event.clone()).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported.contains("ATTENDEE:mailto:a@x.com\r\n"));
+        assert!(exported.contains("ATTENDEE;CN=\"Bob Smith\":invalid:nomail\r\n"));
+
+        let mut imported = EventManager::new();
+        imported.import_ics(&exported);
+        assert_eq!(imported.get_event(id).unwrap().attendees, event.attendees);
+    }
+
+    #[test]
+    fn test_tags_export_as_categories() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let mut event = Event::new(
+            "Flight".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(3),
+        )
+        .unwrap();
+        event.tags = vec!["travel".to_string(), "urgent".to_string()];
+        let id = manager.add_event(event.clone()).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported.contains("CATEGORIES:travel,urgent\r\n"));
+
+        let mut imported = EventManager::new();
+        imported.import_ics(&exported);
+        assert_eq!(imported.get_event(id).unwrap().tags, event.tags);
+        assert_eq!(split_text_list("a\\,b,c"), ["a,b", "c"]);
+    }
+
+    #[test]
+    fn test_import_report() {
+        let mut manager = EventManager::new();
+        let start = Utc.with_ymd_and_hms(2025, 3, 14, 15, 0, 0).unwrap();
+        let mut existing = Event::new(
+            "Existing".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        existing.id = Uuid::parse_str("7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a").unwrap();
+        manager.add_event(existing).unwrap();
+
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:20250315T090000Z\n\
+                       DTEND:20250315T100000Z\n\
+                       SUMMARY:New\n\
+                       END:VEVENT\n\
+                       BEGIN:VEVENT\n\
+                       UID:7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a\n\
+                       DTSTART:20250314T150000Z\n\
+                       DTEND:20250314T160000Z\n\
+                       SUMMARY:Existing\n\
+                       END:VEVENT\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:20250316T090000Z\n\
+                       SUMMARY:No end\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+
+        let report = manager.import_ics(content);
+        assert_eq!(
+            report,
+            ImportReport {
+                added: 1,
+                updated: 0,
+                skipped: 1,
+                errors: vec!["Invalid VEVENT #3: Missing DTEND".to_string()],
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "1 added, 0 updated, 1 skipped, 1 failed"
+        );
+        assert_eq!(manager.len(), 2);
+
+        // Moving an event with a known UID updates it
+        let moved = content.replace("20250314T160000Z", "20250314T170000Z");
+        let report = manager.import_ics(&moved);
+        assert_eq!((report.added, report.updated, report.skipped), (1, 1, 0));
+        assert_eq!(manager.len(), 3);
+    }
+
+    #[test]
+    fn test_import_into_existing_event_keeps_other_fields() {
+        let mut manager = EventManager::new();
+        let start = Utc.with_ymd_and_hms(2025, 3, 14, 15, 0, 0).unwrap();
+        let mut existing = Event::new(
+            "Standup".to_string(),
+            Some("Daily sync".to_string()),
+            start,
+            start + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        existing.id = Uuid::parse_str("7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a").unwrap();
+        existing.category = Some("work".to_string());
+        existing.reminder = Some(chrono::Duration::minutes(5));
+        existing.recurrence = Some(Recurrence::new(Frequency::Daily));
+        existing.priority = Priority::High;
+        manager.add_event(existing.clone()).unwrap();
+
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       UID:7f8c6a58-5c4b-4b8e-9d7a-1f2e3d4c5b6a\n\
+                       DTSTART:20250314T160000Z\n\
+                       DTEND:20250314T161500Z\n\
+                       LOCATION:Room 2\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+        assert_eq!(manager.import_ics(content).updated, 1);
+        let updated = manager.get_event(existing.id).unwrap();
+        assert_eq!(
+            updated.start_time.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 3, 14, 16, 0, 0).unwrap()
+        );
+        assert_eq!(updated.location.as_deref(), Some("Room 2"));
+        assert_eq!(updated.title, "Standup");
+        assert_eq!(updated.description, existing.description);
+        assert_eq!(updated.category, existing.category);
+        assert_eq!(updated.reminder, existing.reminder);
+        assert_eq!(updated.recurrence, existing.recurrence);
+        assert_eq!(updated.priority, Priority::High);
+
+        // Importing again changes nothing, and undo restores the original
+        assert_eq!(manager.import_ics(content).skipped, 1);
+        manager.undo().unwrap();
+        assert_eq!(manager.get_event(existing.id), Some(&existing));
+    }
+
+    #[test]
+    fn test_undo_imported_event() {
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:20250314T150000Z\n\
+                       DTEND:20250314T160000Z\n\
+                       SUMMARY:Imported\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+        let mut manager = EventManager::new();
+        assert_eq!(manager.import_ics(content).added, 1);
+        manager.undo().unwrap();
+        assert!(manager.is_empty());
+    }
+
+    #[test]
+    fn test_multiline_description_roundtrip() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let event = Event::new(
+            "Planning".to_string(),
+            Some("Agenda first\nThen budget".to_string()),
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        manager.add_event(event.clone()).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported.contains("DESCRIPTION:Agenda first\\nThen budget\r\n"));
+        let mut imported = EventManager::new();
+        imported.import_ics(&exported);
+        assert_eq!(
+            imported.get_event(event.id).unwrap().description.as_deref(),
+            Some("Agenda first\nThen budget")
+        );
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let mut manager = EventManager::new();
+        let start = Utc.with_ymd_and_hms(2025, 3, 14, 15, 0, 0).unwrap();
+        let title = ["Café planning"; 12].join(" ");
+        let event = Event::new(
+            title.clone(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        manager.add_event(event.clone()).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported.lines().all(|line| line.len() <= 75));
+        assert!(exported.contains("\r\n "));
+        let mut imported = EventManager::new();
+        assert_eq!(imported.import_ics(&exported).added, 1);
+        assert_eq!(imported.get_event(event.id).unwrap().title, title);
+    }
+
+    #[test]
+    fn test_recurrence_and_exceptions_roundtrip() {
+        let mut manager = EventManager::new();
+        // A Monday
+        let start = Utc.with_ymd_and_hms(2025, 3, 10, 15, 0, 0).unwrap();
+        let mut event = Event::new(
+            "Review".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        let mut recurrence = Recurrence::new(Frequency::Weekly);
+        recurrence.interval = 2;
+        recurrence.weekdays = vec![Weekday::Mon, Weekday::Wed];
+        recurrence.until = NaiveDate::from_ymd_opt(2025, 6, 30);
+        event.recurrence = Some(recurrence);
+        manager.add_event(event.clone()).unwrap();
+        let skipped = NaiveDate::from_ymd_opt(2025, 3, 24).unwrap();
+        manager.delete_occurrence(event.id, skipped).unwrap();
+
+        let exported = manager.export_ics();
+        assert!(exported
+            .contains("RRULE:FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20250630T150000Z\r\n"));
+        assert!(exported.contains("EXDATE:20250324T150000Z\r\n"));
+
+        let mut imported = EventManager::new();
+        assert_eq!(imported.import_ics(&exported).added, 1);
+        assert_eq!(
+            imported.get_event(event.id).unwrap().recurrence,
+            event.recurrence
+        );
+        assert_eq!(imported.exceptions_for(event.id), vec![skipped]);
+        // Importing the same file again changes nothing
+        assert_eq!(imported.import_ics(&exported).skipped, 1);
+    }
+
+    #[test]
+    fn test_import_rrule() {
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART;VALUE=DATE:20250314\n\
+                       DTEND;VALUE=DATE:20250315\n\
+                       RRULE:FREQ=DAILY;COUNT=3\n\
+                       EXDATE;VALUE=DATE:20250315\n\
+                       END:VEVENT\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART:20250314T150000Z\n\
+                       DTEND:20250314T160000Z\n\
+                       RRULE:FREQ=YEARLY\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+        let mut manager = EventManager::new();
+        let report = manager.import_ics(content);
+        assert_eq!(report.added, 1);
+        assert_eq!(
+            report.errors,
+            vec!["Invalid VEVENT #2: Invalid RRULE: FREQ=YEARLY is not supported".to_string()]
+        );
+
+        let event = manager.list_events()[0].clone();
+        let recurrence = event.recurrence.as_ref().unwrap();
+        assert_eq!(recurrence.frequency, Frequency::Daily);
+        assert_eq!(recurrence.until, NaiveDate::from_ymd_opt(2025, 3, 16));
+        assert_eq!(
+            manager.exceptions_for(event.id),
+            vec![NaiveDate::from_ymd_opt(2025, 3, 15).unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_import_tzid() {
+        let content = "BEGIN:VCALENDAR\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART;TZID=UTC:20250314T150000\n\
+                       DTEND;TZID=UTC:20250314T160000\n\
+                       SUMMARY:In UTC\n\
+                       END:VEVENT\n\
+                       BEGIN:VEVENT\n\
+                       DTSTART;TZID=Europe/Berlin:20250314T150000\n\
+                       DTEND;TZID=Europe/Berlin:20250314T160000\n\
+                       SUMMARY:In Berlin\n\
+                       END:VEVENT\n\
+                       END:VCALENDAR\n";
+        let mut manager = EventManager::new();
+        let report = manager.import_ics(content);
+        assert_eq!(report.added, 1);
+        assert_eq!(
+            report.errors,
+            vec![
+                "Invalid VEVENT #2: time zone 'Europe/Berlin' is not supported, use UTC times"
+                    .to_string()
+            ]
+        );
+        assert_eq!(
+            manager.list_events()[0].start_time.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2025, 3, 14, 15, 0, 0).unwrap()
+        );
+    }
+}
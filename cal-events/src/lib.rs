@@ -1,91 +1,841 @@
 use anyhow::{anyhow, Result};
-use chrono::{DateTime, Local};
-use std::collections::HashMap;
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
 use uuid::Uuid;
 
-#[derive(Debug, Clone)]
+mod builder;
+mod csv;
+mod ics;
+mod quick;
+mod recurrence;
+mod store;
+
+pub use builder::EventBuilder;
+pub use ics::ImportReport;
+pub use quick::{parse_duration, parse_quick_event};
+pub use recurrence::{Frequency, Recurrence};
+pub use store::LoadedEvents;
+
+const UNDO_LIMIT: usize = 50;
+/// How many days ahead `next_free_slot` looks before giving up.
+const FREE_SLOT_SEARCH_DAYS: i64 = 14;
+/// Calendar that events belong to unless another is chosen.
+pub const DEFAULT_CALENDAR: &str = "personal";
+/// How many days `next_day_with_events` and `previous_day_with_events` scan.
+const EVENT_DAY_SEARCH_DAYS: i64 = 366;
+
+/// Ordered so that sorting ascending puts high-priority events first.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    High,
+    #[default]
+    Normal,
+    Low,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Priority::High => write!(f, "High"),
+            Priority::Normal => write!(f, "Normal"),
+            Priority::Low => write!(f, "Low"),
+        }
+    }
+}
+
+/// Whether an event is going ahead. Cancelled events are kept so they can
+/// still be shown, dimmed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventStatus {
+    Tentative,
+    #[default]
+    Confirmed,
+    Cancelled,
+}
+
+impl fmt::Display for EventStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventStatus::Tentative => write!(f, "Tentative"),
+            EventStatus::Confirmed => write!(f, "Confirmed"),
+            EventStatus::Cancelled => write!(f, "Cancelled"),
+        }
+    }
+}
+
+/// Times keep the offset they were created with, so a 10:00 meeting set up in
+/// New York stays 10:00 New York time when the machine moves to London.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Event {
     pub id: Uuid,
     pub title: String,
     pub description: Option<String>,
-    pub start_time: DateTime<Local>,
-    pub end_time: DateTime<Local>,
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    /// How long before `start_time` to send a reminder, if at all.
+    #[serde(default, with = "store::optional_minutes")]
+    pub reminder: Option<Duration>,
+    #[serde(default)]
+    pub priority: Priority,
+    #[serde(default)]
+    pub status: EventStatus,
+    /// Names or email addresses of the people invited.
+    #[serde(default)]
+    pub attendees: Vec<String>,
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Free-form grouping such as "work", colored by the theme's `[categories]`.
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Any number of labels such as "urgent" or "travel", unlike the single category.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Name of the calendar the event belongs to, e.g. "work" or "personal".
+    #[serde(default = "default_calendar")]
+    pub calendar: String,
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
 }
 
 impl Event {
-    pub fn new(
+    /// Accepts times in any zone; `Local` times are pinned to the system zone's
+    /// offset at that moment. Use [`EventBuilder`] to set the other fields.
+    pub fn new<Tz: TimeZone>(
         title: String,
         description: Option<String>,
-        start_time: DateTime<Local>,
-        end_time: DateTime<Local>,
+        start_time: DateTime<Tz>,
+        end_time: DateTime<Tz>,
     ) -> Result<Self> {
-        if end_time <= start_time {
-            return Err(anyhow!("End time must be after start time"));
+        let builder = EventBuilder::new()
+            .title(title)
+            .start(start_time)
+            .end(end_time);
+        match description {
+            Some(description) => builder.description(description),
+            None => builder,
+        }
+        .build()
+    }
+
+    pub fn reminder_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.reminder
+            .and_then(|reminder| self.start_time.checked_sub_signed(reminder))
+    }
+
+    /// Start times of the event within `[from, to)`, following its recurrence
+    /// rule if it has one. Occurrences repeat at the same clock time in the
+    /// event's own offset.
+    pub fn occurrences(&self, from: DateTime<Local>, to: DateTime<Local>) -> Vec<DateTime<Local>> {
+        let Some(recurrence) = &self.recurrence else {
+            let start = self.start_time.with_timezone(&Local);
+            return (from <= start && start < to)
+                .then_some(start)
+                .into_iter()
+                .collect();
+        };
+
+        let offset = *self.start_time.offset();
+        let time = self.start_time.time();
+        let last_date = to.with_timezone(&offset).date_naive();
+        recurrence
+            .dates(self.start_time.date_naive())
+            .take_while(|date| *date <= last_date)
+            .filter_map(|date| offset.from_local_datetime(&date.and_time(time)).single())
+            .map(|start| start.with_timezone(&Local))
+            .filter(|start| *start >= from && *start < to)
+            .collect()
+    }
+
+    /// The event, or for a recurring event copies of it moved to each
+    /// occurrence, wherever it overlaps `date`. Copies keep the series' id.
+    pub fn instances_on(&self, date: NaiveDate) -> Vec<Cow<'_, Event>> {
+        if self.recurrence.is_none() {
+            return if self.occurs_on(date) {
+                vec![Cow::Borrowed(self)]
+            } else {
+                Vec::new()
+            };
         }
 
-        Ok(Self {
-            id: Uuid::new_v4(),
-            title,
-            description,
-            start_time,
-            end_time,
-        })
+        let (day_start, day_end) = self.day_bounds(date);
+        let length = self.end_time - self.start_time;
+        // An occurrence starting the day before may run past midnight
+        self.instances_between(
+            (day_start - length).with_timezone(&Local),
+            day_end.with_timezone(&Local),
+        )
+        .into_iter()
+        .filter(|instance| instance.occurs_on(date))
+        .collect()
+    }
+
+    /// The event, or copies of it moved to each occurrence, for every
+    /// occurrence starting within `[from, to)`. Copies keep the series' id.
+    pub fn instances_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+    ) -> Vec<Cow<'_, Event>> {
+        let length = self.end_time - self.start_time;
+        self.occurrences(from, to)
+            .into_iter()
+            .map(|start| {
+                if start == self.start_time {
+                    return Cow::Borrowed(self);
+                }
+                let start_time = start.with_timezone(self.start_time.offset());
+                Cow::Owned(Event {
+                    start_time,
+                    end_time: start_time + length,
+                    ..self.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Whether any part of `[start_time, end_time)` falls on `date`, so an
+    /// event ending exactly at midnight does not spill into the next day.
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        let (day_start, day_end) = self.day_bounds(date);
+        self.start_time < day_end && self.end_time > day_start
+    }
+
+    /// Whether the event covers the whole of `date`, from midnight to midnight.
+    pub fn is_all_day_on(&self, date: NaiveDate) -> bool {
+        let (day_start, day_end) = self.day_bounds(date);
+        self.start_time <= day_start && self.end_time >= day_end
+    }
+
+    /// Whether the event shares any time with `[start, end)`.
+    pub fn overlaps<Tz: TimeZone>(&self, start: &DateTime<Tz>, end: &DateTime<Tz>) -> bool {
+        self.start_time < *end && self.end_time > *start
+    }
+
+    /// Midnight at the start and end of `date` in the event's own zone.
+    fn day_bounds(&self, date: NaiveDate) -> (DateTime<FixedOffset>, DateTime<FixedOffset>) {
+        let offset = *self.start_time.offset();
+        let midnight = |date: NaiveDate| {
+            offset
+                .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                .unwrap()
+        };
+        (midnight(date), midnight(date + chrono::Duration::days(1)))
+    }
+}
+
+/// Splits tags typed as `urgent, travel` or `#urgent #travel`, dropping
+/// blanks and repeats.
+pub fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in input.split([' ', ',']) {
+        let tag = tag.trim().trim_start_matches('#');
+        if !tag.is_empty() && !tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Ids of the events that overlap at least one other, ignoring cancelled
+/// ones, which take up no time.
+pub fn conflicts<'a>(events: impl IntoIterator<Item = &'a Event>) -> HashSet<Uuid> {
+    let events: Vec<&Event> = events
+        .into_iter()
+        .filter(|event| event.status != EventStatus::Cancelled)
+        .collect();
+    let mut ids = HashSet::new();
+    for (index, event) in events.iter().enumerate() {
+        for other in &events[index + 1..] {
+            if event.overlaps(&other.start_time, &other.end_time) {
+                ids.insert(event.id);
+                ids.insert(other.id);
+            }
+        }
+    }
+    ids
+}
+
+/// Orders events by start time, breaking ties by title and then id so that
+/// listings do not depend on `HashMap` iteration order.
+fn chronological(a: &Event, b: &Event) -> Ordering {
+    a.start_time
+        .cmp(&b.start_time)
+        .then_with(|| a.title.cmp(&b.title))
+        .then_with(|| a.id.cmp(&b.id))
+}
+
+fn default_calendar() -> String {
+    DEFAULT_CALENDAR.to_string()
+}
+
+/// A mutating operation recorded so that it can be undone.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Add(Event),
+    Delete(Event),
+    /// Holds the event as it was before the edit.
+    Edit(Event),
+    /// Several events removed together, e.g. a whole day, along with the
+    /// occurrences hidden from recurring events at the same time.
+    DeleteMany(Vec<Event>, Vec<(Uuid, NaiveDate)>),
+    /// One occurrence, starting on the date, hidden from a recurring event.
+    DeleteOccurrence(Event, NaiveDate),
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::Add(event) => write!(f, "add '{}'", event.title),
+            Operation::Delete(event) => write!(f, "delete '{}'", event.title),
+            Operation::Edit(event) => write!(f, "edit '{}'", event.title),
+            Operation::DeleteMany(events, occurrences) => {
+                write!(f, "delete {} events", events.len() + occurrences.len())
+            }
+            Operation::DeleteOccurrence(event, date) => {
+                write!(f, "delete '{}' on {}", event.title, date)
+            }
+        }
+    }
+}
+
+/// Returned by `add_event_checked` for an event that has already ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PastEventError {
+    pub title: String,
+}
+
+impl fmt::Display for PastEventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' is entirely in the past", self.title)
     }
 }
 
+impl std::error::Error for PastEventError {}
+
 #[derive(Debug, Default)]
 pub struct EventManager {
     events: HashMap<Uuid, Event>,
+    /// Occurrences of recurring events that were deleted, by series id and
+    /// the date the occurrence starts on.
+    exceptions: HashSet<(Uuid, NaiveDate)>,
+    /// Calendars added by name, even before they hold any event, and
+    /// whether each is shown. Calendars missing here are shown.
+    calendars: BTreeMap<String, bool>,
+    undo_stack: VecDeque<Operation>,
 }
 
 impl EventManager {
     pub fn new() -> Self {
         Self {
             events: HashMap::new(),
+            exceptions: HashSet::new(),
+            calendars: BTreeMap::new(),
+            undo_stack: VecDeque::new(),
         }
     }
 
     pub fn add_event(&mut self, event: Event) -> Result<Uuid> {
         let id = event.id;
+        self.record(Operation::Add(event.clone()));
         self.events.insert(id, event);
         Ok(id)
     }
 
+    /// Like `add_event`, but unless `allow_past` is set an event that ends
+    /// before now is refused with a `PastEventError` so the caller can confirm.
+    pub fn add_event_checked(&mut self, event: Event, allow_past: bool) -> Result<Uuid> {
+        if !allow_past && event.end_time <= Local::now() {
+            return Err(PastEventError { title: event.title }.into());
+        }
+        self.add_event(event)
+    }
+
     pub fn delete_event(&mut self, id: Uuid) -> Result<()> {
-        self.events
+        let event = self
+            .events
             .remove(&id)
             .ok_or_else(|| anyhow!("Event not found"))?;
+        self.record(Operation::Delete(event));
+        Ok(())
+    }
+
+    /// Deletes only the occurrence of a recurring event that starts on `date`,
+    /// leaving the rest of the series in place.
+    pub fn delete_occurrence(&mut self, id: Uuid, date: NaiveDate) -> Result<()> {
+        let event = self
+            .events
+            .get(&id)
+            .ok_or_else(|| anyhow!("Event not found"))?;
+        if event.recurrence.is_none() {
+            return Err(anyhow!("'{}' does not repeat", event.title));
+        }
+        let starts_on_date = event
+            .instances_on(date)
+            .iter()
+            .any(|instance| instance.start_time.date_naive() == date);
+        if !starts_on_date || self.exceptions.contains(&(id, date)) {
+            return Err(anyhow!("'{}' does not occur on {}", event.title, date));
+        }
+
+        let event = event.clone();
+        self.exceptions.insert((id, date));
+        self.record(Operation::DeleteOccurrence(event, date));
         Ok(())
     }
 
+    /// Dates of the deleted occurrences of the event with this id, in order.
+    pub fn exceptions_for(&self, id: Uuid) -> Vec<NaiveDate> {
+        let mut dates: Vec<NaiveDate> = self
+            .exceptions
+            .iter()
+            .filter(|(series, _)| *series == id)
+            .map(|(_, date)| *date)
+            .collect();
+        dates.sort_unstable();
+        dates
+    }
+
+    /// Removes every event starting on `date` as one undoable step and
+    /// returns how many were removed. Recurring events only lose the
+    /// occurrences starting that day; events and occurrences running into
+    /// `date` from the day before are left alone.
+    pub fn delete_events_for_day(&mut self, date: NaiveDate) -> usize {
+        let (ids, occurrences): (Vec<_>, Vec<_>) = self
+            .instances_starting_on(date)
+            .map(|instance| (instance.id, date))
+            .partition(|(id, _)| self.events[id].recurrence.is_none());
+        if ids.is_empty() && occurrences.is_empty() {
+            return 0;
+        }
+
+        let removed: Vec<Event> = ids
+            .iter()
+            .filter_map(|(id, _)| self.events.remove(id))
+            .collect();
+        self.exceptions.extend(occurrences.iter().copied());
+        let count = removed.len() + occurrences.len();
+        self.record(Operation::DeleteMany(removed, occurrences));
+        count
+    }
+
+    /// Removes every event along with their deleted occurrences and the undo
+    /// history, leaving an empty manager that still knows its calendars.
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.exceptions.clear();
+        self.undo_stack.clear();
+    }
+
+    /// Folds in the events of `other`, each as its own undoable step. An event
+    /// with the id of an existing one replaces it unless the two are equal.
+    /// Deleted occurrences and calendars come along too.
+    pub fn merge(&mut self, other: EventManager) -> ImportReport {
+        let mut report = ImportReport::default();
+        for (id, event) in other.events {
+            match self.events.get(&id) {
+                Some(existing) if *existing == event => report.skipped += 1,
+                Some(_) => {
+                    if let Some(previous) = self.events.insert(id, event) {
+                        self.record(Operation::Edit(previous));
+                    }
+                    report.updated += 1;
+                }
+                None => {
+                    self.record(Operation::Add(event.clone()));
+                    self.events.insert(id, event);
+                    report.added += 1;
+                }
+            }
+        }
+        self.exceptions.extend(other.exceptions);
+        for (calendar, visible) in other.calendars {
+            self.calendars.entry(calendar).or_insert(visible);
+        }
+        report
+    }
+
     pub fn edit_event(&mut self, id: Uuid, mut updated_event: Event) -> Result<()> {
         if !self.events.contains_key(&id) {
             return Err(anyhow!("Event not found"));
         }
         updated_event.id = id; // Preserve the original ID
-        self.events.insert(id, updated_event);
+        if let Some(previous) = self.events.insert(id, updated_event) {
+            self.record(Operation::Edit(previous));
+        }
+        Ok(())
+    }
+
+    /// The operation that the next call to `undo` would revert.
+    pub fn last_operation(&self) -> Option<&Operation> {
+        self.undo_stack.back()
+    }
+
+    pub fn undo(&mut self) -> Result<()> {
+        let operation = self
+            .undo_stack
+            .pop_back()
+            .ok_or_else(|| anyhow!("Nothing to undo"))?;
+        match operation {
+            Operation::Add(event) => {
+                self.events.remove(&event.id);
+            }
+            Operation::Delete(event) | Operation::Edit(event) => {
+                self.events.insert(event.id, event);
+            }
+            Operation::DeleteMany(events, occurrences) => {
+                for event in events {
+                    self.events.insert(event.id, event);
+                }
+                for occurrence in occurrences {
+                    self.exceptions.remove(&occurrence);
+                }
+            }
+            Operation::DeleteOccurrence(event, date) => {
+                self.exceptions.remove(&(event.id, date));
+            }
+        }
         Ok(())
     }
 
+    fn record(&mut self, operation: Operation) {
+        if self.undo_stack.len() == UNDO_LIMIT {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(operation);
+    }
+
     pub fn get_event(&self, id: Uuid) -> Option<&Event> {
         self.events.get(&id)
     }
 
+    /// Number of events, without collecting them.
+    ///
+    /// ```
+    /// use cal_events::{Event, EventManager};
+    /// use chrono::{Duration, Local};
+    ///
+    /// let mut manager = EventManager::new();
+    /// assert!(manager.is_empty());
+    ///
+    /// let start = Local::now();
+    /// let event = Event::new("Lunch".to_string(), None, start, start + Duration::hours(1))?;
+    /// manager.add_event(event)?;
+    /// assert_eq!(manager.len(), 1);
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Distinct tags of all events, sorted.
+    pub fn tags(&self) -> Vec<&str> {
+        let mut tags: Vec<&str> = self
+            .events
+            .values()
+            .flat_map(|event| event.tags.iter().map(String::as_str))
+            .collect();
+        tags.sort_unstable();
+        tags.dedup();
+        tags
+    }
+
+    /// Distinct categories of all events, sorted.
+    pub fn categories(&self) -> Vec<&str> {
+        let mut categories: Vec<&str> = self
+            .events
+            .values()
+            .filter_map(|event| event.category.as_deref())
+            .collect();
+        categories.sort_unstable();
+        categories.dedup();
+        categories
+    }
+
+    /// Every event, sorted by start time and then title.
     pub fn list_events(&self) -> Vec<&Event> {
-        self.events.values().collect()
+        let mut events: Vec<&Event> = self.events.values().collect();
+        events.sort_by(|a, b| chronological(a, b));
+        events
+    }
+
+    /// Makes a calendar known so events can be put in it, shown at first.
+    pub fn add_calendar(&mut self, name: &str) {
+        self.calendars.entry(name.to_string()).or_insert(true);
+    }
+
+    /// Every calendar added or holding an event, plus the default one, sorted.
+    pub fn calendars(&self) -> Vec<&str> {
+        let mut calendars: Vec<&str> = self
+            .calendars
+            .keys()
+            .map(String::as_str)
+            .chain(self.events.values().map(|event| event.calendar.as_str()))
+            .chain([DEFAULT_CALENDAR])
+            .collect();
+        calendars.sort_unstable();
+        calendars.dedup();
+        calendars
+    }
+
+    /// Shows or hides the events of a calendar in the day and range listings.
+    pub fn set_calendar_visible(&mut self, name: &str, visible: bool) {
+        self.calendars.insert(name.to_string(), visible);
+    }
+
+    pub fn is_calendar_visible(&self, name: &str) -> bool {
+        self.calendars.get(name).copied().unwrap_or(true)
     }
 
-    pub fn list_events_for_day(&self, date: DateTime<Local>) -> Vec<&Event> {
+    /// Events in calendars that are shown.
+    fn visible_events(&self) -> impl Iterator<Item = &Event> {
         self.events
             .values()
-            .filter(|event| event.start_time.date_naive() == date.date_naive())
-            .collect()
+            .filter(|event| self.is_calendar_visible(&event.calendar))
+    }
+
+    /// Every event and occurrence of a recurring event on `date` in a shown
+    /// calendar, without the deleted occurrences.
+    fn instances_on(&self, date: NaiveDate) -> impl Iterator<Item = Cow<'_, Event>> {
+        self.visible_events()
+            .flat_map(move |event| event.instances_on(date))
+            .filter(|instance| !self.is_deleted_occurrence(instance))
+    }
+
+    /// The occurrence of event `id` starting at `start`, unless it was deleted.
+    pub fn occurrence(&self, id: Uuid, start: DateTime<FixedOffset>) -> Option<Cow<'_, Event>> {
+        let from = start.with_timezone(&Local);
+        self.get_event(id)?
+            .instances_between(from, from + Duration::seconds(1))
+            .into_iter()
+            .find(|instance| instance.start_time == start && !self.is_deleted_occurrence(instance))
+    }
+
+    /// Whether `instance` is an occurrence removed with `delete_occurrence`.
+    fn is_deleted_occurrence(&self, instance: &Event) -> bool {
+        self.exceptions
+            .contains(&(instance.id, instance.start_time.date_naive()))
+    }
+
+    /// Events occurring on `date`, with all-day events first and the rest by
+    /// start time. Recurring events appear as copies moved to that occurrence.
+    pub fn list_events_for_day(&self, date: DateTime<Local>) -> Vec<Cow<'_, Event>> {
+        let date = date.date_naive();
+        let mut events: Vec<Cow<'_, Event>> = self.instances_on(date).collect();
+        events.sort_by(|a, b| {
+            (!a.is_all_day_on(date), a.start_time, a.priority)
+                .cmp(&(!b.is_all_day_on(date), b.start_time, b.priority))
+                .then_with(|| chronological(a, b))
+        });
+        events
+    }
+
+    pub fn has_events_on(&self, date: NaiveDate) -> bool {
+        self.instances_on(date).next().is_some()
+    }
+
+    /// The first day after `after`, within a year, that has any event.
+    pub fn next_day_with_events(&self, after: NaiveDate) -> Option<NaiveDate> {
+        (1..=EVENT_DAY_SEARCH_DAYS)
+            .map(|offset| after + Duration::days(offset))
+            .find(|day| self.has_events_on(*day))
+    }
+
+    /// The last day before `before`, within a year, that has any event.
+    pub fn previous_day_with_events(&self, before: NaiveDate) -> Option<NaiveDate> {
+        (1..=EVENT_DAY_SEARCH_DAYS)
+            .map(|offset| before - Duration::days(offset))
+            .find(|day| self.has_events_on(*day))
+    }
+
+    /// Ids of the events on `date` that are double-booked, see [`conflicts`].
+    pub fn conflicts(&self, date: NaiveDate) -> HashSet<Uuid> {
+        let instances: Vec<_> = self.instances_on(date).collect();
+        conflicts(instances.iter().map(|instance| instance.as_ref()))
+    }
+
+    pub fn count_events_for_day(&self, date: NaiveDate) -> usize {
+        self.instances_on(date).count()
+    }
+
+    /// How many events `delete_events_for_day` would remove from `date`.
+    pub fn count_events_starting_on(&self, date: NaiveDate) -> usize {
+        self.instances_starting_on(date).count()
+    }
+
+    fn instances_starting_on(&self, date: NaiveDate) -> impl Iterator<Item = Cow<'_, Event>> {
+        self.instances_on(date)
+            .filter(move |instance| instance.start_time.date_naive() == date)
+    }
+
+    /// Wall-clock minutes of `date` taken up by events, counting only the
+    /// part of each event that falls on that day. Time covered by several
+    /// overlapping events is counted once, so two events from 9:00 to 10:00
+    /// make 60 busy minutes rather than 120.
+    pub fn busy_minutes_for_day(&self, date: NaiveDate) -> u32 {
+        let mut intervals: Vec<_> = self
+            .instances_on(date)
+            .map(|event| {
+                let (day_start, day_end) = event.day_bounds(date);
+                (event.start_time.max(day_start), event.end_time.min(day_end))
+            })
+            .collect();
+        intervals.sort_unstable();
+
+        let mut busy = Duration::zero();
+        let mut current: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = None;
+        for (start, end) in intervals {
+            current = match current {
+                Some((busy_start, busy_end)) if start <= busy_end => {
+                    Some((busy_start, busy_end.max(end)))
+                }
+                Some((busy_start, busy_end)) => {
+                    busy += busy_end - busy_start;
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((busy_start, busy_end)) = current {
+            busy += busy_end - busy_start;
+        }
+        busy.num_minutes() as u32
+    }
+
+    /// Events, and occurrences of recurring events, whose reminder fell due in
+    /// `(since, now]` and which have not started yet.
+    pub fn due_reminders(
+        &self,
+        since: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Vec<Cow<'_, Event>> {
+        let mut events: Vec<Cow<'_, Event>> = self
+            .events
+            .values()
+            .filter_map(|event| {
+                let reminder = event.reminder?;
+                let from = since.checked_add_signed(reminder)?;
+                let to = now
+                    .checked_add_signed(reminder)?
+                    .checked_add_signed(Duration::seconds(1))?;
+                Some(event.instances_between(from, to))
+            })
+            .flatten()
+            .filter(|instance| {
+                instance.start_time > now
+                    && instance
+                        .reminder_time()
+                        .is_some_and(|time| time > since && time <= now)
+                    && !self.is_deleted_occurrence(instance)
+            })
+            .collect();
+        events.sort_by(|a, b| chronological(a, b));
+        events
+    }
+
+    /// Events and occurrences of recurring events in shown calendars starting
+    /// within `[start, end)`, sorted by start time. Occurrences are copies
+    /// moved to their start, as in [`EventManager::list_events_for_day`].
+    pub fn list_events_in_range(
+        &self,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Vec<Cow<'_, Event>> {
+        let mut events: Vec<Cow<'_, Event>> = self
+            .visible_events()
+            .flat_map(|event| event.instances_between(start, end))
+            .filter(|instance| !self.is_deleted_occurrence(instance))
+            .collect();
+        events.sort_by(|a, b| chronological(a, b));
+        events
+    }
+
+    /// The earliest start on or after `after` where `duration` fits between
+    /// existing events and occurrences of recurring events, inside the daily
+    /// `within_hours` window, e.g. `(9, 17)`.
+    pub fn next_free_slot(
+        &self,
+        after: DateTime<Local>,
+        duration: Duration,
+        within_hours: (u32, u32),
+    ) -> Option<DateTime<Local>> {
+        let (open, close) = within_hours;
+        if open >= close || close > 24 || duration <= Duration::zero() {
+            return None;
+        }
+
+        for offset in 0..FREE_SLOT_SEARCH_DAYS {
+            let midnight = cal_core::local_midnight(after.date_naive() + Duration::days(offset));
+            let window_end = midnight + Duration::hours(close.into());
+            let mut candidate = after.max(midnight + Duration::hours(open.into()));
+            while candidate + duration <= window_end {
+                let slot_end = candidate + duration;
+                let blocking_end = self
+                    .events
+                    .values()
+                    .flat_map(|event| {
+                        let length = event.end_time - event.start_time;
+                        event.instances_between(candidate - length, slot_end)
+                    })
+                    .filter(|instance| {
+                        instance.overlaps(&candidate, &slot_end)
+                            && !self.is_deleted_occurrence(instance)
+                    })
+                    .map(|instance| instance.end_time.with_timezone(&Local))
+                    .max();
+                match blocking_end {
+                    Some(end) => candidate = end,
+                    None => return Some(candidate),
+                }
+            }
+        }
+        None
+    }
+
+    /// Events whose title, description or one of whose tags contains `query`,
+    /// ignoring case.
+    pub fn search(&self, query: &str) -> Vec<&Event> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut results: Vec<&Event> = self
+            .events
+            .values()
+            .filter(|event| {
+                event.title.to_lowercase().contains(&query)
+                    || event
+                        .description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&query))
+                    || event
+                        .tags
+                        .iter()
+                        .any(|tag| tag.to_lowercase().contains(&query))
+            })
+            .collect();
+        results.sort_by(|a, b| chronological(a, b));
+        results
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use cal_core::local_midnight;
+    use chrono::{Datelike, Timelike, Weekday};
 
     #[test]
     fn test_event_creation() {
@@ -121,9 +871,1143 @@ mod tests {
         let event = Event::new("Test Event".to_string(), None, now, later).unwrap();
 
         let id = manager.add_event(event.clone()).unwrap();
-        assert_eq!(manager.list_events().len(), 1);
+        assert_eq!(manager.len(), 1);
+
+        manager.delete_event(id).unwrap();
+        assert_eq!(manager.len(), 0);
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags("urgent, travel"), ["urgent", "travel"]);
+        assert_eq!(
+            parse_tags("#urgent #travel,,  home"),
+            ["urgent", "travel", "home"]
+        );
+        assert_eq!(parse_tags("Work work WORK"), ["Work"]);
+        assert!(parse_tags(" , # ").is_empty());
+    }
+
+    #[test]
+    fn test_search_matches_tags() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let mut flight = EventBuilder::new()
+            .title("Flight")
+            .start(now)
+            .end(now + chrono::Duration::hours(3))
+            .build()
+            .unwrap();
+        flight.tags = parse_tags("travel urgent");
+        let id = manager.add_event(flight).unwrap();
+        manager
+            .add_event(
+                Event::new(
+                    "Dentist".to_string(),
+                    None,
+                    now,
+                    now + chrono::Duration::hours(1),
+                )
+                .unwrap(),
+            )
+            .unwrap();
 
+        let ids: Vec<Uuid> = manager.search("TRAVEL").iter().map(|e| e.id).collect();
+        assert_eq!(ids, [id]);
+        assert_eq!(manager.tags(), ["travel", "urgent"]);
+    }
+
+    #[test]
+    fn test_search() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let hour = chrono::Duration::hours(1);
+
+        let later = Event::new(
+            "Team Standup".to_string(),
+            None,
+            now + hour * 24,
+            now + hour * 25,
+        )
+        .unwrap();
+        let earlier = Event::new(
+            "Lunch".to_string(),
+            Some("Catch up with the team".to_string()),
+            now,
+            now + hour,
+        )
+        .unwrap();
+        let unrelated = Event::new("Dentist".to_string(), None, now, now + hour).unwrap();
+        manager.add_event(later.clone()).unwrap();
+        manager.add_event(earlier.clone()).unwrap();
+        manager.add_event(unrelated).unwrap();
+
+        let results = manager.search("TEAM");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, earlier.id);
+        assert_eq!(results[1].id, later.id);
+
+        assert!(manager.search("").is_empty());
+        assert!(manager.search("   ").is_empty());
+        assert!(manager.search("nothing").is_empty());
+    }
+
+    #[test]
+    fn test_undo_delete_restores_event() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let event = Event::new(
+            "Standup".to_string(),
+            Some("Daily sync".to_string()),
+            now,
+            now + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+
+        let id = manager.add_event(event.clone()).unwrap();
         manager.delete_event(id).unwrap();
-        assert_eq!(manager.list_events().len(), 0);
+        assert!(manager.get_event(id).is_none());
+
+        assert_eq!(
+            manager.last_operation(),
+            Some(&Operation::Delete(event.clone()))
+        );
+        manager.undo().unwrap();
+        assert_eq!(manager.get_event(id), Some(&event));
+
+        // Undoing the add removes the event again
+        manager.undo().unwrap();
+        assert!(manager.get_event(id).is_none());
+    }
+
+    #[test]
+    fn test_undo_edit_and_empty_stack() {
+        let mut manager = EventManager::new();
+        assert_eq!(manager.undo().unwrap_err().to_string(), "Nothing to undo");
+
+        let now = Local::now();
+        let later = now + chrono::Duration::hours(1);
+        let original = Event::new("Draft".to_string(), None, now, later).unwrap();
+        let id = manager.add_event(original.clone()).unwrap();
+        let renamed = Event::new("Final".to_string(), None, now, later).unwrap();
+        manager.edit_event(id, renamed).unwrap();
+
+        manager.undo().unwrap();
+        assert_eq!(manager.get_event(id), Some(&original));
+    }
+
+    #[test]
+    fn test_undo_stack_is_bounded() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let later = now + chrono::Duration::hours(1);
+        for i in 0..UNDO_LIMIT + 10 {
+            manager
+                .add_event(Event::new(format!("Event {i}"), None, now, later).unwrap())
+                .unwrap();
+        }
+
+        for _ in 0..UNDO_LIMIT {
+            manager.undo().unwrap();
+        }
+        assert!(manager.undo().is_err());
+        assert_eq!(manager.len(), 10);
+    }
+
+    #[test]
+    fn test_same_start_events_have_a_stable_order() {
+        let start = Local::now();
+        let end = start + chrono::Duration::hours(1);
+        let events: Vec<Event> = ["Sync", "Lunch", "Sync", "Call"]
+            .into_iter()
+            .map(|title| Event::new(title.to_string(), None, start, end).unwrap())
+            .collect();
+        let mut syncs: Vec<Uuid> = vec![events[0].id, events[2].id];
+        syncs.sort();
+
+        // Every manager hashes differently, so try a few insertion orders
+        for rotation in 0..events.len() {
+            let mut manager = EventManager::new();
+            for event in events.iter().cycle().skip(rotation).take(events.len()) {
+                manager.add_event(event.clone()).unwrap();
+            }
+            let expected = [events[3].id, events[1].id, syncs[0], syncs[1]];
+            let in_range: Vec<Uuid> = manager
+                .list_events_in_range(start, end)
+                .iter()
+                .map(|event| event.id)
+                .collect();
+            assert_eq!(in_range, expected);
+            let on_day: Vec<Uuid> = manager
+                .list_events_for_day(start)
+                .iter()
+                .map(|event| event.id)
+                .collect();
+            assert_eq!(on_day, expected);
+        }
+    }
+
+    #[test]
+    fn test_list_events_in_range() {
+        let mut manager = EventManager::new();
+        let start = Local::now();
+        let end = start + chrono::Duration::days(7);
+        let hour = chrono::Duration::hours(1);
+
+        let at_start = Event::new("At start".to_string(), None, start, start + hour).unwrap();
+        let at_end = Event::new("At end".to_string(), None, end, end + hour).unwrap();
+        let spanning_start =
+            Event::new("Spans start".to_string(), None, start - hour, start + hour).unwrap();
+        let spanning_end =
+            Event::new("Spans end".to_string(), None, end - hour, end + hour).unwrap();
+        for event in [&at_start, &at_end, &spanning_start, &spanning_end] {
+            manager.add_event(event.clone()).unwrap();
+        }
+
+        let ids: Vec<Uuid> = manager
+            .list_events_in_range(start, end)
+            .iter()
+            .map(|event| event.id)
+            .collect();
+        assert_eq!(ids, vec![at_start.id, spanning_end.id]);
+    }
+
+    #[test]
+    fn test_list_events_in_range_expands_recurrences() {
+        let mut manager = EventManager::new();
+        let first = Local.with_ymd_and_hms(2030, 5, 6, 9, 0, 0).unwrap();
+        let mut standup = Event::new(
+            "Standup".to_string(),
+            None,
+            first,
+            first + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        standup.recurrence = Some(Recurrence::new(Frequency::Weekly));
+        let id = manager.add_event(standup).unwrap();
+        let skipped = first + chrono::Duration::weeks(3);
+        manager.delete_occurrence(id, skipped.date_naive()).unwrap();
+
+        // From the second to the fifth week, leaving out the deleted fourth
+        let starts: Vec<_> = manager
+            .list_events_in_range(
+                first + chrono::Duration::days(1),
+                first + chrono::Duration::weeks(4) + chrono::Duration::hours(1),
+            )
+            .iter()
+            .map(|event| (event.id, event.start_time))
+            .collect();
+        assert_eq!(
+            starts,
+            [1, 2, 4].map(|weeks| (id, first.fixed_offset() + chrono::Duration::weeks(weeks)))
+        );
+    }
+
+    #[test]
+    fn test_has_events_on() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let event = Event::new(
+            "Test Event".to_string(),
+            None,
+            now,
+            now + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        manager.add_event(event).unwrap();
+
+        assert!(manager.has_events_on(now.date_naive()));
+        assert!(!manager.has_events_on(now.date_naive() + chrono::Duration::days(2)));
+    }
+
+    #[test]
+    fn test_count_events_for_day() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        for hour in [9, 11, 14] {
+            let event = Event::new(
+                "Meeting".to_string(),
+                None,
+                local(day, hour),
+                local(day, hour + 1),
+            )
+            .unwrap();
+            manager.add_event(event).unwrap();
+        }
+
+        assert_eq!(manager.count_events_for_day(day), 3);
+        assert_eq!(manager.count_events_for_day(day.succ_opt().unwrap()), 0);
+    }
+
+    fn local(date: NaiveDate, hour: u32) -> DateTime<Local> {
+        local_midnight(date) + chrono::Duration::hours(hour as i64)
+    }
+
+    #[test]
+    fn test_overnight_event_appears_on_both_days() {
+        let mut manager = EventManager::new();
+        let friday = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let saturday = friday.succ_opt().unwrap();
+        let event = Event::new(
+            "Late show".to_string(),
+            None,
+            local(friday, 23),
+            local(saturday, 2),
+        )
+        .unwrap();
+        manager.add_event(event).unwrap();
+
+        assert_eq!(manager.list_events_for_day(local(friday, 12)).len(), 1);
+        assert_eq!(manager.list_events_for_day(local(saturday, 12)).len(), 1);
+        assert!(manager
+            .list_events_for_day(local(saturday.succ_opt().unwrap(), 12))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_multi_day_event_appears_on_every_day() {
+        let mut manager = EventManager::new();
+        let first_day = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let conference = Event::new(
+            "Conference".to_string(),
+            None,
+            local(first_day, 9),
+            local(first_day + chrono::Duration::days(2), 17),
+        )
+        .unwrap();
+        // Ends exactly at midnight, so it must not leak into the next day
+        let until_midnight = Event::new(
+            "Until midnight".to_string(),
+            None,
+            local(first_day, 20),
+            local(first_day + chrono::Duration::days(1), 0),
+        )
+        .unwrap();
+        manager.add_event(conference).unwrap();
+        manager.add_event(until_midnight).unwrap();
+
+        assert_eq!(manager.list_events_for_day(local(first_day, 0)).len(), 2);
+        for offset in 1..3 {
+            let day = first_day + chrono::Duration::days(offset);
+            let events = manager.list_events_for_day(local(day, 0));
+            assert_eq!(events.len(), 1);
+            assert_eq!(events[0].title, "Conference");
+        }
+        assert!(!manager.has_events_on(first_day + chrono::Duration::days(3)));
+    }
+
+    #[test]
+    fn test_list_events_for_day_is_sorted() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 6, 3).unwrap();
+        let hour = chrono::Duration::hours(1);
+        let afternoon = Event::new(
+            "Afternoon".to_string(),
+            None,
+            local(day, 15),
+            local(day, 16),
+        )
+        .unwrap();
+        let morning =
+            Event::new("Morning".to_string(), None, local(day, 9), local(day, 10)).unwrap();
+        // Covers the whole day, so it sorts ahead of earlier timed events
+        let all_day = Event::new(
+            "Holiday".to_string(),
+            None,
+            local(day, 0),
+            local(day, 24) + hour,
+        )
+        .unwrap();
+        for event in [&afternoon, &morning, &all_day] {
+            manager.add_event(event.clone()).unwrap();
+        }
+
+        let titles: Vec<String> = manager
+            .list_events_for_day(local(day, 12))
+            .iter()
+            .map(|event| event.title.clone())
+            .collect();
+        assert_eq!(titles, vec!["Holiday", "Morning", "Afternoon"]);
+    }
+
+    #[test]
+    fn test_due_reminders() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+
+        let mut soon = Event::new(
+            "Soon".to_string(),
+            None,
+            now + Duration::minutes(10),
+            now + Duration::minutes(40),
+        )
+        .unwrap();
+        soon.reminder = Some(Duration::minutes(15));
+        let mut later = Event::new(
+            "Later".to_string(),
+            None,
+            now + Duration::hours(2),
+            now + Duration::hours(3),
+        )
+        .unwrap();
+        later.reminder = Some(Duration::minutes(15));
+        let mut started = Event::new(
+            "Started".to_string(),
+            None,
+            now - Duration::minutes(5),
+            now + Duration::minutes(30),
+        )
+        .unwrap();
+        started.reminder = Some(Duration::minutes(15));
+        let no_reminder = Event::new(
+            "Quiet".to_string(),
+            None,
+            now + Duration::minutes(1),
+            now + Duration::minutes(30),
+        )
+        .unwrap();
+        for event in [&soon, &later, &started, &no_reminder] {
+            manager.add_event(event.clone()).unwrap();
+        }
+
+        let due = manager.due_reminders(now - Duration::minutes(30), now);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, soon.id);
+
+        // Nothing new falls due in the following instant
+        assert!(manager
+            .due_reminders(now, now + Duration::seconds(1))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_due_reminders_for_later_occurrences() {
+        let mut manager = EventManager::new();
+        let first = Local.with_ymd_and_hms(2030, 5, 6, 9, 0, 0).unwrap();
+        let mut standup = Event::new(
+            "Standup".to_string(),
+            None,
+            first,
+            first + Duration::minutes(15),
+        )
+        .unwrap();
+        standup.reminder = Some(Duration::minutes(10));
+        standup.recurrence = Some(Recurrence::new(Frequency::Weekly));
+        let id = manager.add_event(standup).unwrap();
+        let second = first + Duration::weeks(1);
+        manager
+            .delete_occurrence(id, (first + Duration::weeks(2)).date_naive())
+            .unwrap();
+
+        let due = manager.due_reminders(second - Duration::hours(1), second - Duration::minutes(5));
+        assert_eq!(due.len(), 1);
+        assert_eq!((due[0].id, due[0].start_time), (id, second.fixed_offset()));
+        assert_eq!(
+            manager.occurrence(id, second.fixed_offset()).as_deref(),
+            Some(&*due[0])
+        );
+
+        // The deleted third week stays quiet
+        let third = first + Duration::weeks(2);
+        assert!(manager
+            .due_reminders(third - Duration::hours(1), third)
+            .is_empty());
+        assert!(manager.occurrence(id, third.fixed_offset()).is_none());
+    }
+
+    #[test]
+    fn test_event_keeps_its_own_zone() {
+        let new_york = FixedOffset::west_opt(5 * 3600).unwrap();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let at = |hour| {
+            new_york
+                .from_local_datetime(&day.and_hms_opt(hour, 0, 0).unwrap())
+                .unwrap()
+        };
+
+        // 23:00 in New York is already the next day in UTC and most of Europe
+        let late = Event::new(
+            "Late call".to_string(),
+            None,
+            at(23),
+            at(23) + Duration::minutes(30),
+        )
+        .unwrap();
+        assert_eq!(late.start_time.hour(), 23);
+        assert!(late.occurs_on(day));
+        assert!(!late.occurs_on(day.succ_opt().unwrap()));
+
+        // Local times are pinned to the system zone
+        let start = Local::now();
+        let event =
+            Event::new("Here".to_string(), None, start, start + Duration::hours(1)).unwrap();
+        assert_eq!(event.start_time.offset(), start.offset());
+        assert_eq!(event.start_time, start);
+    }
+
+    #[test]
+    fn test_add_event_checked_rejects_past_events() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let hour = Duration::hours(1);
+
+        let past = Event::new(
+            "Yesterday".to_string(),
+            None,
+            now - Duration::hours(25),
+            now - Duration::hours(24),
+        )
+        .unwrap();
+        let err = manager.add_event_checked(past.clone(), false).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PastEventError>(),
+            Some(&PastEventError {
+                title: "Yesterday".to_string()
+            })
+        );
+        assert!(manager.is_empty());
+        assert!(manager.add_event_checked(past, true).is_ok());
+
+        // Events still in progress or yet to come need no confirmation
+        let present = Event::new("Now".to_string(), None, now - hour, now + hour).unwrap();
+        assert!(manager.add_event_checked(present, false).is_ok());
+        let future = Event::new(
+            "Tomorrow".to_string(),
+            None,
+            now + Duration::hours(24),
+            now + Duration::hours(25),
+        )
+        .unwrap();
+        assert!(manager.add_event_checked(future, false).is_ok());
+        assert_eq!(manager.len(), 3);
+    }
+
+    #[test]
+    fn test_next_free_slot_on_free_day() {
+        let manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let half_hour = chrono::Duration::minutes(30);
+
+        // Before opening hours the slot starts when the window opens
+        assert_eq!(
+            manager.next_free_slot(local(day, 6), half_hour, (9, 17)),
+            Some(local(day, 9))
+        );
+        let mid_morning = local(day, 10) + chrono::Duration::minutes(15);
+        assert_eq!(
+            manager.next_free_slot(mid_morning, half_hour, (9, 17)),
+            Some(mid_morning)
+        );
+        assert_eq!(
+            manager.next_free_slot(local(day, 6), half_hour, (17, 9)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_next_free_slot_skips_back_to_back_meetings() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        for hour in [9, 10, 11] {
+            let event = Event::new(
+                "Meeting".to_string(),
+                None,
+                local(day, hour),
+                local(day, hour + 1),
+            )
+            .unwrap();
+            manager.add_event(event).unwrap();
+        }
+        // A 45 minute gap is too short for an hour-long slot
+        let lunch = local(day, 13) - chrono::Duration::minutes(15);
+        manager
+            .add_event(Event::new("Lunch".to_string(), None, lunch, local(day, 14)).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            manager.next_free_slot(local(day, 9), chrono::Duration::minutes(30), (9, 17)),
+            Some(local(day, 12))
+        );
+        assert_eq!(
+            manager.next_free_slot(local(day, 9), chrono::Duration::hours(1), (9, 17)),
+            Some(local(day, 14))
+        );
+    }
+
+    #[test]
+    fn test_next_free_slot_avoids_recurring_events() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let mut standup =
+            Event::new("Standup".to_string(), None, local(day, 9), local(day, 10)).unwrap();
+        standup.recurrence = Some(Recurrence::new(Frequency::Daily));
+        let id = manager.add_event(standup).unwrap();
+        let thursday = day + chrono::Duration::days(3);
+        manager.delete_occurrence(id, thursday).unwrap();
+
+        let wednesday = day + chrono::Duration::days(2);
+        assert_eq!(
+            manager.next_free_slot(local(wednesday, 9), chrono::Duration::minutes(30), (9, 17)),
+            Some(local(wednesday, 10))
+        );
+        // The deleted occurrence leaves the morning free
+        assert_eq!(
+            manager.next_free_slot(local(thursday, 9), chrono::Duration::minutes(30), (9, 17)),
+            Some(local(thursday, 9))
+        );
+    }
+
+    #[test]
+    fn test_next_free_slot_respects_working_hours() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let next_day = day.succ_opt().unwrap();
+        let late = local(day, 16) + chrono::Duration::minutes(30);
+
+        // Only 30 minutes remain today, so the hour moves to tomorrow morning
+        assert_eq!(
+            manager.next_free_slot(late, chrono::Duration::hours(1), (9, 17)),
+            Some(local(next_day, 9))
+        );
+
+        // Busy every working day for the whole horizon
+        for offset in 0..FREE_SLOT_SEARCH_DAYS {
+            let date = day + chrono::Duration::days(offset);
+            manager
+                .add_event(
+                    Event::new("Busy".to_string(), None, local(date, 9), local(date, 17)).unwrap(),
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            manager.next_free_slot(local(day, 0), chrono::Duration::minutes(15), (9, 17)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_delete_events_for_day() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let before = day.pred_opt().unwrap();
+        let after = day.succ_opt().unwrap();
+        for (date, hour) in [(before, 22), (day, 9), (day, 14), (after, 0)] {
+            let event = Event::new(
+                "Talk".to_string(),
+                None,
+                local(date, hour),
+                local(date, hour + 1),
+            )
+            .unwrap();
+            manager.add_event(event).unwrap();
+        }
+
+        assert_eq!(manager.delete_events_for_day(day), 2);
+        assert!(!manager.has_events_on(day));
+        assert_eq!(manager.count_events_for_day(before), 1);
+        assert_eq!(manager.count_events_for_day(after), 1);
+        assert_eq!(manager.delete_events_for_day(day), 0);
+
+        // The whole day comes back with a single undo
+        assert_eq!(
+            manager.last_operation().unwrap().to_string(),
+            "delete 2 events"
+        );
+        manager.undo().unwrap();
+        assert_eq!(manager.count_events_for_day(day), 2);
+
+        // A series only loses its occurrence on that day
+        let series = recurring(local(before, 8), Recurrence::new(Frequency::Daily));
+        let series = manager.add_event(series).unwrap();
+        assert_eq!(manager.delete_events_for_day(day), 3);
+        assert_eq!(manager.exceptions_for(series), [day]);
+        assert_eq!(manager.count_events_for_day(after), 2);
+        manager.undo().unwrap();
+        assert_eq!(manager.count_events_for_day(day), 3);
+    }
+
+    #[test]
+    fn test_delete_events_for_day_leaves_overnight_events() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let before = day.pred_opt().unwrap();
+        let after = day.succ_opt().unwrap();
+        // Every night from 22:00 to 02:00, and a trip from the day before
+        let mut night = Event::new(
+            "Night shift".to_string(),
+            None,
+            local(before, 22),
+            local(day, 2),
+        )
+        .unwrap();
+        night.recurrence = Some(Recurrence::new(Frequency::Daily));
+        let night = manager.add_event(night).unwrap();
+        let trip = Event::new("Trip".to_string(), None, local(before, 9), local(after, 9));
+        let trip = manager.add_event(trip.unwrap()).unwrap();
+
+        assert_eq!(manager.count_events_for_day(day), 3);
+        assert_eq!(manager.count_events_starting_on(day), 1);
+        assert_eq!(manager.delete_events_for_day(day), 1);
+        assert_eq!(manager.exceptions_for(night), [day]);
+        assert!(manager.get_event(trip).is_some());
+
+        // The shift that started the night before still runs into the morning
+        let titles: Vec<_> = manager
+            .list_events_for_day(local(day, 0))
+            .iter()
+            .map(|event| (event.title.clone(), event.start_time.date_naive()))
+            .collect();
+        assert!(titles.contains(&("Night shift".to_string(), before)));
+        assert!(!titles.contains(&("Night shift".to_string(), day)));
+    }
+
+    #[test]
+    fn test_list_events_is_chronological() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        for (title, hour) in [("Lunch", 12), ("Standup", 9), ("Review", 16), ("Coffee", 9)] {
+            let event = Event::new(
+                title.to_string(),
+                None,
+                local(day, hour),
+                local(day, hour + 1),
+            )
+            .unwrap();
+            manager.add_event(event).unwrap();
+        }
+
+        let titles: Vec<&str> = manager
+            .list_events()
+            .iter()
+            .map(|event| event.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Coffee", "Standup", "Lunch", "Review"]);
+    }
+
+    #[test]
+    fn test_busy_minutes_for_day() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let next_day = day.succ_opt().unwrap();
+        let standup = local(day, 9);
+        let events = [
+            (standup, standup + chrono::Duration::minutes(15)),
+            (local(day, 13), local(day, 15)),
+            // Only the hour before midnight counts towards the first day
+            (local(day, 23), local(next_day, 2)),
+        ];
+        for (start, end) in events {
+            manager
+                .add_event(Event::new("Busy".to_string(), None, start, end).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(manager.busy_minutes_for_day(day), 15 + 120 + 60);
+        assert_eq!(manager.busy_minutes_for_day(next_day), 120);
+        assert_eq!(
+            manager.busy_minutes_for_day(next_day.succ_opt().unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_busy_minutes_count_overlaps_once() {
+        let mut manager = EventManager::new();
+        let day = date(3, 14);
+        let events = [
+            (local(day, 9), local(day, 11)),
+            // Inside the first event, so adds nothing
+            (
+                local(day, 10),
+                local(day, 10) + chrono::Duration::minutes(30),
+            ),
+            // Overlaps the first event's last hour and runs one hour past it
+            (local(day, 10), local(day, 12)),
+            // Starts exactly when the busy block ends
+            (
+                local(day, 12),
+                local(day, 12) + chrono::Duration::minutes(30),
+            ),
+            (local(day, 15), local(day, 16)),
+        ];
+        for (start, end) in events {
+            manager
+                .add_event(Event::new("Busy".to_string(), None, start, end).unwrap())
+                .unwrap();
+        }
+
+        assert_eq!(manager.busy_minutes_for_day(day), 3 * 60 + 30 + 60);
+    }
+
+    fn recurring(start: DateTime<Local>, recurrence: Recurrence) -> Event {
+        let mut event = Event::new(
+            "Standup".to_string(),
+            None,
+            start,
+            start + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        event.recurrence = Some(recurrence);
+        event
+    }
+
+    fn occurrence_dates(event: &Event, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        event
+            .occurrences(local_midnight(from), local_midnight(to))
+            .iter()
+            .map(|start| {
+                assert_eq!(start.time(), event.start_time.time());
+                start.date_naive()
+            })
+            .collect()
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_single_event_occurrences() {
+        let event = Event::new(
+            "Once".to_string(),
+            None,
+            local(date(3, 14), 9),
+            local(date(3, 14), 10),
+        )
+        .unwrap();
+        assert_eq!(
+            occurrence_dates(&event, date(3, 1), date(4, 1)),
+            [date(3, 14)]
+        );
+        assert!(occurrence_dates(&event, date(3, 15), date(4, 1)).is_empty());
+    }
+
+    #[test]
+    fn test_daily_occurrences_stop_at_until() {
+        let mut rule = Recurrence::new(Frequency::Daily);
+        rule.until = Some(date(3, 16));
+        let event = recurring(local(date(3, 12), 9), rule);
+
+        // The range starts mid-series and runs past the end of it
+        assert_eq!(
+            occurrence_dates(&event, date(3, 14), date(3, 30)),
+            [date(3, 14), date(3, 15), date(3, 16)]
+        );
+
+        let mut every_other_day = Recurrence::new(Frequency::Daily);
+        every_other_day.interval = 2;
+        let event = recurring(local(date(3, 12), 9), every_other_day);
+        assert_eq!(
+            occurrence_dates(&event, date(3, 1), date(3, 18)),
+            [date(3, 12), date(3, 14), date(3, 16)]
+        );
+    }
+
+    #[test]
+    fn test_weekly_occurrences_on_weekdays() {
+        // Wednesday the 12th; the Monday of that week is before the series starts
+        let mut rule = Recurrence::new(Frequency::Weekly);
+        rule.weekdays = vec![
+            chrono::Weekday::Fri,
+            chrono::Weekday::Mon,
+            chrono::Weekday::Wed,
+        ];
+        let event = recurring(local(date(3, 12), 9), rule);
+        assert_eq!(
+            occurrence_dates(&event, date(3, 1), date(3, 22)),
+            [
+                date(3, 12),
+                date(3, 14),
+                date(3, 17),
+                date(3, 19),
+                date(3, 21)
+            ]
+        );
+
+        // Without weekdays it repeats on the weekday of the first occurrence
+        let mut fortnightly = Recurrence::new(Frequency::Weekly);
+        fortnightly.interval = 2;
+        let event = recurring(local(date(3, 12), 9), fortnightly);
+        assert_eq!(
+            occurrence_dates(&event, date(3, 1), date(4, 20)),
+            [date(3, 12), date(3, 26), date(4, 9)]
+        );
+    }
+
+    #[test]
+    fn test_monthly_occurrences_skip_short_months() {
+        let mut rule = Recurrence::new(Frequency::Monthly);
+        rule.until = Some(date(7, 30));
+        let event = recurring(local(date(1, 31), 9), rule);
+        assert_eq!(
+            occurrence_dates(&event, date(1, 1), date(12, 31)),
+            [date(1, 31), date(3, 31), date(5, 31)]
+        );
+    }
+
+    #[test]
+    fn test_recurrence_ends_at_last_supported_year() {
+        let last_year = |day| NaiveDate::from_ymd_opt(cal_core::MAX_YEAR, 12, day).unwrap();
+        let monthly = Recurrence::new(Frequency::Monthly);
+        assert_eq!(
+            monthly.dates(last_year(31)).collect::<Vec<_>>(),
+            [last_year(31)]
+        );
+        let mut weekly = Recurrence::new(Frequency::Weekly);
+        weekly.weekdays = vec![Weekday::Mon, Weekday::Sun];
+        assert!(weekly
+            .dates(last_year(1))
+            .all(|date| date.year() == cal_core::MAX_YEAR));
+
+        // Steps too large for chrono end the series rather than panic or spin
+        let mut rare = Recurrence::new(Frequency::Daily);
+        rare.interval = u32::MAX;
+        assert_eq!(rare.dates(last_year(1)).count(), 1);
+        let mut yearly = Recurrence::new(Frequency::Monthly);
+        yearly.interval = u32::MAX;
+        assert_eq!(yearly.dates(last_year(31)).count(), 1);
+        let beyond = NaiveDate::MAX.with_day(1).unwrap();
+        assert_eq!(monthly.dates(beyond).count(), 0);
+    }
+
+    #[test]
+    fn test_categories_are_distinct_and_sorted() {
+        let mut manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        for (hour, category) in [
+            (9, Some("work")),
+            (11, None),
+            (13, Some("home")),
+            (15, Some("work")),
+        ] {
+            let mut builder = EventBuilder::new()
+                .title("Errand")
+                .start(local(day, hour))
+                .end(local(day, hour + 1));
+            if let Some(category) = category {
+                builder = builder.category(category);
+            }
+            manager.add_event(builder.build().unwrap()).unwrap();
+        }
+        assert_eq!(manager.categories(), ["home", "work"]);
+    }
+
+    #[test]
+    fn test_recurring_events_listed_on_each_occurrence() {
+        let mut manager = EventManager::new();
+        let mut weekly = Recurrence::new(Frequency::Weekly);
+        weekly.until = Some(date(3, 28));
+        let event = recurring(local(date(3, 7), 9), weekly);
+        manager.add_event(event.clone()).unwrap();
+
+        fn on(manager: &EventManager, day: NaiveDate) -> Vec<Cow<'_, Event>> {
+            manager.list_events_for_day(local(day, 12))
+        }
+        assert_eq!(on(&manager, date(3, 7))[0].as_ref(), &event);
+        let later = on(&manager, date(3, 21));
+        assert_eq!(later.len(), 1);
+        assert_eq!(later[0].id, event.id);
+        assert_eq!(later[0].start_time, local(date(3, 21), 9));
+        assert_eq!(
+            later[0].end_time - later[0].start_time,
+            Duration::minutes(15)
+        );
+        assert!(on(&manager, date(3, 20)).is_empty());
+        assert!(!manager.has_events_on(date(4, 4)));
+        assert_eq!(manager.busy_minutes_for_day(date(3, 14)), 15);
+    }
+
+    #[test]
+    fn test_delete_occurrence_keeps_rest_of_series() {
+        let mut manager = EventManager::new();
+        let event = recurring(local(date(3, 7), 9), Recurrence::new(Frequency::Weekly));
+        let id = manager.add_event(event).unwrap();
+
+        manager.delete_occurrence(id, date(3, 14)).unwrap();
+        assert!(!manager.has_events_on(date(3, 14)));
+        for day in [date(3, 7), date(3, 21), date(3, 28)] {
+            assert_eq!(manager.count_events_for_day(day), 1, "{day}");
+        }
+        assert_eq!(manager.exceptions_for(id), [date(3, 14)]);
+        assert_eq!(
+            manager.last_operation().unwrap().to_string(),
+            "delete 'Standup' on 2025-03-14"
+        );
+
+        // Already deleted, not an occurrence, or not a series at all
+        assert!(manager.delete_occurrence(id, date(3, 14)).is_err());
+        assert!(manager.delete_occurrence(id, date(3, 15)).is_err());
+        let single = Event::new(
+            "Once".to_string(),
+            None,
+            local(date(3, 7), 12),
+            local(date(3, 7), 13),
+        )
+        .unwrap();
+        let single = manager.add_event(single).unwrap();
+        assert!(manager.delete_occurrence(single, date(3, 7)).is_err());
+
+        manager.undo().unwrap();
+        manager.undo().unwrap();
+        assert!(manager.has_events_on(date(3, 14)));
+        assert!(manager.exceptions_for(id).is_empty());
+    }
+
+    #[test]
+    fn test_day_with_events_search() {
+        let mut manager = EventManager::new();
+        let day = date(3, 14);
+        assert_eq!(manager.next_day_with_events(day), None);
+
+        // Runs overnight, so the 21st counts as well as the 20th
+        let late = Event::new(
+            "Late".to_string(),
+            None,
+            local(date(3, 20), 23),
+            local(date(3, 21), 1),
+        )
+        .unwrap();
+        manager.add_event(late).unwrap();
+        let early = Event::new(
+            "Early".to_string(),
+            None,
+            local(date(3, 2), 9),
+            local(date(3, 2), 10),
+        )
+        .unwrap();
+        manager.add_event(early).unwrap();
+
+        assert_eq!(manager.next_day_with_events(day), Some(date(3, 20)));
+        assert_eq!(manager.next_day_with_events(date(3, 20)), Some(date(3, 21)));
+        assert_eq!(manager.next_day_with_events(date(3, 21)), None);
+        assert_eq!(manager.previous_day_with_events(day), Some(date(3, 2)));
+        assert_eq!(manager.previous_day_with_events(date(3, 2)), None);
+    }
+
+    #[test]
+    fn test_hidden_calendars_leave_day_list() {
+        let mut manager = EventManager::new();
+        let day = date(3, 14);
+        for (title, calendar) in [("Standup", "work"), ("Gym", DEFAULT_CALENDAR)] {
+            let event = EventBuilder::new()
+                .title(title)
+                .calendar(calendar)
+                .start(local(day, 9))
+                .end(local(day, 10))
+                .build()
+                .unwrap();
+            manager.add_event(event).unwrap();
+        }
+        manager.add_calendar("family");
+        assert_eq!(manager.calendars(), ["family", "personal", "work"]);
+
+        manager.set_calendar_visible("work", false);
+        let titles: Vec<String> = manager
+            .list_events_for_day(local(day, 12))
+            .iter()
+            .map(|event| event.title.clone())
+            .collect();
+        assert_eq!(titles, ["Gym"]);
+        assert_eq!(manager.count_events_for_day(day), 1);
+        assert_eq!(manager.list_events().len(), 2);
+
+        manager.set_calendar_visible("work", true);
+        assert_eq!(manager.count_events_for_day(day), 2);
+    }
+
+    #[test]
+    fn test_clear_empties_manager() {
+        let mut manager = EventManager::new();
+        let now = Local::now();
+        let mut event = Event::new(
+            "Standup".to_string(),
+            None,
+            now,
+            now + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        event.recurrence = Some(Recurrence::new(Frequency::Daily));
+        let id = manager.add_event(event).unwrap();
+        manager
+            .delete_occurrence(id, (now + chrono::Duration::days(1)).date_naive())
+            .unwrap();
+        manager.add_calendar("work");
+
+        manager.clear();
+        assert!(manager.is_empty());
+        assert!(manager.exceptions_for(id).is_empty());
+        assert!(manager.undo().is_err());
+        assert!(manager.calendars().contains(&"work"));
+    }
+
+    #[test]
+    fn test_merge_disjoint_and_colliding() {
+        let now = Local::now();
+        let event = |title: &str| {
+            Event::new(
+                title.to_string(),
+                None,
+                now,
+                now + chrono::Duration::hours(1),
+            )
+            .unwrap()
+        };
+        let mut manager = EventManager::new();
+        let kept = manager.add_event(event("Kept")).unwrap();
+        let same = manager.add_event(event("Same")).unwrap();
+        let renamed = manager.add_event(event("Draft")).unwrap();
+
+        let mut other = EventManager::new();
+        other.add_event(event("New")).unwrap();
+        other
+            .add_event(manager.get_event(same).unwrap().clone())
+            .unwrap();
+        other
+            .add_event(Event {
+                id: renamed,
+                ..event("Final")
+            })
+            .unwrap();
+
+        let report = manager.merge(other);
+        assert_eq!((report.added, report.updated, report.skipped), (1, 1, 1));
+        assert_eq!(manager.len(), 4);
+        assert_eq!(manager.get_event(kept).unwrap().title, "Kept");
+        assert_eq!(manager.get_event(renamed).unwrap().title, "Final");
+
+        // Each change undoes on its own
+        manager.undo().unwrap();
+        manager.undo().unwrap();
+        assert_eq!(manager.len(), 3);
+        assert_eq!(manager.get_event(renamed).unwrap().title, "Draft");
+
+        let report = manager.merge(EventManager::new());
+        assert!(!report.changed());
+    }
+
+    #[test]
+    fn test_conflicts_on_day() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let at = |hour: i64, minutes: i64| {
+            local_midnight(day) + chrono::Duration::minutes(hour * 60 + minutes)
+        };
+        let mut manager = EventManager::new();
+        let mut add = |title: &str, start, end| {
+            manager
+                .add_event(Event::new(title.to_string(), None, start, end).unwrap())
+                .unwrap()
+        };
+        let review = add("Review", at(9, 0), at(10, 0));
+        let call = add("Call", at(9, 30), at(10, 30));
+        add("Lunch", at(12, 0), at(13, 0));
+        // Back to back is not a conflict
+        add("Walk", at(13, 0), at(13, 30));
+        let cancelled = add("Cancelled sync", at(12, 15), at(12, 45));
+        let mut event = manager.get_event(cancelled).unwrap().clone();
+        event.status = EventStatus::Cancelled;
+        manager.edit_event(cancelled, event).unwrap();
+
+        assert_eq!(manager.conflicts(day), HashSet::from([review, call]));
+        assert!(manager.conflicts(day.succ_opt().unwrap()).is_empty());
     }
 }
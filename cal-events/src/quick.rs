@@ -0,0 +1,307 @@
+use crate::{Event, EventManager, ImportReport, Operation};
+use anyhow::{anyhow, Result};
+use cal_core::local_midnight;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+
+/// Length of a quick event given only a start time.
+const DEFAULT_QUICK_DURATION: Duration = Duration::hours(1);
+
+/// Parses one-line input such as `Lunch 12:30-13:30`, `Dentist 15:00 1h` or
+/// `Call 3pm` into an event on `date`. Everything before the first time is the
+/// title; input without a time becomes an all-day event.
+pub fn parse_quick_event(input: &str, date: NaiveDate) -> Result<Event> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let time_index = words
+        .iter()
+        .position(|word| parse_time_range(word).is_some() || parse_time(word).is_some());
+    let (title_words, spec) = words.split_at(time_index.unwrap_or(words.len()));
+
+    let title = title_words.join(" ");
+    if title.is_empty() {
+        return Err(anyhow!("Quick event needs a title before the time"));
+    }
+
+    // Allow spaces around the dash of a range, e.g. "18:00 - 19:30"
+    let spec = spec.join(" ").replace(" - ", "-");
+    let spec: Vec<&str> = spec.split_whitespace().collect();
+    let at = |time: NaiveTime| at_local_time(date, time);
+
+    let (start, end) = match spec.as_slice() {
+        [] => (
+            local_midnight(date),
+            local_midnight(date + Duration::days(1)),
+        ),
+        [range] if range.contains('-') => {
+            let (start, end) =
+                parse_time_range(range).ok_or_else(|| anyhow!("Invalid time range '{}'", range))?;
+            let start = at(start)?;
+            let mut end = at(end)?;
+            // A range such as 23:00-01:00 ends the next day
+            if end <= start {
+                end += Duration::days(1);
+            }
+            (start, end)
+        }
+        [time] => {
+            let start = at(parse_time(time).unwrap())?;
+            (start, start + DEFAULT_QUICK_DURATION)
+        }
+        [time, duration] if parse_time(time).is_some() => {
+            let start = at(parse_time(time).unwrap())?;
+            let end = start
+                .checked_add_signed(parse_duration(duration)?)
+                .ok_or_else(|| anyhow!("Duration '{}' is too long", duration))?;
+            (start, end)
+        }
+        _ => return Err(anyhow!("Could not understand '{}'", spec.join(" "))),
+    };
+
+    Event::new(title, None, start, end)
+}
+
+impl EventManager {
+    /// Adds one event per line of `DATE TIME DURATION TITLE`, e.g.
+    /// `2025-03-14 15:00 1h Dentist`, skipping blank lines and `#` comments.
+    /// Lines that cannot be read are left out and reported by line number;
+    /// each added event can be undone on its own.
+    pub fn import_plain(&mut self, content: &str) -> ImportReport {
+        let mut report = ImportReport::default();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_plain_line(line) {
+                Ok(event) => {
+                    self.record(Operation::Add(event.clone()));
+                    self.events.insert(event.id, event);
+                    report.added += 1;
+                }
+                Err(err) => report.errors.push(format!("Line {}: {err:#}", index + 1)),
+            }
+        }
+        report
+    }
+}
+
+/// One `DATE TIME DURATION TITLE` line, handed to the quick-event parser as
+/// `TITLE TIME DURATION`.
+fn parse_plain_line(line: &str) -> Result<Event> {
+    let mut words = line.split_whitespace();
+    let (Some(date), Some(time), Some(duration)) = (words.next(), words.next(), words.next())
+    else {
+        return Err(anyhow!("Expected DATE TIME DURATION TITLE"));
+    };
+    let title = words.collect::<Vec<_>>().join(" ");
+    if title.is_empty() {
+        return Err(anyhow!("Expected DATE TIME DURATION TITLE"));
+    }
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+    if parse_time(time).is_none() {
+        return Err(anyhow!("Invalid time '{}'", time));
+    }
+    parse_duration(duration)?;
+    parse_quick_event(&format!("{title} {time} {duration}"), date)
+}
+
+/// Parses durations such as `90m`, `2h` or `1h15m`.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let invalid = || {
+        anyhow!(
+            "Invalid duration '{}', expected e.g. 15m, 1h or 1h30m",
+            input
+        )
+    };
+
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+    for c in input.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let amount: i64 = digits.parse().map_err(|_| invalid())?;
+        let part = match c.to_ascii_lowercase() {
+            'd' => Duration::try_days(amount),
+            'h' => Duration::try_hours(amount),
+            'm' => Duration::try_minutes(amount),
+            _ => return Err(invalid()),
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total.is_zero() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+fn at_local_time(date: NaiveDate, time: NaiveTime) -> Result<DateTime<Local>> {
+    Local
+        .from_local_datetime(&date.and_time(time))
+        .earliest()
+        .ok_or_else(|| anyhow!("{} {} does not exist in the local time zone", date, time))
+}
+
+fn parse_time_range(word: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = word.split_once('-')?;
+    Some((parse_time(start)?, parse_time(end)?))
+}
+
+/// Accepts `9:00`, `15:30`, `3pm` and `9:30am`.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    let word = word.to_ascii_lowercase();
+    let (clock, pm) = match word.strip_suffix("pm") {
+        Some(clock) => (clock, Some(true)),
+        None => match word.strip_suffix("am") {
+            Some(clock) => (clock, Some(false)),
+            None => (word.as_str(), None),
+        },
+    };
+
+    let (hour, minute): (u32, u32) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        None if pm.is_some() => (clock.parse().ok()?, 0),
+        _ => return None,
+    };
+    let hour = match pm {
+        Some(pm) if (1..=12).contains(&hour) => hour % 12 + if pm { 12 } else { 0 },
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Timelike;
+
+    fn day() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()
+    }
+
+    fn times(event: &Event) -> (String, String) {
+        (
+            event.start_time.format("%d %H:%M").to_string(),
+            event.end_time.format("%d %H:%M").to_string(),
+        )
+    }
+
+    #[test]
+    fn test_quick_event_with_range() {
+        let event = parse_quick_event("Lunch 12:30-13:30", day()).unwrap();
+        assert_eq!(event.title, "Lunch");
+        assert_eq!(times(&event), ("14 12:30".into(), "14 13:30".into()));
+
+        let event = parse_quick_event("Team sync 9:00 - 9:45", day()).unwrap();
+        assert_eq!(event.title, "Team sync");
+        assert_eq!(times(&event), ("14 09:00".into(), "14 09:45".into()));
+
+        // Ranges past midnight end on the following day
+        let event = parse_quick_event("Night shift 23:00-01:00", day()).unwrap();
+        assert_eq!(times(&event), ("14 23:00".into(), "15 01:00".into()));
+    }
+
+    #[test]
+    fn test_quick_event_with_duration() {
+        let event = parse_quick_event("Dentist 15:00 1h", day()).unwrap();
+        assert_eq!(event.title, "Dentist");
+        assert_eq!(times(&event), ("14 15:00".into(), "14 16:00".into()));
+
+        let event = parse_quick_event("Call mom 3pm 1h30m", day()).unwrap();
+        assert_eq!(event.title, "Call mom");
+        assert_eq!(times(&event), ("14 15:00".into(), "14 16:30".into()));
+    }
+
+    #[test]
+    fn test_quick_event_with_start_only() {
+        let event = parse_quick_event("Standup 9:30am", day()).unwrap();
+        assert_eq!(event.start_time.hour(), 9);
+        assert_eq!(event.end_time - event.start_time, DEFAULT_QUICK_DURATION);
+
+        let event = parse_quick_event("Midnight snack 12am", day()).unwrap();
+        assert_eq!(event.start_time.hour(), 0);
+    }
+
+    #[test]
+    fn test_quick_event_without_time_is_all_day() {
+        let event = parse_quick_event("Mom's birthday", day()).unwrap();
+        assert_eq!(event.title, "Mom's birthday");
+        assert!(event.is_all_day_on(day()));
+        assert!(!event.occurs_on(day().succ_opt().unwrap()));
+    }
+
+    #[test]
+    fn test_quick_event_rejects_bad_input() {
+        assert!(parse_quick_event("", day()).is_err());
+        assert!(parse_quick_event("15:00 1h", day()).is_err());
+        assert!(parse_quick_event("Dentist 15:00 soon", day()).is_err());
+        assert!(parse_quick_event("Dentist 15:00 1h extra", day()).is_err());
+        assert!(parse_quick_event("Dentist 15:00 99999999d", day()).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_huge_values() {
+        for input in [
+            "99999999999999d",
+            "9999999999999999h",
+            "9223372036854775807m",
+            "106751991167300d1d",
+        ] {
+            assert!(
+                parse_duration(input)
+                    .unwrap_err()
+                    .to_string()
+                    .starts_with("Invalid duration"),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_import_plain() {
+        let content = "\
+# Team calendar for March
+2025-03-14 15:00 1h Dentist
+
+  2025-03-17\t9:30am  15m   Standup with design
+# 2025-03-18 10:00 1h Skipped
+";
+        let mut manager = EventManager::new();
+        let report = manager.import_plain(content);
+        assert_eq!((report.added, report.errors.len()), (2, 0));
+        let events = manager.list_events();
+        assert_eq!(events[0].title, "Dentist");
+        assert_eq!(times(events[0]), ("14 15:00".into(), "14 16:00".into()));
+        assert_eq!(events[1].title, "Standup with design");
+        assert_eq!(times(events[1]), ("17 09:30".into(), "17 09:45".into()));
+    }
+
+    #[test]
+    fn test_import_plain_reports_every_bad_line() {
+        let content = "\
+2025-03-14 15:00 1h Dentist
+14/03/2025 15:00 1h Wrong date
+2025-03-14 15:00 soon Wrong duration
+2025-03-14 15:00 1h
+";
+        let mut manager = EventManager::new();
+        let report = manager.import_plain(content);
+        assert_eq!(report.added, 1);
+        let errors = &report.errors;
+        assert_eq!(errors.len(), 3, "{errors:?}");
+        assert!(errors[0].starts_with("Line 2: Invalid date"));
+        assert!(errors[1].starts_with("Line 3: Invalid duration"));
+        assert!(errors[2].starts_with("Line 4: Expected"));
+        assert_eq!(manager.list_events()[0].title, "Dentist");
+
+        // The good lines come back out one undo at a time
+        manager.undo().unwrap();
+        assert!(manager.is_empty());
+    }
+}
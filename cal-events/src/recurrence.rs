@@ -0,0 +1,92 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// How an event repeats after its first occurrence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    /// Repeat every `interval` days, weeks or months; 0 is treated as 1.
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    /// Days a weekly rule falls on. Empty means the weekday of the first occurrence.
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
+    /// Last date an occurrence may start on, inclusive.
+    #[serde(default)]
+    pub until: Option<NaiveDate>,
+}
+
+fn default_interval() -> u32 {
+    1
+}
+
+impl Recurrence {
+    pub fn new(frequency: Frequency) -> Self {
+        Self {
+            frequency,
+            interval: 1,
+            weekdays: Vec::new(),
+            until: None,
+        }
+    }
+
+    /// Every date the series starts on, in order, beginning with `first`.
+    /// Monthly rules skip months too short for the first date's day, as in RFC 5545.
+    /// The series ends with `until` or the last supported year at the latest.
+    pub fn dates(&self, first: NaiveDate) -> impl Iterator<Item = NaiveDate> + '_ {
+        let within = move |date: &NaiveDate| {
+            date.year() <= cal_core::MAX_YEAR && self.until.is_none_or(|until| *date <= until)
+        };
+        (0..)
+            .map_while(move |period| self.period_start(first, period))
+            .take_while(within)
+            .flat_map(move |start| self.period_dates(first, start))
+            .filter(move |date| *date >= first)
+            .take_while(within)
+    }
+
+    /// First day of the `period`th repetition after the one containing `first`,
+    /// or `None` once that is past the dates chrono can represent.
+    fn period_start(&self, first: NaiveDate, period: u32) -> Option<NaiveDate> {
+        let step = period.checked_mul(self.interval.max(1))?;
+        match self.frequency {
+            Frequency::Daily => first.checked_add_days(Days::new(step.into())),
+            Frequency::Weekly => first
+                .checked_sub_days(Days::new(first.weekday().num_days_from_monday().into()))?
+                .checked_add_days(Days::new(u64::from(step) * 7)),
+            Frequency::Monthly => first.with_day(1)?.checked_add_months(Months::new(step)),
+        }
+    }
+
+    /// Dates falling in the repetition starting on `start`.
+    fn period_dates(&self, first: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        match self.frequency {
+            Frequency::Daily => vec![start],
+            Frequency::Weekly => {
+                let mut offsets: Vec<u32> = if self.weekdays.is_empty() {
+                    vec![first.weekday().num_days_from_monday()]
+                } else {
+                    self.weekdays
+                        .iter()
+                        .map(|day| day.num_days_from_monday())
+                        .collect()
+                };
+                offsets.sort_unstable();
+                offsets.dedup();
+                offsets
+                    .into_iter()
+                    .filter_map(|offset| start.checked_add_days(Days::new(offset.into())))
+                    .collect()
+            }
+            Frequency::Monthly => start.with_day(first.day()).into_iter().collect(),
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{Event, EventManager};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// An event as written to the events file, along with the dates of any
+/// occurrences deleted from it.
+#[derive(Serialize, Deserialize)]
+struct StoredEvent<E> {
+    #[serde(flatten)]
+    event: E,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exceptions: Vec<NaiveDate>,
+}
+
+/// Events read by [`EventManager::load_from_file`].
+#[derive(Debug)]
+pub struct LoadedEvents {
+    pub manager: EventManager,
+    /// Whether the events file was corrupt and its backup was read instead.
+    pub from_backup: bool,
+}
+
+impl EventManager {
+    /// Writes every event to `path` as JSON, creating its directory if needed.
+    /// The file is replaced in one step, so a crash leaves either the old or
+    /// the new version, and the old version is kept next to it as a backup
+    /// unless it is itself corrupt.
+    pub fn save_to_file(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let stored: Vec<StoredEvent<&Event>> = self
+            .list_events()
+            .into_iter()
+            .map(|event| StoredEvent {
+                exceptions: self.exceptions_for(event.id),
+                event,
+            })
+            .collect();
+        let json = serde_json::to_string_pretty(&stored)?;
+
+        let temp = with_suffix(path, ".tmp");
+        std::fs::File::create(&temp)
+            .and_then(|mut file| {
+                file.write_all(json.as_bytes())?;
+                file.sync_all()
+            })
+            .with_context(|| format!("Failed to write {}", temp.display()))?;
+        // A corrupt file would overwrite the last good backup
+        if read_events(path).is_ok() {
+            let backup = backup_path(path);
+            std::fs::copy(path, &backup)
+                .with_context(|| format!("Failed to write {}", backup.display()))?;
+        }
+        std::fs::rename(&temp, path).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Reads events written by `save_to_file`, falling back to the backup
+    /// if the file is corrupt. A missing file is an empty calendar; loading
+    /// does not create any undo history.
+    pub fn load_from_file(path: &Path) -> Result<LoadedEvents> {
+        if !path.exists() {
+            return Ok(LoadedEvents {
+                manager: Self::new(),
+                from_backup: false,
+            });
+        }
+        let backup = backup_path(path);
+        match read_events(path) {
+            Ok(manager) => Ok(LoadedEvents {
+                manager,
+                from_backup: false,
+            }),
+            Err(err) if backup.exists() => {
+                let manager = read_events(&backup).map_err(|_| err)?;
+                Ok(LoadedEvents {
+                    manager,
+                    from_backup: true,
+                })
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Where `save_to_file` keeps the previous version of `path`.
+fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn read_events(path: &Path) -> Result<EventManager> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let events: Vec<StoredEvent<Event>> = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid events file {}", path.display()))?;
+    let mut manager = EventManager::new();
+    for StoredEvent { event, exceptions } in events {
+        manager
+            .exceptions
+            .extend(exceptions.into_iter().map(|date| (event.id, date)));
+        manager.events.insert(event.id, event);
+    }
+    Ok(manager)
+}
+
+/// Stores an optional `Duration` as a whole number of minutes.
+pub(crate) mod optional_minutes {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value
+            .map(|duration| duration.num_minutes())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<i64>::deserialize(deserializer)?.map(Duration::minutes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventStatus, Frequency, Priority, Recurrence};
+    use chrono::{Duration, Local};
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir()
+            .join(format!("cal-rs-test-{}", uuid::Uuid::new_v4()))
+            .join("events.json")
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = temp_path();
+        let start = Local::now();
+        let mut event = Event::new(
+            "Planning".to_string(),
+            Some("Quarterly\nBring the numbers".to_string()),
+            start,
+            start + Duration::hours(2),
+        )
+        .unwrap();
+        event.reminder = Some(Duration::minutes(15));
+        event.priority = Priority::High;
+        event.status = EventStatus::Tentative;
+        event.attendees = vec!["ana@example.com".to_string()];
+        event.location = Some("Room 4".to_string());
+
+        let mut manager = EventManager::new();
+        manager.add_event(event.clone()).unwrap();
+        manager.save_to_file(&path).unwrap();
+
+        let mut loaded = EventManager::load_from_file(&path).unwrap().manager;
+        assert_eq!(loaded.list_events(), vec![&event]);
+        assert!(loaded.undo().is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_deleted_occurrences_survive_reload() {
+        let path = temp_path();
+        let start = Local::now();
+        let mut event = Event::new(
+            "Standup".to_string(),
+            None,
+            start,
+            start + Duration::minutes(15),
+        )
+        .unwrap();
+        event.recurrence = Some(Recurrence::new(Frequency::Daily));
+        let skipped = (start + Duration::days(2)).date_naive();
+
+        let mut manager = EventManager::new();
+        let id = manager.add_event(event).unwrap();
+        manager.delete_occurrence(id, skipped).unwrap();
+        manager.save_to_file(&path).unwrap();
+
+        let loaded = EventManager::load_from_file(&path).unwrap().manager;
+        assert_eq!(loaded.exceptions_for(id), [skipped]);
+        assert!(!loaded.has_events_on(skipped));
+        assert!(loaded.has_events_on(skipped.succ_opt().unwrap()));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_file_recovers_from_backup() {
+        let path = temp_path();
+        let start = Local::now();
+        let mut manager = EventManager::new();
+        let first = Event::new("First".to_string(), None, start, start + Duration::hours(1));
+        manager.add_event(first.unwrap()).unwrap();
+        manager.save_to_file(&path).unwrap();
+        assert!(!backup_path(&path).exists());
+
+        let second = Event::new(
+            "Second".to_string(),
+            None,
+            start,
+            start + Duration::hours(1),
+        );
+        manager.add_event(second.unwrap()).unwrap();
+        manager.save_to_file(&path).unwrap();
+        let loaded = EventManager::load_from_file(&path).unwrap();
+        assert_eq!(loaded.manager.len(), 2);
+        assert!(!loaded.from_backup);
+
+        // A crash mid-write left the main file truncated
+        std::fs::write(&path, "[{ \"title\": ").unwrap();
+        let loaded = EventManager::load_from_file(&path).unwrap();
+        assert!(loaded.from_backup);
+        let titles: Vec<_> = loaded
+            .manager
+            .list_events()
+            .iter()
+            .map(|e| e.title.clone())
+            .collect();
+        assert_eq!(titles, ["First"]);
+
+        // Saving over the corrupt file keeps the good backup
+        manager.save_to_file(&path).unwrap();
+        assert_eq!(read_events(&backup_path(&path)).unwrap().len(), 1);
+
+        std::fs::write(&path, "[{ \"title\": ").unwrap();
+        std::fs::write(backup_path(&path), "not json").unwrap();
+        assert!(EventManager::load_from_file(&path)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid events file"));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_load_missing_or_corrupt_file() {
+        let path = temp_path();
+        assert!(EventManager::load_from_file(&path)
+            .unwrap()
+            .manager
+            .is_empty());
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "[{ not json").unwrap();
+        assert!(EventManager::load_from_file(&path).is_err());
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
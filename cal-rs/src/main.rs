@@ -1,6 +1,27 @@
-use anyhow::Result;
-use cal_tui;
+use cal_tui::RunOptions;
+use clap::Parser;
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
-    cal_tui::run()
+/// A terminal calendar.
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// Show events without allowing any change to them
+    #[arg(long)]
+    read_only: bool,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let options = RunOptions {
+        read_only: args.read_only,
+    };
+
+    match cal_tui::run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("cal-rs: {err}");
+            ExitCode::FAILURE
+        }
+    }
 }
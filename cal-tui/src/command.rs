@@ -0,0 +1,125 @@
+use crate::ViewMode;
+use anyhow::{anyhow, Result};
+use cal_core::parse_relative_date;
+use chrono::{Local, NaiveDate};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ics,
+    Csv,
+}
+
+/// A line typed at the `:` prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Delete the event with this title, ignoring case.
+    Delete(String),
+    GoTo(NaiveDate),
+    Export(ExportFormat),
+    View(ViewMode),
+    /// Add a calendar if it is new and send new events to it.
+    Calendar(String),
+    Save,
+}
+
+impl Command {
+    /// Whether running the command changes events or the events file.
+    pub fn modifies_events(&self) -> bool {
+        matches!(
+            self,
+            Command::Delete(_) | Command::Calendar(_) | Command::Save
+        )
+    }
+
+    /// Parses commands such as `delete Standup`, `goto 2025-03-14`,
+    /// `export ics`, `calendar work` or `week`.
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim();
+        let (name, argument) = match input.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (input, ""),
+        };
+
+        match (name.to_ascii_lowercase().as_str(), argument) {
+            ("", _) => Err(anyhow!("Type a command, e.g. goto 2025-03-14")),
+            ("delete", "") => Err(anyhow!("Usage: delete <title>")),
+            ("delete", title) => Ok(Command::Delete(title.to_string())),
+            ("goto", "") => Err(anyhow!("Usage: goto <date>")),
+            ("goto", date) => parse_relative_date(date, Local::now().date_naive())
+                .map(Command::GoTo)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Invalid date '{}', try YYYY-MM-DD, tomorrow, next fri or +3d",
+                        date
+                    )
+                }),
+            ("export", "ics") => Ok(Command::Export(ExportFormat::Ics)),
+            ("export", "csv") => Ok(Command::Export(ExportFormat::Csv)),
+            ("export", format) => Err(anyhow!(
+                "Unknown export format '{}', use ics or csv",
+                format
+            )),
+            ("month", "") => Ok(Command::View(ViewMode::Month)),
+            ("week", "") => Ok(Command::View(ViewMode::Week)),
+            ("day", "") => Ok(Command::View(ViewMode::Day)),
+            ("year", "") => Ok(Command::View(ViewMode::Year)),
+            ("agenda", "") => Ok(Command::View(ViewMode::Agenda)),
+            ("calendar", "") => Err(anyhow!("Usage: calendar <name>")),
+            ("calendar", name) => Ok(Command::Calendar(name.to_string())),
+            ("save", "") => Ok(Command::Save),
+            _ => Err(anyhow!("Unknown command '{}'", input)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse("delete Team Standup").unwrap(),
+            Command::Delete("Team Standup".to_string())
+        );
+        assert_eq!(
+            Command::parse("  goto 2025-03-14 ").unwrap(),
+            Command::GoTo(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap())
+        );
+        assert_eq!(
+            Command::parse("export csv").unwrap(),
+            Command::Export(ExportFormat::Csv)
+        );
+        assert_eq!(
+            Command::parse("goto tomorrow").unwrap(),
+            Command::GoTo(Local::now().date_naive().succ_opt().unwrap())
+        );
+        assert_eq!(
+            Command::parse("calendar work").unwrap(),
+            Command::Calendar("work".to_string())
+        );
+        assert_eq!(
+            Command::parse("WEEK").unwrap(),
+            Command::View(ViewMode::Week)
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_commands() {
+        for input in [
+            "",
+            "delete",
+            "calendar",
+            "goto 14/03/2025",
+            "export pdf",
+            "week 3",
+            "frobnicate",
+        ] {
+            assert!(Command::parse(input).is_err(), "{input}");
+        }
+        assert_eq!(
+            Command::parse("frobnicate now").unwrap_err().to_string(),
+            "Unknown command 'frobnicate now'"
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use crate::keys::KeyConfig;
+use crate::labels::{MonthLabels, WeekdayLabels};
+use crate::theme::{Theme, ThemeFile};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// The on-disk layout of `config.toml`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct ConfigFile {
+    keys: HashMap<String, String>,
+    theme: Option<ThemeFile>,
+    weekdays: WeekdayLabels,
+    months: MonthLabels,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub keys: KeyConfig,
+    /// Only set when `config.toml` has a `[theme]` table, which then takes
+    /// precedence over the preset remembered in the preferences.
+    pub theme: Option<Theme>,
+    pub weekday_labels: WeekdayLabels,
+    pub month_labels: MonthLabels,
+}
+
+impl Config {
+    /// `~/.config/cal-rs/config.toml`, or the platform equivalent.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("cal-rs").join("config.toml"))
+    }
+
+    /// Loads the user's config, falling back to the defaults when there is none.
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) if path.exists() => {
+                let content = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                Self::from_toml(&content).with_context(|| format!("Invalid {}", path.display()))
+            }
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let file: ConfigFile = toml::from_str(content)?;
+        Ok(Self {
+            keys: KeyConfig::with_overrides(&file.keys)?,
+            theme: file.theme.map(Theme::from_file).transpose()?,
+            weekday_labels: file.weekdays,
+            month_labels: file.months,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keys::{Action, KeyBinding};
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn test_custom_key_mapping() {
+        let config = Config::from_toml(
+            r#"
+            [keys]
+            page_next = "n"
+
+            [theme]
+            preset = "high-contrast"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme, Some(Theme::high_contrast()));
+
+        assert_eq!(
+            config.keys.binding(Action::PageNext).code,
+            KeyCode::Char('n')
+        );
+        assert_eq!(
+            config.keys.binding(Action::ViewWeek),
+            KeyBinding::parse("w").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::from_toml("").unwrap();
+        assert_eq!(config.keys, KeyConfig::default());
+        assert_eq!(config.theme, None);
+        assert_eq!(config.weekday_labels, WeekdayLabels::default());
+        assert_eq!(config.month_labels, MonthLabels::default());
+    }
+
+    #[test]
+    fn test_weekday_labels() {
+        let config = Config::from_toml(
+            r#"
+            [weekdays]
+            short = ["Lu", "Ma", "Me", "Je", "Ve", "Sa", "Di"]
+            "#,
+        )
+        .unwrap();
+        let labels = config.weekday_labels;
+        assert_eq!(labels.short(chrono::Weekday::Sun), "Di");
+        // Forms left out keep their English defaults
+        assert_eq!(labels.narrow(chrono::Weekday::Sun), "S");
+
+        assert!(Config::from_toml("[weekdays]\nshort = [\"Mo\", \"Tu\"]").is_err());
+    }
+
+    #[test]
+    fn test_month_labels() {
+        let config = Config::from_toml(
+            r#"
+            [months]
+            full = ["janvier", "février", "mars", "avril", "mai", "juin", "juillet",
+                    "août", "septembre", "octobre", "novembre", "décembre"]
+            "#,
+        )
+        .unwrap();
+        let months = config.month_labels;
+        assert_eq!(months.month_name(2), "février");
+        assert_eq!(months.month_name(13), "");
+        let date = chrono::NaiveDate::from_ymd_opt(2025, 3, 4).unwrap();
+        assert_eq!(months.long_date(date), "mars 4, 2025");
+        assert_eq!(MonthLabels::default().day_and_month(date), "March 4");
+
+        assert!(Config::from_toml("[months]\nfull = [\"Jan\"]").is_err());
+    }
+}
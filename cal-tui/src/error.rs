@@ -0,0 +1,65 @@
+use std::fmt;
+use std::io;
+
+/// An error that stops the app. Mistakes in what the user types are shown in
+/// the status bar instead and never end up here.
+#[derive(Debug)]
+pub enum AppError {
+    /// Setting up, drawing to or reading from the terminal failed.
+    Terminal(io::Error),
+    /// Preferences could not be written on the way out.
+    SavePreferences(anyhow::Error),
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Terminal(err) => write!(
+                f,
+                "Terminal error: {err}\nMake sure cal-rs runs in an interactive terminal."
+            ),
+            AppError::SavePreferences(err) => write!(
+                f,
+                "Could not save preferences: {err:#}\nCheck that the config directory is writable."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Terminal(err) => Some(err),
+            AppError::SavePreferences(err) => Some(err.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        AppError::Terminal(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_messages_are_user_facing() {
+        let terminal = AppError::from(io::Error::new(io::ErrorKind::BrokenPipe, "pipe closed"));
+        assert_eq!(
+            terminal.to_string(),
+            "Terminal error: pipe closed\nMake sure cal-rs runs in an interactive terminal."
+        );
+
+        let cause: anyhow::Result<()> =
+            Err(io::Error::from(io::ErrorKind::PermissionDenied).into());
+        let save =
+            AppError::SavePreferences(cause.context("Failed to write prefs.json").unwrap_err());
+        assert!(save.to_string().starts_with(
+            "Could not save preferences: Failed to write prefs.json: permission denied"
+        ));
+    }
+}
@@ -0,0 +1,713 @@
+use anyhow::{anyhow, Result};
+use chrono::Weekday;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    Help,
+    ViewMonth,
+    ViewWeek,
+    ViewDay,
+    ViewYear,
+    CopyEvent,
+    ViewAgenda,
+    AgendaRange,
+    ToggleWeekStart,
+    CycleTheme,
+    ToggleHeatmap,
+    ToggleWeekNumbers,
+    ToggleFitMonth,
+    ToggleTimeFormat,
+    ToggleOffHours,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    PagePrev,
+    PageNext,
+    ScrollTimelineUp,
+    ScrollTimelineDown,
+    NextPanel,
+    Select,
+    Today,
+    FirstDayOfMonth,
+    LastDayOfMonth,
+    NextEventDay,
+    PreviousEventDay,
+    /// Select this day of the selected week.
+    SelectWeekday(Weekday),
+    GoToDate,
+    AddEvent,
+    QuickAdd,
+    Duplicate,
+    ExtendEvent,
+    ShrinkEvent,
+    MoveEventEarlier,
+    MoveEventLater,
+    DeleteDay,
+    DeleteOccurrence,
+    CycleCategory,
+    CycleTag,
+    ToggleCancelled,
+    CycleCalendar,
+    ToggleCalendar,
+    Search,
+    Import,
+    Export,
+    ExportCsv,
+    Undo,
+    Command,
+}
+
+impl Action {
+    /// Whether the action adds, changes or removes events, which read-only
+    /// mode refuses.
+    pub fn modifies_events(self) -> bool {
+        matches!(
+            self,
+            Action::AddEvent
+                | Action::QuickAdd
+                | Action::Duplicate
+                | Action::ExtendEvent
+                | Action::ShrinkEvent
+                | Action::MoveEventEarlier
+                | Action::MoveEventLater
+                | Action::DeleteDay
+                | Action::DeleteOccurrence
+                | Action::Import
+                | Action::Undo
+        )
+    }
+}
+
+pub struct ActionInfo {
+    pub action: Action,
+    /// Name used for the action in the `[keys]` table of the config file.
+    pub name: &'static str,
+    pub default_key: &'static str,
+    pub context: &'static str,
+    pub description: &'static str,
+}
+
+/// Every configurable action, in the order shown by the help overlay.
+pub const ACTIONS: &[ActionInfo] = &[
+    ActionInfo {
+        action: Action::MoveLeft,
+        name: "move_left",
+        default_key: "Left",
+        context: "Navigation",
+        description: "Previous day (previous year in year view)",
+    },
+    ActionInfo {
+        action: Action::MoveRight,
+        name: "move_right",
+        default_key: "Right",
+        context: "Navigation",
+        description: "Next day (next year in year view)",
+    },
+    ActionInfo {
+        action: Action::MoveUp,
+        name: "move_up",
+        default_key: "Up",
+        context: "Navigation",
+        description: "Previous week, earlier hour, or scroll the focused panel",
+    },
+    ActionInfo {
+        action: Action::MoveDown,
+        name: "move_down",
+        default_key: "Down",
+        context: "Navigation",
+        description: "Next week, later hour, or scroll the focused panel",
+    },
+    ActionInfo {
+        action: Action::PagePrev,
+        name: "page_prev",
+        default_key: "PageUp",
+        context: "Navigation",
+        description: "Previous month, week or day (by view)",
+    },
+    ActionInfo {
+        action: Action::PageNext,
+        name: "page_next",
+        default_key: "PageDown",
+        context: "Navigation",
+        description: "Next month, week or day (by view)",
+    },
+    ActionInfo {
+        action: Action::ScrollTimelineUp,
+        name: "scroll_timeline_up",
+        default_key: "Ctrl+u",
+        context: "Navigation",
+        description: "Scroll the week or day view up half a page",
+    },
+    ActionInfo {
+        action: Action::ScrollTimelineDown,
+        name: "scroll_timeline_down",
+        default_key: "Ctrl+d",
+        context: "Navigation",
+        description: "Scroll the week or day view down half a page",
+    },
+    ActionInfo {
+        action: Action::FirstDayOfMonth,
+        name: "first_day_of_month",
+        default_key: "Home",
+        context: "Navigation",
+        description: "First day of the month",
+    },
+    ActionInfo {
+        action: Action::LastDayOfMonth,
+        name: "last_day_of_month",
+        default_key: "End",
+        context: "Navigation",
+        description: "Last day of the month",
+    },
+    ActionInfo {
+        action: Action::NextEventDay,
+        name: "next_event_day",
+        default_key: "n",
+        context: "Navigation",
+        description: "Next day with events",
+    },
+    ActionInfo {
+        action: Action::PreviousEventDay,
+        name: "previous_event_day",
+        default_key: "N",
+        context: "Navigation",
+        description: "Previous day with events",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Mon),
+        name: "select_monday",
+        default_key: "1",
+        context: "Navigation",
+        description: "Monday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Tue),
+        name: "select_tuesday",
+        default_key: "2",
+        context: "Navigation",
+        description: "Tuesday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Wed),
+        name: "select_wednesday",
+        default_key: "3",
+        context: "Navigation",
+        description: "Wednesday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Thu),
+        name: "select_thursday",
+        default_key: "4",
+        context: "Navigation",
+        description: "Thursday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Fri),
+        name: "select_friday",
+        default_key: "5",
+        context: "Navigation",
+        description: "Friday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Sat),
+        name: "select_saturday",
+        default_key: "6",
+        context: "Navigation",
+        description: "Saturday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::SelectWeekday(Weekday::Sun),
+        name: "select_sunday",
+        default_key: "7",
+        context: "Navigation",
+        description: "Sunday of this week (week and day views)",
+    },
+    ActionInfo {
+        action: Action::NextPanel,
+        name: "next_panel",
+        default_key: "Tab",
+        context: "Navigation",
+        description: "Cycle focused panel",
+    },
+    ActionInfo {
+        action: Action::Today,
+        name: "today",
+        default_key: "t",
+        context: "Navigation",
+        description: "Jump to today",
+    },
+    ActionInfo {
+        action: Action::GoToDate,
+        name: "go_to_date",
+        default_key: "g",
+        context: "Navigation",
+        description: "Go to date",
+    },
+    ActionInfo {
+        action: Action::ViewMonth,
+        name: "view_month",
+        default_key: "m",
+        context: "Views",
+        description: "Month view",
+    },
+    ActionInfo {
+        action: Action::ViewWeek,
+        name: "view_week",
+        default_key: "w",
+        context: "Views",
+        description: "Week view",
+    },
+    ActionInfo {
+        action: Action::ViewDay,
+        name: "view_day",
+        default_key: "d",
+        context: "Views",
+        description: "Day view",
+    },
+    // Ahead of view_year, which `y` means outside the Events panel
+    ActionInfo {
+        action: Action::CopyEvent,
+        name: "copy_event",
+        default_key: "y",
+        context: "Events",
+        description: "Copy the highlighted event to the clipboard (Events panel)",
+    },
+    ActionInfo {
+        action: Action::ViewYear,
+        name: "view_year",
+        default_key: "y",
+        context: "Views",
+        description: "Year view",
+    },
+    ActionInfo {
+        action: Action::ViewAgenda,
+        name: "view_agenda",
+        default_key: "l",
+        context: "Views",
+        description: "Agenda of upcoming events",
+    },
+    ActionInfo {
+        action: Action::AgendaRange,
+        name: "agenda_range",
+        default_key: "r",
+        context: "Views",
+        description: "Dates shown in the agenda (agenda view)",
+    },
+    ActionInfo {
+        action: Action::Select,
+        name: "select",
+        default_key: "Enter",
+        context: "Views",
+        description: "Open the focused month (year view)",
+    },
+    ActionInfo {
+        action: Action::ToggleWeekStart,
+        name: "toggle_week_start",
+        default_key: "s",
+        context: "Views",
+        description: "Start weeks on Sunday or Monday",
+    },
+    ActionInfo {
+        action: Action::CycleTheme,
+        name: "cycle_theme",
+        default_key: "T",
+        context: "Views",
+        description: "Switch theme preset",
+    },
+    ActionInfo {
+        action: Action::ToggleHeatmap,
+        name: "toggle_heatmap",
+        default_key: "H",
+        context: "Views",
+        description: "Shade month days by hours booked",
+    },
+    ActionInfo {
+        action: Action::ToggleWeekNumbers,
+        name: "toggle_week_numbers",
+        default_key: "W",
+        context: "Views",
+        description: "Show ISO week numbers in month grids",
+    },
+    ActionInfo {
+        action: Action::ToggleFitMonth,
+        name: "toggle_fit_month",
+        default_key: "F",
+        context: "Views",
+        description: "Size the month grid to its weeks, giving the rest to events",
+    },
+    ActionInfo {
+        action: Action::ToggleTimeFormat,
+        name: "toggle_time_format",
+        default_key: "h",
+        context: "Views",
+        description: "Show times on a 12-hour or 24-hour clock",
+    },
+    ActionInfo {
+        action: Action::ToggleOffHours,
+        name: "toggle_off_hours",
+        default_key: "O",
+        context: "Views",
+        description: "Shade hours outside the working day and weekends",
+    },
+    ActionInfo {
+        action: Action::AddEvent,
+        name: "add_event",
+        default_key: "a",
+        context: "Events",
+        description: "Add event (Events panel)",
+    },
+    ActionInfo {
+        action: Action::QuickAdd,
+        name: "quick_add",
+        default_key: "A",
+        context: "Events",
+        description: "Quick add, e.g. 'Lunch 12:30-13:30'",
+    },
+    ActionInfo {
+        action: Action::Duplicate,
+        name: "duplicate",
+        default_key: "c",
+        context: "Events",
+        description: "Copy the highlighted event (Events panel)",
+    },
+    ActionInfo {
+        action: Action::ExtendEvent,
+        name: "extend_event",
+        default_key: "+",
+        context: "Events",
+        description: "Make the highlighted event 15 minutes longer (Events panel)",
+    },
+    ActionInfo {
+        action: Action::MoveEventEarlier,
+        name: "move_event_earlier",
+        default_key: "Shift+Left",
+        context: "Events",
+        description: "Move the highlighted event a day earlier (Events panel)",
+    },
+    ActionInfo {
+        action: Action::MoveEventLater,
+        name: "move_event_later",
+        default_key: "Shift+Right",
+        context: "Events",
+        description: "Move the highlighted event a day later (Events panel)",
+    },
+    ActionInfo {
+        action: Action::ShrinkEvent,
+        name: "shrink_event",
+        default_key: "-",
+        context: "Events",
+        description: "Make the highlighted event 15 minutes shorter (Events panel)",
+    },
+    ActionInfo {
+        action: Action::DeleteDay,
+        name: "delete_day",
+        default_key: "D",
+        context: "Events",
+        description: "Delete every event on the selected day (press twice)",
+    },
+    ActionInfo {
+        action: Action::DeleteOccurrence,
+        name: "delete_occurrence",
+        default_key: "X",
+        context: "Events",
+        description: "Delete only this day's occurrence of a repeating event (Events panel)",
+    },
+    ActionInfo {
+        action: Action::CycleCategory,
+        name: "cycle_category",
+        default_key: "f",
+        context: "Events",
+        description: "Show only one category of events, or all",
+    },
+    ActionInfo {
+        action: Action::CycleTag,
+        name: "cycle_tag",
+        default_key: "#",
+        context: "Events",
+        description: "Show only events with one tag, or all",
+    },
+    ActionInfo {
+        action: Action::ToggleCancelled,
+        name: "toggle_cancelled",
+        default_key: "x",
+        context: "Events",
+        description: "Hide or show cancelled events",
+    },
+    ActionInfo {
+        action: Action::CycleCalendar,
+        name: "cycle_calendar",
+        default_key: "C",
+        context: "Events",
+        description: "Choose the calendar new events go to",
+    },
+    ActionInfo {
+        action: Action::ToggleCalendar,
+        name: "toggle_calendar",
+        default_key: "V",
+        context: "Events",
+        description: "Hide or show the events of that calendar",
+    },
+    ActionInfo {
+        action: Action::Search,
+        name: "search",
+        default_key: "/",
+        context: "Events",
+        description: "Search events",
+    },
+    ActionInfo {
+        action: Action::Import,
+        name: "import",
+        default_key: "i",
+        context: "Events",
+        description: "Import from .ics",
+    },
+    ActionInfo {
+        action: Action::Export,
+        name: "export",
+        default_key: "Ctrl+e",
+        context: "Events",
+        description: "Export to cal-export.ics",
+    },
+    ActionInfo {
+        action: Action::ExportCsv,
+        name: "export_csv",
+        default_key: "E",
+        context: "Events",
+        description: "Export to events.csv",
+    },
+    ActionInfo {
+        action: Action::Undo,
+        name: "undo",
+        default_key: "u",
+        context: "Events",
+        description: "Undo last change",
+    },
+    ActionInfo {
+        action: Action::Help,
+        name: "help",
+        default_key: "?",
+        context: "General",
+        description: "Toggle this help",
+    },
+    ActionInfo {
+        action: Action::Command,
+        name: "command",
+        default_key: ":",
+        context: "General",
+        description: "Command prompt, e.g. goto 2025-03-14 or delete <title>",
+    },
+    ActionInfo {
+        action: Action::Quit,
+        name: "quit",
+        default_key: "q",
+        context: "General",
+        description: "Quit",
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parses names such as `q`, `PageDown`, `Ctrl+e` or `Shift+Left`.
+    pub fn parse(value: &str) -> Result<Self> {
+        let (modifiers, key) = match value.split_once('+') {
+            Some((modifier, key)) if !key.is_empty() => {
+                let modifier = match modifier.to_ascii_lowercase().as_str() {
+                    "ctrl" => KeyModifiers::CONTROL,
+                    "alt" => KeyModifiers::ALT,
+                    "shift" => KeyModifiers::SHIFT,
+                    _ => return Err(anyhow!("Unknown modifier in key '{}'", value)),
+                };
+                (modifier, key)
+            }
+            _ => (KeyModifiers::NONE, value),
+        };
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "tab" => KeyCode::Tab,
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "delete" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n) => KeyCode::F(n),
+                    None => return Err(anyhow!("Unknown key '{}'", value)),
+                },
+            },
+        };
+        Ok(Self { code, modifiers })
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        // Shift is implied by the character itself, e.g. `?` or `N`
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers.difference(KeyModifiers::SHIFT),
+            _ => key.modifiers,
+        };
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyConfig {
+    bindings: HashMap<Action, KeyBinding>,
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|info| (info.action, KeyBinding::parse(info.default_key).unwrap()))
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyConfig {
+    /// Applies `action name -> key` overrides on top of the defaults.
+    pub fn with_overrides(overrides: &HashMap<String, String>) -> Result<Self> {
+        let mut config = Self::default();
+        for (name, key) in overrides {
+            let info = ACTIONS
+                .iter()
+                .find(|info| info.name == name)
+                .ok_or_else(|| anyhow!("Unknown action '{}' in [keys]", name))?;
+            config.bindings.insert(info.action, KeyBinding::parse(key)?);
+        }
+        Ok(config)
+    }
+
+    pub fn binding(&self, action: Action) -> KeyBinding {
+        self.bindings[&action]
+    }
+
+    pub fn action_for(&self, key: &KeyEvent) -> Option<Action> {
+        self.action_for_where(key, |_| true)
+    }
+
+    /// The first action bound to `key` that `applies` accepts, so that a key
+    /// can mean one thing in a panel and another elsewhere.
+    pub fn action_for_where(
+        &self,
+        key: &KeyEvent,
+        applies: impl Fn(Action) -> bool,
+    ) -> Option<Action> {
+        ACTIONS
+            .iter()
+            .map(|info| info.action)
+            .find(|action| self.bindings[action].matches(key) && applies(*action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_binding() {
+        assert_eq!(
+            KeyBinding::parse("Ctrl+e").unwrap(),
+            KeyBinding {
+                code: KeyCode::Char('e'),
+                modifiers: KeyModifiers::CONTROL
+            }
+        );
+        assert_eq!(
+            KeyBinding::parse("PageDown").unwrap().code,
+            KeyCode::PageDown
+        );
+        assert_eq!(KeyBinding::parse("+").unwrap().code, KeyCode::Char('+'));
+        assert_eq!(
+            KeyBinding::parse("Shift+Left").unwrap().to_string(),
+            "Shift+←"
+        );
+        assert!(KeyBinding::parse("Hyper+x").is_err());
+        assert!(KeyBinding::parse("nonsense").is_err());
+    }
+
+    #[test]
+    fn test_override_one_action() {
+        let overrides = HashMap::from([("move_left".to_string(), "h".to_string())]);
+        let config = KeyConfig::with_overrides(&overrides).unwrap();
+
+        let h = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        let w = KeyEvent::new(KeyCode::Char('w'), KeyModifiers::NONE);
+        assert_eq!(config.action_for(&h), Some(Action::MoveLeft));
+        assert_eq!(config.action_for(&w), Some(Action::ViewWeek));
+
+        // Shift only goes unnoticed when it picks the character
+        let config = KeyConfig::default();
+        let shifted = |code| KeyEvent::new(code, KeyModifiers::SHIFT);
+        assert_eq!(
+            config.action_for(&shifted(KeyCode::Char('N'))),
+            Some(Action::PreviousEventDay)
+        );
+        assert_eq!(
+            config.action_for(&shifted(KeyCode::Left)),
+            Some(Action::MoveEventEarlier)
+        );
+        assert_eq!(
+            config.action_for(&KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)),
+            Some(Action::MoveLeft)
+        );
+
+        let unknown = HashMap::from([("fly".to_string(), "f".to_string())]);
+        assert!(KeyConfig::with_overrides(&unknown).is_err());
+    }
+
+    #[test]
+    fn test_shared_key_falls_through() {
+        let config = KeyConfig::default();
+        let y = KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(config.action_for(&y), Some(Action::CopyEvent));
+        assert_eq!(
+            config.action_for_where(&y, |action| action != Action::CopyEvent),
+            Some(Action::ViewYear)
+        );
+    }
+}
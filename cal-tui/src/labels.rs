@@ -0,0 +1,104 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::Deserialize;
+
+/// Weekday names shown in headers, each list starting from Monday. Set from
+/// the `[weekdays]` table of `config.toml` to translate or shorten them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct WeekdayLabels {
+    /// Used by the agenda, e.g. "Monday".
+    pub full: [String; 7],
+    /// Used by the month grid and the week and day views, e.g. "Mon".
+    pub short: [String; 7],
+    /// Used by the mini calendars, e.g. "M".
+    pub narrow: [String; 7],
+}
+
+impl Default for WeekdayLabels {
+    fn default() -> Self {
+        let labels = |names: [&str; 7]| names.map(str::to_string);
+        Self {
+            full: labels([
+                "Monday",
+                "Tuesday",
+                "Wednesday",
+                "Thursday",
+                "Friday",
+                "Saturday",
+                "Sunday",
+            ]),
+            short: labels(["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]),
+            narrow: labels(["M", "T", "W", "T", "F", "S", "S"]),
+        }
+    }
+}
+
+impl WeekdayLabels {
+    pub fn full(&self, day: Weekday) -> &str {
+        &self.full[day.num_days_from_monday() as usize]
+    }
+
+    pub fn short(&self, day: Weekday) -> &str {
+        &self.short[day.num_days_from_monday() as usize]
+    }
+
+    pub fn narrow(&self, day: Weekday) -> &str {
+        &self.narrow[day.num_days_from_monday() as usize]
+    }
+}
+
+/// Month names shown in titles and headers, starting from January. Set from
+/// the `[months]` table of `config.toml` to translate them.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MonthLabels {
+    pub full: [String; 12],
+}
+
+impl Default for MonthLabels {
+    fn default() -> Self {
+        Self {
+            full: [
+                "January",
+                "February",
+                "March",
+                "April",
+                "May",
+                "June",
+                "July",
+                "August",
+                "September",
+                "October",
+                "November",
+                "December",
+            ]
+            .map(str::to_string),
+        }
+    }
+}
+
+impl MonthLabels {
+    /// Name of `month`, counting January as 1, or an empty string for a
+    /// month that does not exist.
+    pub fn month_name(&self, month: u32) -> &str {
+        month
+            .checked_sub(1)
+            .and_then(|index| self.full.get(index as usize))
+            .map_or("", String::as_str)
+    }
+
+    /// `date` written out, e.g. "March 14, 2025".
+    pub fn long_date(&self, date: NaiveDate) -> String {
+        format!(
+            "{} {}, {}",
+            self.month_name(date.month()),
+            date.day(),
+            date.year()
+        )
+    }
+
+    /// `date` without the year, e.g. "March 14".
+    pub fn day_and_month(&self, date: NaiveDate) -> String {
+        format!("{} {}", self.month_name(date.month()), date.day())
+    }
+}
@@ -1,28 +1,61 @@
-use anyhow::Result;
-use cal_core::Calendar;
-use cal_events::EventManager;
-use chrono::{DateTime, Datelike, Local, Timelike};
+mod command;
+mod config;
+mod error;
+mod keys;
+mod labels;
+mod prefs;
+mod theme;
+
+pub use error::AppError;
+
+use anyhow::{anyhow, Result};
+use cal_core::{local_midnight, parse_relative_date, Calendar};
+use cal_events::{
+    parse_duration, parse_quick_event, parse_tags, EventManager, EventStatus, Frequency,
+    ImportReport, PastEventError, Priority, Recurrence,
+};
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike, Weekday,
+};
+use command::{Command, ExportFormat};
+use config::Config;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
+use keys::{Action, KeyConfig, ACTIONS};
+use labels::{MonthLabels, WeekdayLabels};
+use notify_rust::Notification;
+use prefs::{Preferences, TimeFormat};
 use ratatui::{
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Flex, Layout},
     prelude::*,
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    style::{Modifier, Style},
+    widgets::{Block, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table},
 };
+use serde::{Deserialize, Serialize};
 use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
     io,
+    path::PathBuf,
     time::{Duration, Instant},
 };
+use theme::Theme;
+use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ViewMode {
     Month,
     Week,
     Day,
+    Year,
+    Agenda,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -38,10 +71,230 @@ pub enum PopupState {
     CreateEvent {
         title: String,
         description: String,
-        start_time: DateTime<Local>,
-        end_time: DateTime<Local>,
+        start_time: String,
+        end_time: String,
+        reminder: String,
+        priority: Priority,
+        attendees: String,
+        location: String,
+        tags: String,
+        status: EventStatus,
+        recurrence: Option<Frequency>,
+        /// Days a weekly event repeats on; none means the start's weekday.
+        weekdays: Vec<Weekday>,
         focused_field: usize,
+        error: Option<String>,
+        /// Set once the user has been warned that the event is in the past.
+        confirm_past: bool,
+    },
+    GoToDate {
+        input: String,
+        error: Option<String>,
+    },
+    AgendaRange {
+        input: String,
+        error: Option<String>,
     },
+    Search {
+        query: String,
+        selected: usize,
+    },
+    QuickAdd {
+        input: String,
+        error: Option<String>,
+        confirm_past: bool,
+    },
+    ImportFile {
+        path: String,
+        error: Option<String>,
+    },
+    Command {
+        input: String,
+    },
+    /// Asked on quit when there are unsaved changes.
+    ConfirmQuit,
+    /// Shown when a reminder fires so that it can be snoozed.
+    Reminder {
+        id: Uuid,
+        /// Start of the occurrence being reminded of.
+        start: DateTime<FixedOffset>,
+        title: String,
+        body: String,
+    },
+    Help,
+}
+
+const READ_ONLY_MESSAGE: &str = "Read-only mode";
+const DATETIME_INPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
+const DATETIME_INPUT_LEN: usize = 16;
+const CREATE_EVENT_FIELDS: usize = 11;
+const DESCRIPTION_FIELD: usize = 1;
+/// Lines of the description shown at once in the create popup.
+const DESCRIPTION_VISIBLE_LINES: u16 = 3;
+const START_TIME_FIELD: usize = 2;
+const END_TIME_FIELD: usize = 3;
+/// How far Left/Right or +/- move a time field, snapping to multiples of it.
+const TIME_STEP_MINUTES: u32 = 15;
+const PRIORITY_FIELD: usize = 5;
+const ATTENDEES_FIELD: usize = 6;
+const LOCATION_FIELD: usize = 7;
+const TAGS_FIELD: usize = 8;
+const STATUS_FIELD: usize = 9;
+const RECURRENCE_FIELD: usize = 10;
+const DATE_INPUT_LEN: usize = 10;
+/// Longest relative phrase the go-to prompt accepts, e.g. `next wednesday`.
+const GO_TO_INPUT_LEN: usize = 16;
+/// Snooze lengths offered when a reminder fires, picked with keys 1 to 3.
+const SNOOZE_MINUTES: [i64; 3] = [5, 10, 30];
+const ICS_EXPORT_PATH: &str = "cal-export.ics";
+const CSV_EXPORT_PATH: &str = "events.csv";
+/// Indent of wrapped lines in the Events panel, lining up under the text after the bullet.
+const EVENT_WRAP_INDENT: &str = "  ";
+const STATUS_DURATION: Duration = Duration::from_secs(4);
+/// Faster ticks than this would only burn CPU.
+const MIN_TICK_RATE: Duration = Duration::from_millis(50);
+
+/// Keys used inside popups, which are not configurable.
+const POPUP_KEYBINDINGS: &[(&str, &str)] = &[
+    ("Tab/↑/↓", "Move between fields"),
+    ("←/→ or +/-", "Change a time by 15 minutes"),
+    ("Alt/Shift+Enter", "New line in a description"),
+    ("1/2/3", "Snooze a reminder 5, 10 or 30 minutes"),
+    ("Enter", "Confirm"),
+    ("Esc", "Cancel"),
+];
+
+const MONTH_HEADER_HEIGHT: u16 = 2;
+const MONTH_ROW_HEIGHT: u16 = 3;
+const MONTH_COLUMN_WIDTHS: [Constraint; 7] = [Constraint::Percentage(14); 7];
+const WEEK_NUMBER_WIDTH: u16 = 2;
+const TIMELINE_VISIBLE_HOURS: usize = 8;
+const TIMELINE_HEADER_HEIGHT: u16 = 2;
+const TIMELINE_ROW_HEIGHT: u16 = 3;
+const WEEK_VIEW_WIDTHS: [Constraint; 8] = [
+    Constraint::Length(6),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+    Constraint::Percentage(13),
+];
+const DAY_VIEW_WIDTHS: [Constraint; 2] = [Constraint::Length(6), Constraint::Percentage(94)];
+const MAX_TIMELINE_SCROLL: usize = 24 - TIMELINE_VISIBLE_HOURS;
+/// Hours scrolled at once by the half-page timeline keys.
+const TIMELINE_PAGE_HOURS: isize = (TIMELINE_VISIBLE_HOURS / 2) as isize;
+/// Start hour for new events when no timeline hour is focused.
+const DEFAULT_EVENT_HOUR: u32 = 9;
+/// Days after today the agenda shows when no range has been entered.
+const AGENDA_DAYS: i64 = 30;
+const AGENDA_PAGE_LINES: isize = 10;
+/// Events listed in the sidebar's upcoming panel.
+const UPCOMING_EVENTS: usize = 5;
+const UPCOMING_DAYS: i64 = 30;
+const AGENDA_LOCATION_LEN: usize = 24;
+
+impl PopupState {
+    /// The create popup for the given times, with the other fields copied
+    /// from `template` when there is one.
+    fn create_event(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        template: Option<&cal_events::Event>,
+    ) -> Self {
+        PopupState::CreateEvent {
+            title: template.map(|e| e.title.clone()).unwrap_or_default(),
+            description: template
+                .and_then(|e| e.description.clone())
+                .unwrap_or_default(),
+            start_time: start.format(DATETIME_INPUT_FORMAT).to_string(),
+            end_time: end.format(DATETIME_INPUT_FORMAT).to_string(),
+            reminder: String::new(),
+            priority: template.map(|e| e.priority).unwrap_or_default(),
+            attendees: template.map(|e| e.attendees.join(", ")).unwrap_or_default(),
+            location: template
+                .and_then(|e| e.location.clone())
+                .unwrap_or_default(),
+            tags: template.map(|e| e.tags.join(" ")).unwrap_or_default(),
+            status: template.map(|e| e.status).unwrap_or_default(),
+            recurrence: template
+                .and_then(|e| e.recurrence.as_ref())
+                .map(|recurrence| recurrence.frequency),
+            weekdays: template
+                .and_then(|e| e.recurrence.as_ref())
+                .map(|recurrence| recurrence.weekdays.clone())
+                .unwrap_or_default(),
+            focused_field: 0,
+            error: None,
+            confirm_past: false,
+        }
+    }
+
+    /// Asks again before adding a past event once its input has changed
+    /// since the warning.
+    fn forget_past_warning(&mut self) {
+        if let PopupState::CreateEvent { confirm_past, .. }
+        | PopupState::QuickAdd { confirm_past, .. } = self
+        {
+            *confirm_past = false;
+        }
+    }
+
+    fn time_field_focused(&self) -> bool {
+        matches!(
+            self,
+            PopupState::CreateEvent {
+                focused_field: START_TIME_FIELD | END_TIME_FIELD,
+                ..
+            }
+        )
+    }
+
+    /// Moves the focused start or end time to the next or previous quarter
+    /// hour, rolling over into the next or previous day as needed.
+    fn step_time(&mut self, forward: bool) {
+        let PopupState::CreateEvent {
+            start_time,
+            end_time,
+            focused_field,
+            error,
+            ..
+        } = self
+        else {
+            return;
+        };
+        let step = |time: DateTime<Local>| {
+            let past_step = i64::from(time.minute() % TIME_STEP_MINUTES);
+            let minutes = match (forward, past_step) {
+                (true, _) => i64::from(TIME_STEP_MINUTES) - past_step,
+                (false, 0) => -i64::from(TIME_STEP_MINUTES),
+                (false, _) => -past_step,
+            };
+            (time + chrono::Duration::minutes(minutes))
+                .format(DATETIME_INPUT_FORMAT)
+                .to_string()
+        };
+
+        let result = match *focused_field {
+            START_TIME_FIELD => {
+                parse_datetime_input(start_time).map(|start| *start_time = step(start))
+            }
+            END_TIME_FIELD => parse_datetime_input(start_time)
+                .and_then(|start| resolve_end_input(start, end_time))
+                .map(|end| *end_time = step(end)),
+            _ => return,
+        };
+        *error = result.err().map(|err| err.to_string());
+    }
+}
+
+/// What became of an event's reminder since the app started.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReminderState {
+    Fired,
+    /// Fires again once this time comes.
+    Snoozed(DateTime<Local>),
 }
 
 pub struct App {
@@ -50,690 +303,5651 @@ pub struct App {
     view_mode: ViewMode,
     week_scroll: usize,
     day_scroll: usize,
+    /// Hour the week and day views open at, from the preferences.
+    day_view_start_hour: u32,
+    agenda_scroll: usize,
+    /// First and last day shown in the agenda, if set with the range popup.
+    agenda_range: Option<(NaiveDate, NaiveDate)>,
+    events_scroll: usize,
+    /// Hour row picked with Up/Down in the week and day views.
+    focused_hour: Option<u32>,
+    /// Day whose events are deleted if `DeleteDay` is pressed again straight away.
+    pending_day_delete: Option<NaiveDate>,
     focused_panel: FocusedPanel,
     popup: PopupState,
+    status: Option<(String, Instant)>,
+    keys: KeyConfig,
+    theme: Theme,
+    theme_name: String,
+    weekday_labels: WeekdayLabels,
+    month_labels: MonthLabels,
+    /// Shade month cells by how many hours are booked instead of the busy colour.
+    heatmap: bool,
+    show_week_numbers: bool,
+    /// Size the month grid to the weeks the month spans instead of filling the
+    /// space above the Events panel, so short months leave no empty rows.
+    fit_month_grid: bool,
+    time_format: TimeFormat,
+    /// Working hours of each weekday, as a half-open range of hours.
+    work_hours: std::ops::Range<u32>,
+    shade_off_hours: bool,
+    /// Only events in this category are listed; `None` lists them all.
+    category_filter: Option<String>,
+    tag_filter: Option<String>,
+    hide_cancelled: bool,
+    /// Calendar that new events are added to and that `ToggleCalendar` hides.
+    target_calendar: String,
+    /// Keyed by event id and occurrence start, so each occurrence of a
+    /// recurring event is reminded of separately.
+    reminders: HashMap<(Uuid, DateTime<FixedOffset>), ReminderState>,
+    last_reminder_check: DateTime<Local>,
+    /// Where events are loaded from and saved to; `None` keeps them in memory only.
+    events_path: Option<PathBuf>,
+    /// Whether events changed since they were last loaded or saved.
+    dirty: bool,
+    /// Set by `--read-only`: events can be viewed but not changed.
+    read_only: bool,
+    /// Inner area of the month grid as last drawn, used to hit-test mouse clicks.
+    month_grid_area: Option<Rect>,
+    /// How the month grid's rows were last fitted into that area.
+    month_rows: MonthRows,
+    /// Inner area of the week or day view as last drawn, for mouse drags.
+    timeline_area: Option<Rect>,
+    /// Day and hour where a drag on the timeline began.
+    drag_start: Option<(NaiveDate, u32)>,
+    tick_rate: Duration,
+    /// Opened on first copy and kept, since on X11 the copied text is only
+    /// offered while it is alive.
+    clipboard: Option<arboard::Clipboard>,
+    /// Copied text that no clipboard took, printed once the terminal is restored.
+    print_on_exit: Vec<String>,
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl App {
+    /// An app with the default preferences; nothing is read from disk.
     pub fn new() -> Self {
-        Self {
+        Self::with_preferences(Preferences::default())
+    }
+
+    fn with_preferences(prefs: Preferences) -> Self {
+        let mut app = Self {
             calendar: Calendar::new(),
             event_manager: EventManager::new(),
             view_mode: ViewMode::Month,
             week_scroll: 0,
             day_scroll: 0,
+            day_view_start_hour: 0,
+            agenda_scroll: 0,
+            agenda_range: None,
+            events_scroll: 0,
+            focused_hour: None,
+            pending_day_delete: None,
             focused_panel: FocusedPanel::Calendar,
             popup: PopupState::Hidden,
+            status: None,
+            keys: KeyConfig::default(),
+            theme: Theme::default().with_color(theme::color_supported()),
+            theme_name: Theme::PRESETS[0].to_string(),
+            weekday_labels: WeekdayLabels::default(),
+            month_labels: MonthLabels::default(),
+            heatmap: false,
+            show_week_numbers: false,
+            fit_month_grid: false,
+            time_format: TimeFormat::default(),
+            work_hours: 8..18,
+            shade_off_hours: true,
+            category_filter: None,
+            tag_filter: None,
+            hide_cancelled: false,
+            target_calendar: cal_events::DEFAULT_CALENDAR.to_string(),
+            reminders: HashMap::new(),
+            // Reminders that fell due before startup are never fired
+            last_reminder_check: Local::now(),
+            events_path: None,
+            dirty: false,
+            read_only: false,
+            month_grid_area: None,
+            month_rows: MonthRows::default(),
+            timeline_area: None,
+            drag_start: None,
+            tick_rate: Duration::from_millis(Preferences::default().tick_rate_ms),
+            clipboard: None,
+            print_on_exit: Vec::new(),
+        };
+        app.apply_preferences(prefs);
+        app
+    }
+
+    fn apply_preferences(&mut self, prefs: Preferences) {
+        self.view_mode = prefs.view_mode;
+        self.calendar.set_week_start(prefs.week_start);
+        self.day_view_start_hour = prefs.day_view_start_hour;
+        self.time_format = prefs.time_format;
+        self.work_hours =
+            if prefs.work_start_hour < prefs.work_end_hour && prefs.work_end_hour <= 24 {
+                prefs.work_start_hour..prefs.work_end_hour
+            } else {
+                let defaults = Preferences::default();
+                defaults.work_start_hour..defaults.work_end_hour
+            };
+        self.shade_off_hours = prefs.shade_off_hours;
+        if let Some(date) = date_to_restore(prefs.selected_date, Local::now().date_naive()) {
+            self.calendar.go_to_date(date);
+        }
+        self.tick_rate = Duration::from_millis(prefs.tick_rate_ms).max(MIN_TICK_RATE);
+        let scroll = (prefs.day_view_start_hour as usize).min(MAX_TIMELINE_SCROLL);
+        self.week_scroll = scroll;
+        self.day_scroll = scroll;
+        match Theme::preset(&prefs.theme) {
+            Ok(theme) => {
+                self.theme = theme.with_color(self.theme.color_enabled);
+                self.theme_name = prefs.theme;
+            }
+            Err(_) => {
+                self.theme = Theme::default().with_color(self.theme.color_enabled);
+                self.theme_name = Theme::PRESETS[0].to_string();
+            }
         }
     }
-}
 
-pub fn run() -> Result<()> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    /// `~/.local/share/cal-rs/events.json`, or the platform equivalent.
+    fn default_events_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("cal-rs").join("events.json"))
+    }
 
-    let app = App::new();
-    let res = run_app(&mut terminal, app);
+    /// Loads the saved events from `path` and saves back there from now on.
+    /// Says so in the status bar if the file was corrupt and its backup used.
+    fn open_events(&mut self, path: PathBuf) -> Result<()> {
+        let loaded = EventManager::load_from_file(&path)?;
+        self.event_manager = loaded.manager;
+        if loaded.from_backup {
+            self.set_status(format!(
+                "{} is corrupt; loaded the backup instead",
+                path.display()
+            ));
+        }
+        self.events_path = Some(path);
+        self.dirty = false;
+        Ok(())
+    }
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    fn save_events(&mut self) -> Result<()> {
+        let path = self
+            .events_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("No data directory to save events in"))?;
+        self.event_manager.save_to_file(path)?;
+        self.dirty = false;
+        Ok(())
+    }
 
-    if let Err(err) = res {
-        println!("{err:?}");
+    /// Whether the app can quit straight away; with unsaved changes it asks first.
+    fn request_quit(&mut self) -> bool {
+        if self.dirty {
+            self.popup = PopupState::ConfirmQuit;
+        }
+        !self.dirty
     }
 
-    Ok(())
-}
+    fn preferences(&self) -> Preferences {
+        Preferences {
+            view_mode: self.view_mode.clone(),
+            week_start: self.calendar.week_start,
+            theme: self.theme_name.clone(),
+            day_view_start_hour: self.day_view_start_hour,
+            tick_rate_ms: self.tick_rate.as_millis() as u64,
+            time_format: self.time_format,
+            work_start_hour: self.work_hours.start,
+            work_end_hour: self.work_hours.end,
+            shade_off_hours: self.shade_off_hours,
+            selected_date: Some(self.calendar.selected_naive()),
+        }
+    }
 
-fn run_app(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut app: App) -> Result<()> {
-    let mut last_tick = Instant::now();
-    let tick_rate = Duration::from_millis(250);
+    /// Events on the selected day that pass the filters.
+    fn day_events(&self) -> Vec<Cow<'_, cal_events::Event>> {
+        self.events_on(self.calendar.selected_date)
+    }
 
-    loop {
-        terminal.draw(|f| ui(f, &app))?;
+    /// Events on each day of the selected week that pass the filters.
+    fn week_events(&self) -> Vec<Vec<Cow<'_, cal_events::Event>>> {
+        self.calendar
+            .selected_week()
+            .map(|day| self.events_on(local_midnight(day)))
+            .into()
+    }
 
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+    fn events_on(&self, date: DateTime<Local>) -> Vec<Cow<'_, cal_events::Event>> {
+        let mut events = self.event_manager.list_events_for_day(date);
+        events.retain(|event| self.passes_filters(event));
+        events
+    }
 
-        if crossterm::event::poll(timeout)? {
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    // First handle popup-specific keys if popup is active
-                    key if matches!(app.popup, PopupState::CreateEvent { .. }) => match key {
-                        KeyCode::Up => {
-                            if let PopupState::CreateEvent {
-                                ref mut focused_field,
-                                ..
-                            } = &mut app.popup
-                            {
-                                if *focused_field > 0 {
-                                    *focused_field -= 1;
-                                }
-                            }
-                        }
-                        KeyCode::Down => {
-                            if let PopupState::CreateEvent {
-                                ref mut focused_field,
-                                ..
-                            } = &mut app.popup
-                            {
-                                if *focused_field < 3 {
-                                    *focused_field += 1;
-                                }
-                            }
-                        }
-                        KeyCode::Tab => {
-                            if let PopupState::CreateEvent {
-                                ref mut focused_field,
-                                ..
-                            } = &mut app.popup
-                            {
-                                *focused_field = (*focused_field + 1) % 4;
-                            }
-                        }
-                        KeyCode::Char(c) => {
-                            if let PopupState::CreateEvent {
-                                ref mut title,
-                                ref mut description,
-                                focused_field,
-                                ..
-                            } = &mut app.popup
-                            {
-                                match focused_field {
-                                    0 => title.push(c),
-                                    1 => description.push(c),
-                                    _ => {}
-                                }
-                            }
-                        }
-                        KeyCode::Backspace => {
-                            if let PopupState::CreateEvent {
-                                ref mut title,
-                                ref mut description,
-                                focused_field,
-                                ..
-                            } = &mut app.popup
-                            {
-                                match focused_field {
-                                    0 => {
-                                        title.pop();
-                                    }
-                                    1 => {
-                                        description.pop();
-                                    }
-                                    _ => {}
-                                }
-                            }
-                        }
-                        KeyCode::Esc => app.popup = PopupState::Hidden,
-                        KeyCode::Enter => {
-                            if let PopupState::CreateEvent {
-                                title,
-                                description,
-                                start_time,
-                                end_time,
-                                ..
-                            } = app.popup.clone()
-                            {
-                                if let Ok(event) = cal_events::Event::new(
-                                    title,
-                                    Some(description),
-                                    start_time,
-                                    end_time,
-                                ) {
-                                    let _ = app.event_manager.add_event(event);
-                                }
-                                app.popup = PopupState::Hidden;
-                            }
-                        }
-                        _ => {}
-                    },
-                    // Then handle regular app keys if no popup is active
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Char('m') => app.view_mode = ViewMode::Month,
-                    KeyCode::Char('w') => app.view_mode = ViewMode::Week,
-                    KeyCode::Char('d') => app.view_mode = ViewMode::Day,
-                    KeyCode::Left => {
-                        if !app.calendar.move_selection("left") {
-                            app.calendar.prev_month();
-                            let grid = app.calendar.get_month_grid();
-                            for week in grid.iter().rev() {
-                                if let Some(Some(last_day)) =
-                                    week.iter().rev().find(|d| d.is_some())
-                                {
-                                    app.calendar.selected_date =
-                                        app.calendar.current_date.with_day(*last_day).unwrap();
-                                    break;
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Right => {
-                        if !app.calendar.move_selection("right") {
-                            app.calendar.next_month();
-                            if let Some(Some(first_day)) = app
-                                .calendar
-                                .get_month_grid()
-                                .iter()
-                                .flat_map(|week| week.iter())
-                                .find(|d| d.is_some())
-                            {
-                                app.calendar.selected_date =
-                                    app.calendar.current_date.with_day(*first_day).unwrap();
-                            }
-                        }
-                    }
-                    KeyCode::Up => match app.focused_panel {
-                        FocusedPanel::WeekView if app.view_mode == ViewMode::Week => {
-                            if app.week_scroll > 0 {
-                                app.week_scroll -= 1;
-                            }
-                        }
-                        FocusedPanel::WeekView if app.view_mode == ViewMode::Day => {
-                            if app.day_scroll > 0 {
-                                app.day_scroll -= 1;
-                            }
-                        }
-                        _ => {
-                            app.calendar.move_selection("up");
-                        }
-                    },
-                    KeyCode::Down => {
-                        match app.focused_panel {
-                            FocusedPanel::WeekView if app.view_mode == ViewMode::Week => {
-                                if app.week_scroll < 16 {
-                                    // Max scroll (24 - visible_hours)
-                                    app.week_scroll += 1;
-                                }
-                            }
-                            FocusedPanel::WeekView if app.view_mode == ViewMode::Day => {
-                                if app.day_scroll < 16 {
-                                    // Max scroll (24 - visible_hours)
-                                    app.day_scroll += 1;
-                                }
-                            }
-                            _ => {
-                                app.calendar.move_selection("down");
-                            }
-                        }
-                    }
-                    KeyCode::Tab => {
-                        app.focused_panel = match app.focused_panel {
-                            FocusedPanel::Calendar => FocusedPanel::WeekView,
-                            FocusedPanel::WeekView => FocusedPanel::Events,
-                            FocusedPanel::Events => FocusedPanel::Calendar,
-                        };
-                    }
-                    KeyCode::Char('a') if app.focused_panel == FocusedPanel::Events => {
-                        app.popup = PopupState::CreateEvent {
-                            title: String::new(),
-                            description: String::new(),
-                            start_time: app.calendar.selected_date,
-                            end_time: app.calendar.selected_date + chrono::Duration::hours(1),
-                            focused_field: 0,
-                        };
-                    }
-                    _ => {}
-                }
+    /// Whether `event` has the filtered category and tag and is not a hidden
+    /// cancelled event.
+    fn passes_filters(&self, event: &cal_events::Event) -> bool {
+        self.category_filter
+            .as_ref()
+            .is_none_or(|category| event.category.as_ref() == Some(category))
+            && self
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tag| event.tags.contains(tag))
+            && !(self.hide_cancelled && event.status == EventStatus::Cancelled)
+    }
+
+    /// Moves the category filter from "All" through each category in use and back.
+    fn cycle_category_filter(&mut self) {
+        let categories = self.event_manager.categories();
+        let next = match &self.category_filter {
+            None => categories.first(),
+            Some(current) => categories
+                .iter()
+                .position(|category| category == current)
+                .and_then(|index| categories.get(index + 1)),
+        };
+        self.category_filter = next.map(|category| category.to_string());
+        self.events_scroll = 0;
+    }
+
+    /// Moves the tag filter from "All" through each tag in use and back.
+    fn cycle_tag_filter(&mut self) {
+        let tags = self.event_manager.tags();
+        let next = match &self.tag_filter {
+            None => tags.first(),
+            Some(current) => tags
+                .iter()
+                .position(|tag| tag == current)
+                .and_then(|index| tags.get(index + 1)),
+        };
+        self.tag_filter = next.map(|tag| tag.to_string());
+        self.events_scroll = 0;
+        self.agenda_scroll = 0;
+    }
+
+    /// Moves the target calendar to the next one known, wrapping around.
+    fn cycle_target_calendar(&mut self) {
+        let calendars = self.event_manager.calendars();
+        let next = calendars
+            .iter()
+            .position(|calendar| *calendar == self.target_calendar)
+            .map_or(0, |index| (index + 1) % calendars.len());
+        self.target_calendar = calendars[next].to_string();
+    }
+
+    /// The event at the top of the scrolled Events panel.
+    fn highlighted_event(&self) -> Option<Cow<'_, cal_events::Event>> {
+        let events = self.day_events();
+        let index = self.events_scroll.min(events.len().saturating_sub(1));
+        events.into_iter().nth(index)
+    }
+
+    /// Moves the end of the highlighted event by `TIME_STEP_MINUTES`, refusing
+    /// to let it end at or before its start.
+    fn resize_highlighted_event(&mut self, longer: bool) {
+        // Edit the stored event, which for a series is its first occurrence
+        let Some(event) = self
+            .highlighted_event()
+            .and_then(|event| self.event_manager.get_event(event.id))
+            .cloned()
+        else {
+            return;
+        };
+        let step = chrono::Duration::minutes(TIME_STEP_MINUTES.into());
+        let end_time = if longer {
+            event.end_time + step
+        } else {
+            event.end_time - step
+        };
+        if end_time <= event.start_time {
+            self.set_status(format!(
+                "'{}' is already as short as it can be",
+                event.title
+            ));
+            return;
+        }
+
+        let (id, title) = (event.id, event.title.clone());
+        let length = end_time - event.start_time;
+        match self
+            .event_manager
+            .edit_event(id, cal_events::Event { end_time, ..event })
+        {
+            Ok(()) => {
+                self.dirty = true;
+                self.set_status(format!("'{}' now lasts {}", title, format_duration(length)));
             }
+            Err(err) => self.set_status(err.to_string()),
         }
+    }
 
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
+    /// Whether `action` does anything where the focus is, so that keys it
+    /// shares fall through to other actions when it does not.
+    fn action_applies(&self, action: Action) -> bool {
+        match action {
+            Action::CopyEvent => {
+                self.focused_panel == FocusedPanel::Events && self.highlighted_event().is_some()
+            }
+            Action::AgendaRange => self.view_mode == ViewMode::Agenda,
+            Action::SelectWeekday(_) => {
+                matches!(self.view_mode, ViewMode::Week | ViewMode::Day)
+            }
+            _ => true,
         }
     }
-}
 
-fn create_mini_calendar(app: &App) -> Table {
-    let weekdays = ["S", "M", "T", "W", "T", "F", "S"];
-    let header_cells = weekdays
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Gray)));
-    let header = Row::new(header_cells)
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .height(1);
+    /// Copies a summary of the highlighted event to the system clipboard, or
+    /// keeps it to print on exit when there is no clipboard, e.g. over SSH.
+    fn copy_highlighted_event(&mut self) {
+        let Some(event) = self.highlighted_event() else {
+            return;
+        };
+        let (text, title) = (format_event_for_clipboard(&event), event.title.clone());
+        let copied = match &mut self.clipboard {
+            Some(clipboard) => clipboard.set_text(text.clone()),
+            None => arboard::Clipboard::new()
+                .and_then(|clipboard| self.clipboard.insert(clipboard).set_text(text.clone())),
+        };
+        match copied {
+            Ok(()) => self.set_status(format!("Copied '{}' to the clipboard", title)),
+            Err(err) => {
+                self.print_on_exit.push(text);
+                self.set_status(format!(
+                    "No clipboard ({err}); '{}' will be printed on exit",
+                    title
+                ));
+            }
+        }
+    }
 
-    let grid = app.calendar.get_month_grid();
-    let rows: Vec<Row> = grid
-        .iter()
-        .map(|week| {
-            let cells = week.iter().map(|day| match day {
-                Some(d) => {
-                    let now = Local::now();
-                    let is_current_day = d == &now.day()
-                        && app.calendar.current_date.month() == now.month()
-                        && app.calendar.current_date.year() == now.year();
-
-                    let style = if is_current_day {
-                        Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                    };
+    /// Moves the highlighted event `days` days, keeping its local time of day
+    /// and length, and selects the day it lands on so it stays highlighted.
+    fn move_highlighted_event(&mut self, days: i64) {
+        // Move the stored event, which for a series moves every occurrence
+        let Some(event) = self
+            .highlighted_event()
+            .and_then(|event| self.event_manager.get_event(event.id))
+            .cloned()
+        else {
+            return;
+        };
+        let Some(start_time) = shift_days(event.start_time, days) else {
+            self.set_status(format!("'{}' cannot move there", event.title));
+            return;
+        };
+        let end_time = start_time + (event.end_time - event.start_time);
 
-                    Cell::from(format!("{:2}", d)).style(style)
-                }
-                None => Cell::from("  "),
+        let (id, title) = (event.id, event.title.clone());
+        let moved = cal_events::Event {
+            start_time,
+            end_time,
+            ..event
+        };
+        match self.event_manager.edit_event(id, moved) {
+            Ok(()) => {
+                self.dirty = true;
+                let day = start_time.with_timezone(&Local).date_naive();
+                self.calendar.go_to_date(day);
+                self.events_scroll = self
+                    .day_events()
+                    .iter()
+                    .position(|event| event.id == id)
+                    .unwrap_or(0);
+                self.set_status(format!(
+                    "Moved '{}' to {}",
+                    title,
+                    self.month_labels.long_date(day)
+                ));
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+    }
+
+    /// Adds the event described by the create popup, or shows in the popup
+    /// why it cannot be added.
+    fn submit_event_popup(&mut self) {
+        let PopupState::CreateEvent {
+            title,
+            description,
+            start_time,
+            end_time,
+            reminder,
+            priority,
+            attendees,
+            location,
+            tags,
+            status,
+            recurrence,
+            weekdays,
+            confirm_past,
+            ..
+        } = self.popup.clone()
+        else {
+            return;
+        };
+        let result = parse_datetime_input(&start_time).and_then(|start| {
+            let end = resolve_end_input(start, &end_time)?;
+            let mut event = cal_events::Event::new(title, Some(description), start, end)?;
+            if !reminder.trim().is_empty() {
+                event.reminder = Some(parse_duration(&reminder)?);
+            }
+            event.priority = priority;
+            event.status = status;
+            event.attendees = parse_attendees_input(&attendees);
+            event.location =
+                Some(location.trim().to_string()).filter(|location| !location.is_empty());
+            event.tags = parse_tags(&tags);
+            event.recurrence = recurrence.map(|frequency| Recurrence {
+                weekdays: if frequency == Frequency::Weekly {
+                    weekdays
+                } else {
+                    Vec::new()
+                },
+                ..Recurrence::new(frequency)
             });
-            Row::new(cells).height(1)
-        })
-        .collect();
+            Ok(event)
+        });
+        match result {
+            Ok(event) => {
+                if let Some(warning) = self.add_popup_event(event, confirm_past) {
+                    if let PopupState::CreateEvent {
+                        error,
+                        confirm_past,
+                        ..
+                    } = &mut self.popup
+                    {
+                        *error = Some(warning);
+                        *confirm_past = true;
+                    }
+                }
+            }
+            Err(err) => {
+                if let PopupState::CreateEvent { error, .. } = &mut self.popup {
+                    *error = Some(err.to_string());
+                }
+            }
+        }
+    }
 
-    let widths = [
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-    ];
+    /// Adds an event entered in a popup and closes it. An event in the past is
+    /// only added once confirmed; until then the warning to show is returned.
+    fn add_popup_event(
+        &mut self,
+        mut event: cal_events::Event,
+        allow_past: bool,
+    ) -> Option<String> {
+        event.calendar = self.target_calendar.clone();
+        let title = event.title.clone();
+        match self.event_manager.add_event_checked(event, allow_past) {
+            Err(err) if err.is::<PastEventError>() => {
+                return Some(format!("{err}, press Enter again to add it"));
+            }
+            Ok(_) => {
+                self.dirty = true;
+                self.set_status(format!("Added '{}'", title));
+            }
+            Err(err) => self.set_status(err.to_string()),
+        }
+        self.close_popup();
+        None
+    }
 
-    Table::new(rows, widths)
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!(
-            "{}  {}",
-            app.calendar.current_date.format("%B"),
-            app.calendar.current_date.format("%Y")
-        )))
-        .column_spacing(1)
-}
+    /// Scrolls the hour timeline of the week or day view by `hours`, negative
+    /// to scroll up.
+    fn scroll_timeline(&mut self, hours: isize) {
+        let scroll = match self.view_mode {
+            ViewMode::Week => &mut self.week_scroll,
+            ViewMode::Day => &mut self.day_scroll,
+            ViewMode::Month | ViewMode::Year | ViewMode::Agenda => return,
+        };
+        *scroll = clamp_timeline_scroll(*scroll, hours);
+    }
 
-fn create_clock() -> String {
-    Local::now().format("%H:%M:%S").to_string()
-}
+    /// Moves the focused hour of the week or day view, scrolling to keep it visible.
+    fn move_focused_hour(&mut self, down: bool) {
+        let hour = match self.focused_hour {
+            Some(hour) if down => (hour + 1).min(23),
+            Some(hour) => hour.saturating_sub(1),
+            None => DEFAULT_EVENT_HOUR,
+        };
+        self.focused_hour = Some(hour);
 
-fn ui(f: &mut Frame, app: &App) {
-    let area = f.area();
+        let scroll = match self.view_mode {
+            ViewMode::Week => &mut self.week_scroll,
+            ViewMode::Day => &mut self.day_scroll,
+            ViewMode::Month | ViewMode::Year | ViewMode::Agenda => return,
+        };
+        let hour = hour as usize;
+        if hour < *scroll {
+            *scroll = hour;
+        } else if hour >= *scroll + TIMELINE_VISIBLE_HOURS {
+            *scroll = hour + 1 - TIMELINE_VISIBLE_HOURS;
+        }
+    }
 
-    let main_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
-        .split(area);
+    /// Where a new event starts: the focused timeline hour on the selected day.
+    fn new_event_start(&self) -> DateTime<Local> {
+        let hour = self
+            .focused_hour
+            .filter(|_| matches!(self.view_mode, ViewMode::Week | ViewMode::Day))
+            .unwrap_or(DEFAULT_EVENT_HOUR);
+        local_midnight(self.calendar.selected_naive()) + chrono::Duration::hours(hour.into())
+    }
 
-    let content_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(if app.view_mode == ViewMode::Month {
-            vec![
+    /// First and last day of the agenda, by default today and the following
+    /// `AGENDA_DAYS` days.
+    fn agenda_dates(&self) -> (NaiveDate, NaiveDate) {
+        self.agenda_range
+            .unwrap_or_else(|| default_agenda_range(Local::now().date_naive()))
+    }
+
+    /// Events shown in the agenda view, starting on one of `agenda_dates`.
+    fn agenda_events(&self) -> Vec<Cow<'_, cal_events::Event>> {
+        let (first, last) = self.agenda_dates();
+        let end = last
+            .succ_opt()
+            .map_or(DateTime::<Local>::MAX_UTC.into(), local_midnight);
+        let mut events = self
+            .event_manager
+            .list_events_in_range(local_midnight(first), end);
+        events.retain(|event| self.passes_filters(event));
+        events
+    }
+
+    /// The next few events starting after `now` on any day, for the sidebar.
+    fn upcoming_events(&self, now: DateTime<Local>) -> Vec<Cow<'_, cal_events::Event>> {
+        let mut events = self
+            .event_manager
+            .list_events_in_range(now, now + chrono::Duration::days(UPCOMING_DAYS));
+        if self.hide_cancelled {
+            events.retain(|event| event.status != EventStatus::Cancelled);
+        }
+        events.truncate(UPCOMING_EVENTS);
+        events
+    }
+
+    fn scroll_agenda(&mut self, lines: isize) {
+        let max_scroll = agenda_lines(
+            &self.agenda_events(),
+            &self.theme,
+            &self.weekday_labels,
+            &self.month_labels,
+            self.time_format,
+        )
+        .len()
+        .saturating_sub(1);
+        self.agenda_scroll = self
+            .agenda_scroll
+            .saturating_add_signed(lines)
+            .min(max_scroll);
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) if self.view_mode == ViewMode::Month => {
+                let Some(area) = self.month_grid_area else {
+                    return;
+                };
+                let Some((week, weekday)) = month_cell_at(
+                    area,
+                    mouse.column,
+                    mouse.row,
+                    self.show_week_numbers,
+                    self.month_rows,
+                ) else {
+                    return;
+                };
+                let grid = self.calendar.get_month_grid();
+                if let Some(day) = grid.get(week).and_then(|days| days[weekday]) {
+                    self.calendar.selected_date = self.calendar.safe_with_day(day);
+                    self.focused_panel = FocusedPanel::Calendar;
+                    self.events_scroll = 0;
+                }
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.drag_start = self.timeline_slot_at(mouse.column, mouse.row);
+            }
+            MouseEventKind::Drag(MouseButton::Left) if self.drag_start.is_some() => {
+                if let Some((_, hour)) = self.timeline_slot_at(mouse.column, mouse.row) {
+                    self.focused_hour = Some(hour);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                let Some((day, first)) = self.drag_start.take() else {
+                    return;
+                };
+                // Only the hours count when a drag strays into another day
+                let Some((_, last)) = self.timeline_slot_at(mouse.column, mouse.row) else {
+                    return;
+                };
+                self.calendar.go_to_date(day);
+                self.focused_panel = FocusedPanel::WeekView;
+                self.events_scroll = 0;
+                self.focused_hour = Some(last);
+                if first != last && self.read_only {
+                    self.set_status(READ_ONLY_MESSAGE);
+                } else if first != last {
+                    let start =
+                        local_midnight(day) + chrono::Duration::hours(first.min(last).into());
+                    let hours = first.abs_diff(last) + 1;
+                    self.popup = PopupState::create_event(
+                        start,
+                        start + chrono::Duration::hours(hours.into()),
+                        None,
+                    );
+                }
+            }
+            MouseEventKind::ScrollUp if self.view_mode == ViewMode::Agenda => {
+                self.scroll_agenda(-1);
+            }
+            MouseEventKind::ScrollDown if self.view_mode == ViewMode::Agenda => {
+                self.scroll_agenda(1);
+            }
+            MouseEventKind::ScrollUp => self.scroll_timeline(-1),
+            MouseEventKind::ScrollDown => self.scroll_timeline(1),
+            _ => {}
+        }
+    }
+
+    /// Day and hour of the week or day view under a terminal position.
+    fn timeline_slot_at(&self, column: u16, row: u16) -> Option<(NaiveDate, u32)> {
+        let area = self.timeline_area?;
+        let (widths, scroll): (&[Constraint], usize) = match self.view_mode {
+            ViewMode::Week => (&WEEK_VIEW_WIDTHS, self.week_scroll),
+            ViewMode::Day => (&DAY_VIEW_WIDTHS, self.day_scroll),
+            ViewMode::Month | ViewMode::Year | ViewMode::Agenda => return None,
+        };
+        let (index, hour) = timeline_cell_at(area, column, row, widths, scroll)?;
+        let day = match self.view_mode {
+            // The time labels belong to no day of the week
+            ViewMode::Week => *self.calendar.selected_week().get(index.checked_sub(1)?)?,
+            _ => self.calendar.selected_naive(),
+        };
+        Some((day, hour))
+    }
+
+    fn check_reminders(&mut self) {
+        for (title, body) in self.fire_due_reminders(Local::now()) {
+            match Notification::new().summary(&title).body(&body).show() {
+                Ok(_) => self.set_status(format!("Reminder: '{}' ({})", title, body)),
+                Err(err) => self.set_status(format!("Reminder for '{}' failed: {}", title, err)),
+            }
+        }
+    }
+
+    /// Marks reminders that fell due since the last check, or whose snooze
+    /// ran out, as fired and returns the title and body of each to notify.
+    /// The first one opens the reminder popup unless another popup is open.
+    fn fire_due_reminders(&mut self, now: DateTime<Local>) -> Vec<(String, String)> {
+        let due_now = self
+            .event_manager
+            .due_reminders(self.last_reminder_check, now)
+            .into_iter()
+            .filter(|event| !self.reminders.contains_key(&(event.id, event.start_time)));
+        let snooze_over = self
+            .reminders
+            .iter()
+            .filter_map(|(&(id, start), state)| match state {
+                ReminderState::Snoozed(until) if *until <= now => {
+                    self.event_manager.occurrence(id, start)
+                }
+                _ => None,
+            });
+        let due: Vec<(Uuid, DateTime<FixedOffset>, String, String)> = due_now
+            .chain(snooze_over)
+            .map(|event| {
+                let body = format!(
+                    "Starts at {} on {}",
+                    format_time(&event.start_time, self.time_format),
+                    self.month_labels
+                        .day_and_month(event.start_time.date_naive())
+                );
+                (event.id, event.start_time, event.title.clone(), body)
+            })
+            .collect();
+        self.last_reminder_check = now;
+
+        due.into_iter()
+            .map(|(id, start, title, body)| {
+                self.reminders.insert((id, start), ReminderState::Fired);
+                if self.popup == PopupState::Hidden {
+                    self.popup = PopupState::Reminder {
+                        id,
+                        start,
+                        title: title.clone(),
+                        body: body.clone(),
+                    };
+                }
+                (title, body)
+            })
+            .collect()
+    }
+
+    /// Fires the reminder in the popup again `minutes` after `now`.
+    fn snooze_reminder(&mut self, minutes: i64, now: DateTime<Local>) {
+        let PopupState::Reminder {
+            id, start, title, ..
+        } = &self.popup
+        else {
+            return;
+        };
+        let snooze = chrono::Duration::minutes(minutes);
+        self.reminders
+            .insert((*id, *start), ReminderState::Snoozed(now + snooze));
+        let status = format!("Snoozed '{}' for {}", title, format_duration(snooze));
+        self.close_popup();
+        self.set_status(status);
+    }
+
+    pub fn with_config(config: Config, prefs: Preferences) -> Self {
+        let mut app = Self::with_preferences(prefs);
+        app.keys = config.keys;
+        app.weekday_labels = config.weekday_labels;
+        app.month_labels = config.month_labels;
+        if let Some(theme) = config.theme {
+            app.theme = theme.with_color(app.theme.color_enabled);
+        }
+        app
+    }
+
+    fn handle_action(&mut self, action: Action) {
+        // Any other action cancels a pending bulk delete
+        let pending_day_delete = self.pending_day_delete.take();
+        if self.read_only && action.modifies_events() {
+            self.set_status(READ_ONLY_MESSAGE);
+            return;
+        }
+        match action {
+            Action::Quit => {}
+            Action::Help => self.popup = PopupState::Help,
+            Action::ViewMonth => self.view_mode = ViewMode::Month,
+            Action::ViewWeek => self.view_mode = ViewMode::Week,
+            Action::ViewDay => self.view_mode = ViewMode::Day,
+            Action::ViewYear => self.view_mode = ViewMode::Year,
+            Action::ViewAgenda => {
+                self.view_mode = ViewMode::Agenda;
+                self.agenda_scroll = 0;
+            }
+            Action::AgendaRange => {
+                let input = self
+                    .agenda_range
+                    .map(|(first, last)| format!("{} to {}", first, last))
+                    .unwrap_or_default();
+                self.popup = PopupState::AgendaRange { input, error: None };
+            }
+            Action::ToggleWeekStart => {
+                let week_start = match self.calendar.week_start {
+                    Weekday::Sun => Weekday::Mon,
+                    _ => Weekday::Sun,
+                };
+                self.calendar.set_week_start(week_start);
+            }
+            Action::CycleTheme => {
+                let next = Theme::PRESETS
+                    .iter()
+                    .position(|name| *name == self.theme_name)
+                    .map_or(0, |index| (index + 1) % Theme::PRESETS.len());
+                self.theme_name = Theme::PRESETS[next].to_string();
+                self.theme = Theme::preset(&self.theme_name)
+                    .unwrap()
+                    .with_color(self.theme.color_enabled);
+                self.set_status(format!("Theme: {}", self.theme_name));
+            }
+            Action::ToggleWeekNumbers => self.show_week_numbers = !self.show_week_numbers,
+            Action::ToggleFitMonth => self.fit_month_grid = !self.fit_month_grid,
+            Action::ToggleOffHours => {
+                self.shade_off_hours = !self.shade_off_hours;
+                self.set_status(if self.shade_off_hours {
+                    "Off hours shaded"
+                } else {
+                    "Off hours not shaded"
+                });
+            }
+            Action::ToggleTimeFormat => {
+                self.time_format = self.time_format.toggled();
+                self.set_status(match self.time_format {
+                    TimeFormat::TwentyFourHour => "24-hour clock",
+                    TimeFormat::TwelveHour => "12-hour clock",
+                });
+            }
+            Action::ToggleHeatmap => {
+                self.heatmap = !self.heatmap;
+                self.set_status(if self.heatmap {
+                    "Heatmap on"
+                } else {
+                    "Heatmap off"
+                });
+            }
+            Action::CycleCategory => {
+                self.cycle_category_filter();
+                self.set_status(format!("Showing {}", filter_label(self)));
+            }
+            Action::CycleTag => {
+                self.cycle_tag_filter();
+                self.set_status(format!("Showing {}", filter_label(self)));
+            }
+            Action::CycleCalendar => {
+                self.cycle_target_calendar();
+                self.set_status(format!("New events go to '{}'", self.target_calendar));
+            }
+            Action::ToggleCalendar => {
+                let visible = !self
+                    .event_manager
+                    .is_calendar_visible(&self.target_calendar);
+                self.event_manager
+                    .set_calendar_visible(&self.target_calendar, visible);
+                self.events_scroll = 0;
+                self.set_status(format!(
+                    "Calendar '{}' {}",
+                    self.target_calendar,
+                    if visible { "shown" } else { "hidden" }
+                ));
+            }
+            Action::ToggleCancelled => {
+                self.hide_cancelled = !self.hide_cancelled;
+                self.events_scroll = 0;
+                self.set_status(if self.hide_cancelled {
+                    "Cancelled events hidden"
+                } else {
+                    "Cancelled events shown"
+                });
+            }
+            Action::FirstDayOfMonth => {
+                self.calendar.select_first_day();
+                self.events_scroll = 0;
+            }
+            Action::LastDayOfMonth => {
+                self.calendar.select_last_day();
+                self.events_scroll = 0;
+            }
+            Action::SelectWeekday(weekday) => {
+                self.calendar.select_weekday_in_current_week(weekday);
+                self.events_scroll = 0;
+            }
+            Action::NextEventDay | Action::PreviousEventDay => {
+                let selected = self.calendar.selected_naive();
+                let day = if action == Action::NextEventDay {
+                    self.event_manager.next_day_with_events(selected)
+                } else {
+                    self.event_manager.previous_day_with_events(selected)
+                };
+                match day {
+                    Some(day) => {
+                        self.calendar.go_to_date(day);
+                        self.events_scroll = 0;
+                    }
+                    None => self.set_status("No more events"),
+                }
+            }
+            Action::MoveLeft if self.view_mode == ViewMode::Year => self.calendar.prev_year(),
+            Action::MoveRight if self.view_mode == ViewMode::Year => self.calendar.next_year(),
+            Action::MoveUp if self.view_mode == ViewMode::Year => self.calendar.prev_month(),
+            Action::MoveDown if self.view_mode == ViewMode::Year => self.calendar.next_month(),
+            Action::MoveUp if self.view_mode == ViewMode::Agenda => self.scroll_agenda(-1),
+            Action::MoveDown if self.view_mode == ViewMode::Agenda => self.scroll_agenda(1),
+            Action::MoveLeft if self.view_mode == ViewMode::Week => self.calendar.advance_days(-7),
+            Action::MoveRight if self.view_mode == ViewMode::Week => self.calendar.advance_days(7),
+            Action::MoveLeft => self.select_previous_day(),
+            Action::MoveRight => self.select_next_day(),
+            Action::MoveUp => match self.focused_panel {
+                FocusedPanel::WeekView
+                    if matches!(self.view_mode, ViewMode::Week | ViewMode::Day) =>
+                {
+                    self.move_focused_hour(false);
+                }
+                FocusedPanel::Events if self.view_mode == ViewMode::Month => {
+                    self.events_scroll = self.events_scroll.saturating_sub(1);
+                }
+                _ => {
+                    self.calendar.move_selection(cal_core::Direction::Up);
+                }
+            },
+            Action::MoveDown => match self.focused_panel {
+                FocusedPanel::WeekView
+                    if matches!(self.view_mode, ViewMode::Week | ViewMode::Day) =>
+                {
+                    self.move_focused_hour(true);
+                }
+                FocusedPanel::Events if self.view_mode == ViewMode::Month => {
+                    if self.events_scroll + 1 < self.day_events().len() {
+                        self.events_scroll += 1;
+                    }
+                }
+                _ => {
+                    self.calendar.move_selection(cal_core::Direction::Down);
+                }
+            },
+            Action::ScrollTimelineUp => self.scroll_timeline(-TIMELINE_PAGE_HOURS),
+            Action::ScrollTimelineDown => self.scroll_timeline(TIMELINE_PAGE_HOURS),
+            Action::PagePrev => match self.view_mode {
+                ViewMode::Week => self.calendar.advance_days(-7),
+                ViewMode::Day => self.calendar.advance_days(-1),
+                ViewMode::Month | ViewMode::Year => self.calendar.prev_month(),
+                ViewMode::Agenda => self.scroll_agenda(-AGENDA_PAGE_LINES),
+            },
+            Action::PageNext => match self.view_mode {
+                ViewMode::Week => self.calendar.advance_days(7),
+                ViewMode::Day => self.calendar.advance_days(1),
+                ViewMode::Month | ViewMode::Year => self.calendar.next_month(),
+                ViewMode::Agenda => self.scroll_agenda(AGENDA_PAGE_LINES),
+            },
+            Action::NextPanel => {
+                self.focused_panel = match self.focused_panel {
+                    FocusedPanel::Calendar => FocusedPanel::WeekView,
+                    FocusedPanel::WeekView => FocusedPanel::Events,
+                    FocusedPanel::Events => FocusedPanel::Calendar,
+                };
+            }
+            Action::Select => {
+                if self.view_mode == ViewMode::Year {
+                    self.view_mode = ViewMode::Month;
+                }
+            }
+            Action::Today => self.calendar.go_to_today(),
+            Action::GoToDate => {
+                self.popup = PopupState::GoToDate {
+                    input: String::new(),
+                    error: None,
+                };
+            }
+            Action::AddEvent => {
+                let on_timeline = self.focused_panel == FocusedPanel::WeekView
+                    && matches!(self.view_mode, ViewMode::Week | ViewMode::Day);
+                if self.focused_panel == FocusedPanel::Events || on_timeline {
+                    let start = self.new_event_start();
+                    self.popup =
+                        PopupState::create_event(start, start + chrono::Duration::hours(1), None);
+                }
+            }
+            Action::Duplicate if self.focused_panel == FocusedPanel::Events => {
+                if let Some(event) = self.highlighted_event() {
+                    // Same time of day and length, but on the selected day
+                    let start = self
+                        .calendar
+                        .selected_date
+                        .date_naive()
+                        .and_time(event.start_time.time());
+                    let start = Local
+                        .from_local_datetime(&start)
+                        .earliest()
+                        .unwrap_or(self.calendar.selected_date);
+                    let end = start + (event.end_time - event.start_time);
+                    self.popup = PopupState::create_event(start, end, Some(&event));
+                }
+            }
+            Action::Duplicate => {}
+            Action::ExtendEvent if self.focused_panel == FocusedPanel::Events => {
+                self.resize_highlighted_event(true);
+            }
+            Action::ShrinkEvent if self.focused_panel == FocusedPanel::Events => {
+                self.resize_highlighted_event(false);
+            }
+            Action::ExtendEvent | Action::ShrinkEvent => {}
+            Action::MoveEventEarlier | Action::MoveEventLater
+                if self.focused_panel == FocusedPanel::Events =>
+            {
+                self.move_highlighted_event(if action == Action::MoveEventLater {
+                    1
+                } else {
+                    -1
+                });
+            }
+            Action::MoveEventEarlier | Action::MoveEventLater => {}
+            Action::CopyEvent => self.copy_highlighted_event(),
+            Action::Search => {
+                self.popup = PopupState::Search {
+                    query: String::new(),
+                    selected: 0,
+                };
+            }
+            Action::QuickAdd => {
+                self.popup = PopupState::QuickAdd {
+                    input: String::new(),
+                    error: None,
+                    confirm_past: false,
+                };
+            }
+            Action::Import => {
+                self.popup = PopupState::ImportFile {
+                    path: String::new(),
+                    error: None,
+                };
+            }
+            Action::Export => {
+                match std::fs::write(ICS_EXPORT_PATH, self.event_manager.export_ics()) {
+                    Ok(()) => self.set_status(format!("Exported to {}", ICS_EXPORT_PATH)),
+                    Err(err) => self.set_status(format!("Export failed: {}", err)),
+                }
+            }
+            Action::ExportCsv => {
+                match std::fs::write(CSV_EXPORT_PATH, self.event_manager.export_csv()) {
+                    Ok(()) => self.set_status(format!("Exported to {}", CSV_EXPORT_PATH)),
+                    Err(err) => self.set_status(format!("Export failed: {}", err)),
+                }
+            }
+            Action::DeleteDay => {
+                let date = self.calendar.selected_naive();
+                let count = self.event_manager.count_events_starting_on(date);
+                if count == 0 {
+                    self.set_status(format!(
+                        "No events start on {}",
+                        self.month_labels.day_and_month(date)
+                    ));
+                } else if pending_day_delete == Some(date) {
+                    let removed = self.event_manager.delete_events_for_day(date);
+                    self.dirty = true;
+                    self.events_scroll = 0;
+                    self.set_status(format!(
+                        "Deleted {} events, press {} to undo",
+                        removed,
+                        self.keys.binding(Action::Undo)
+                    ));
+                } else {
+                    self.pending_day_delete = Some(date);
+                    self.set_status(format!(
+                        "Delete {} events starting on {}? Press {} again to confirm",
+                        count,
+                        self.month_labels.day_and_month(date),
+                        self.keys.binding(Action::DeleteDay)
+                    ));
+                }
+            }
+            Action::DeleteOccurrence if self.focused_panel == FocusedPanel::Events => {
+                let Some((id, title, date)) = self
+                    .highlighted_event()
+                    .map(|event| (event.id, event.title.clone(), event.start_time.date_naive()))
+                else {
+                    return;
+                };
+                match self.event_manager.delete_occurrence(id, date) {
+                    Ok(()) => {
+                        self.dirty = true;
+                        self.set_status(format!(
+                            "Deleted '{}' on {}, press {} to undo",
+                            title,
+                            self.month_labels.day_and_month(date),
+                            self.keys.binding(Action::Undo)
+                        ));
+                    }
+                    Err(err) => self.set_status(err.to_string()),
+                }
+            }
+            Action::DeleteOccurrence => {}
+            Action::Command => {
+                self.popup = PopupState::Command {
+                    input: String::new(),
+                };
+            }
+            Action::Undo => {
+                let description = self.event_manager.last_operation().map(|op| op.to_string());
+                match self.event_manager.undo() {
+                    Ok(()) => {
+                        self.dirty = true;
+                        self.set_status(format!("Undid {}", description.unwrap_or_default()));
+                    }
+                    Err(err) => self.set_status(err.to_string()),
+                }
+            }
+        }
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<()> {
+        if self.read_only && command.modifies_events() {
+            return Err(anyhow!(READ_ONLY_MESSAGE));
+        }
+        match command {
+            Command::Delete(title) => {
+                let matches: Vec<&cal_events::Event> = self
+                    .event_manager
+                    .list_events()
+                    .into_iter()
+                    .filter(|event| event.title.eq_ignore_ascii_case(&title))
+                    .collect();
+                // Several events may share a title, so prefer one on the selected day
+                let selected = self.calendar.selected_naive();
+                let on_selected_day: Vec<&cal_events::Event> = matches
+                    .iter()
+                    .copied()
+                    .filter(|event| event.occurs_on(selected))
+                    .collect();
+                let event = match (matches.as_slice(), on_selected_day.as_slice()) {
+                    ([], _) => return Err(anyhow!("No event titled '{}'", title)),
+                    ([event], _) | (_, [event]) => *event,
+                    (all, _) => {
+                        return Err(anyhow!(
+                            "{} events are titled '{}', select the day of the one to delete",
+                            all.len(),
+                            title
+                        ))
+                    }
+                };
+                let (id, title) = (event.id, event.title.clone());
+                self.event_manager.delete_event(id)?;
+                self.dirty = true;
+                self.set_status(format!("Deleted '{}'", title));
+            }
+            Command::GoTo(date) => self.calendar.go_to_date(date),
+            Command::Export(ExportFormat::Ics) => self.handle_action(Action::Export),
+            Command::Export(ExportFormat::Csv) => self.handle_action(Action::ExportCsv),
+            Command::View(view_mode) => self.view_mode = view_mode,
+            Command::Calendar(name) => {
+                self.event_manager.add_calendar(&name);
+                self.set_status(format!("New events go to '{}'", name));
+                self.target_calendar = name;
+            }
+            Command::Save => {
+                self.save_events()?;
+                self.set_status("Saved events");
+            }
+        }
+        Ok(())
+    }
+
+    /// Dismisses whichever popup is open, discarding anything typed into it.
+    fn close_popup(&mut self) {
+        self.popup = PopupState::Hidden;
+    }
+
+    fn set_status(&mut self, message: impl Into<String>) {
+        self.status = Some((message.into(), Instant::now()));
+    }
+
+    fn select_previous_day(&mut self) {
+        if self.calendar.move_selection(cal_core::Direction::Left) {
+            return;
+        }
+        self.calendar.prev_month();
+        let grid = self.calendar.get_month_grid();
+        for week in grid.iter().rev() {
+            if let Some(Some(last_day)) = week.iter().rev().find(|d| d.is_some()) {
+                self.calendar.selected_date = self.calendar.safe_with_day(*last_day);
+                break;
+            }
+        }
+    }
+
+    fn select_next_day(&mut self) {
+        if self.calendar.move_selection(cal_core::Direction::Right) {
+            return;
+        }
+        self.calendar.next_month();
+        if let Some(Some(first_day)) = self
+            .calendar
+            .get_month_grid()
+            .iter()
+            .flat_map(|week| week.iter())
+            .find(|d| d.is_some())
+        {
+            self.calendar.selected_date = self.calendar.safe_with_day(*first_day);
+        }
+    }
+}
+
+/// Startup settings given on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Show events without allowing any change to them.
+    pub read_only: bool,
+}
+
+/// Runs the app until the user quits, restoring the terminal afterwards.
+pub fn run(options: RunOptions) -> Result<(), AppError> {
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let prefs = Preferences::load();
+    let mut app = match Config::load() {
+        Ok(config) => App::with_config(config, prefs),
+        Err(err) => {
+            let mut app = App::with_preferences(prefs);
+            app.set_status(format!("{err:#}; using default settings"));
+            app
+        }
+    };
+    app.read_only = options.read_only;
+    if let Some(path) = App::default_events_path() {
+        if let Err(err) = app.open_events(path) {
+            app.set_status(format!("{err:#}"));
+        }
+    }
+    let res = run_app(&mut terminal, &mut app);
+
+    disable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
+    terminal.show_cursor()?;
+
+    for text in &app.print_on_exit {
+        println!("{text}\n");
+    }
+    res
+}
+
+fn run_app(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+) -> Result<(), AppError> {
+    let mut last_tick = Instant::now();
+    let mut needs_redraw = true;
+    let mut drawn_second = None;
+
+    loop {
+        // Between inputs only the clock changes, once a second
+        let second = Local::now().timestamp();
+        if needs_redraw || drawn_second != Some(second) {
+            terminal.draw(|f| ui(f, app))?;
+            drawn_second = Some(second);
+            needs_redraw = false;
+        }
+
+        let timeout = app
+            .tick_rate
+            .saturating_sub(last_tick.elapsed())
+            .min(until_next_second(Local::now()));
+
+        if crossterm::event::poll(timeout)? {
+            needs_redraw = true;
+            match event::read()? {
+                Event::Mouse(mouse) if app.popup == PopupState::Hidden => app.handle_mouse(mouse),
+                Event::Key(key) => match key.code {
+                    // Esc only ever closes the open popup, so it can never quit the app
+                    KeyCode::Esc => app.close_popup(),
+                    // First handle popup-specific keys if popup is active
+                    _ if app.popup == PopupState::Help => app.close_popup(),
+                    // Not every terminal reports Shift+Enter, but Alt+Enter gets through
+                    KeyCode::Enter
+                        if key
+                            .modifiers
+                            .intersects(KeyModifiers::SHIFT | KeyModifiers::ALT) =>
+                    {
+                        if let PopupState::CreateEvent {
+                            description,
+                            focused_field: DESCRIPTION_FIELD,
+                            ..
+                        } = &mut app.popup
+                        {
+                            description.push('\n');
+                        }
+                        app.popup.forget_past_warning();
+                    }
+                    key if matches!(app.popup, PopupState::CreateEvent { .. }) => match key {
+                        KeyCode::Up => {
+                            check_time_field(&mut app.popup);
+                            if let PopupState::CreateEvent {
+                                ref mut focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                if *focused_field > 0 {
+                                    *focused_field -= 1;
+                                }
+                            }
+                        }
+                        KeyCode::Down => {
+                            check_time_field(&mut app.popup);
+                            if let PopupState::CreateEvent {
+                                ref mut focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                if *focused_field < CREATE_EVENT_FIELDS - 1 {
+                                    *focused_field += 1;
+                                }
+                            }
+                        }
+                        KeyCode::Left | KeyCode::Right if app.popup.time_field_focused() => {
+                            app.popup.step_time(key == KeyCode::Right);
+                            app.popup.forget_past_warning();
+                        }
+                        KeyCode::Char(c @ ('+' | '-')) if app.popup.time_field_focused() => {
+                            app.popup.step_time(c == '+');
+                            app.popup.forget_past_warning();
+                        }
+                        KeyCode::Left | KeyCode::Right => {
+                            app.popup.forget_past_warning();
+                            if let PopupState::CreateEvent {
+                                ref mut priority,
+                                ref mut status,
+                                ref mut recurrence,
+                                focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                match *focused_field {
+                                    PRIORITY_FIELD => {
+                                        *priority =
+                                            cycle_priority(*priority, key == KeyCode::Right);
+                                    }
+                                    STATUS_FIELD => {
+                                        *status = cycle_status(*status, key == KeyCode::Right);
+                                    }
+                                    RECURRENCE_FIELD => {
+                                        *recurrence =
+                                            cycle_recurrence(*recurrence, key == KeyCode::Right);
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        KeyCode::Tab => {
+                            check_time_field(&mut app.popup);
+                            if let PopupState::CreateEvent {
+                                ref mut focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                *focused_field = (*focused_field + 1) % CREATE_EVENT_FIELDS;
+                            }
+                        }
+                        KeyCode::Char(c) => {
+                            app.popup.forget_past_warning();
+                            if let PopupState::CreateEvent {
+                                ref mut title,
+                                ref mut description,
+                                ref mut start_time,
+                                ref mut end_time,
+                                ref mut reminder,
+                                ref mut attendees,
+                                ref mut location,
+                                ref mut tags,
+                                recurrence,
+                                ref mut weekdays,
+                                focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                match *focused_field {
+                                    0 => title.push(c),
+                                    1 => description.push(c),
+                                    2 => push_datetime_digit(start_time, c),
+                                    3 => push_end_time_char(end_time, c),
+                                    4 => reminder.push(c),
+                                    ATTENDEES_FIELD => attendees.push(c),
+                                    LOCATION_FIELD => location.push(c),
+                                    TAGS_FIELD => tags.push(c),
+                                    RECURRENCE_FIELD if *recurrence == Some(Frequency::Weekly) => {
+                                        toggle_weekday(weekdays, c);
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        KeyCode::Backspace => {
+                            app.popup.forget_past_warning();
+                            if let PopupState::CreateEvent {
+                                ref mut title,
+                                ref mut description,
+                                ref mut start_time,
+                                ref mut end_time,
+                                ref mut reminder,
+                                ref mut attendees,
+                                ref mut location,
+                                ref mut tags,
+                                focused_field,
+                                ..
+                            } = &mut app.popup
+                            {
+                                match *focused_field {
+                                    0 => {
+                                        title.pop();
+                                    }
+                                    1 => {
+                                        description.pop();
+                                    }
+                                    2 => pop_datetime_char(start_time),
+                                    3 if is_duration_input(end_time) => {
+                                        end_time.pop();
+                                    }
+                                    3 => pop_datetime_char(end_time),
+                                    4 => {
+                                        reminder.pop();
+                                    }
+                                    ATTENDEES_FIELD => {
+                                        attendees.pop();
+                                    }
+                                    LOCATION_FIELD => {
+                                        location.pop();
+                                    }
+                                    TAGS_FIELD => {
+                                        tags.pop();
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                        KeyCode::Enter => app.submit_event_popup(),
+                        _ => {}
+                    },
+                    key if matches!(app.popup, PopupState::GoToDate { .. }) => {
+                        if let PopupState::GoToDate { input, error } = &mut app.popup {
+                            match key {
+                                KeyCode::Char(c) => push_go_to_char(input, c),
+                                KeyCode::Backspace if is_absolute_date_input(input) => {
+                                    pop_datetime_char(input);
+                                }
+                                KeyCode::Backspace => {
+                                    input.pop();
+                                }
+                                KeyCode::Enter => {
+                                    let today = Local::now().date_naive();
+                                    match parse_relative_date(input, today) {
+                                        Some(date) => {
+                                            app.calendar.go_to_date(date);
+                                            app.close_popup();
+                                        }
+                                        None => {
+                                            *error = Some(format!(
+                                                "Invalid date '{}', try YYYY-MM-DD, tomorrow, next fri or +3d",
+                                                input
+                                            ));
+                                        }
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    key if matches!(app.popup, PopupState::AgendaRange { .. }) => {
+                        if let PopupState::AgendaRange { input, error } = &mut app.popup {
+                            match key {
+                                KeyCode::Char(c) => input.push(c),
+                                KeyCode::Backspace => {
+                                    input.pop();
+                                }
+                                KeyCode::Enter => {
+                                    match parse_agenda_range(input, Local::now().date_naive()) {
+                                        Ok(range) => {
+                                            app.agenda_range = range;
+                                            app.agenda_scroll = 0;
+                                            app.close_popup();
+                                        }
+                                        Err(err) => *error = Some(err.to_string()),
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    key if matches!(app.popup, PopupState::Search { .. }) => {
+                        if let PopupState::Search { query, selected } = &mut app.popup {
+                            let result_count = app.event_manager.search(query).len();
+                            match key {
+                                KeyCode::Char(c) => {
+                                    query.push(c);
+                                    *selected = 0;
+                                }
+                                KeyCode::Backspace => {
+                                    query.pop();
+                                    *selected = 0;
+                                }
+                                KeyCode::Up => *selected = selected.saturating_sub(1),
+                                KeyCode::Down if *selected + 1 < result_count => {
+                                    *selected += 1;
+                                }
+                                KeyCode::Enter => {
+                                    if let Some(event) =
+                                        app.event_manager.search(query).get(*selected)
+                                    {
+                                        app.calendar.go_to_date(event.start_time.date_naive());
+                                        app.close_popup();
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    key if matches!(app.popup, PopupState::QuickAdd { .. }) => {
+                        if let PopupState::QuickAdd {
+                            input,
+                            error,
+                            confirm_past,
+                        } = &mut app.popup
+                        {
+                            match key {
+                                KeyCode::Char(c) => {
+                                    input.push(c);
+                                    *confirm_past = false;
+                                }
+                                KeyCode::Backspace => {
+                                    input.pop();
+                                    *confirm_past = false;
+                                }
+                                KeyCode::Enter => {
+                                    let date = app.calendar.selected_naive();
+                                    match parse_quick_event(input, date) {
+                                        Ok(event) => {
+                                            let (input, allow_past) =
+                                                (input.clone(), *confirm_past);
+                                            if let Some(warning) =
+                                                app.add_popup_event(event, allow_past)
+                                            {
+                                                app.popup = PopupState::QuickAdd {
+                                                    input,
+                                                    error: Some(warning),
+                                                    confirm_past: true,
+                                                };
+                                            }
+                                        }
+                                        Err(err) => *error = Some(format!("{err:#}")),
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    key if matches!(app.popup, PopupState::ImportFile { .. }) => {
+                        if let PopupState::ImportFile { path, error } = &mut app.popup {
+                            match key {
+                                KeyCode::Char(c) => path.push(c),
+                                KeyCode::Backspace => {
+                                    path.pop();
+                                }
+                                KeyCode::Enter => match std::fs::read_to_string(path.trim()) {
+                                    Ok(content) => {
+                                        let report = app.event_manager.import_ics(&content);
+                                        app.dirty |= report.changed();
+                                        app.close_popup();
+                                        app.set_status(import_status(&report));
+                                    }
+                                    Err(err) => *error = Some(err.to_string()),
+                                },
+                                _ => {}
+                            }
+                        }
+                    }
+                    key if matches!(app.popup, PopupState::Command { .. }) => {
+                        if let PopupState::Command { input } = &mut app.popup {
+                            match key {
+                                KeyCode::Char(c) => input.push(c),
+                                KeyCode::Backspace => {
+                                    input.pop();
+                                }
+                                KeyCode::Enter => {
+                                    let input = std::mem::take(input);
+                                    app.close_popup();
+                                    if let Err(err) =
+                                        Command::parse(&input).and_then(|c| app.run_command(c))
+                                    {
+                                        app.set_status(err.to_string());
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    code if matches!(app.popup, PopupState::Reminder { .. }) => match code {
+                        KeyCode::Char(digit @ '1'..='3') => {
+                            let choice = digit as usize - '1' as usize;
+                            app.snooze_reminder(SNOOZE_MINUTES[choice], Local::now());
+                        }
+                        KeyCode::Enter => app.close_popup(),
+                        _ => {}
+                    },
+                    code if app.popup == PopupState::ConfirmQuit => match code {
+                        KeyCode::Char('y') => match app.save_events() {
+                            Ok(()) => return save_preferences(app),
+                            Err(err) => {
+                                app.close_popup();
+                                app.set_status(format!("{err:#}"));
+                            }
+                        },
+                        KeyCode::Char('n') => return save_preferences(app),
+                        _ => {}
+                    },
+                    // Then dispatch regular app keys through the configured keybindings
+                    _ => match app
+                        .keys
+                        .action_for_where(&key, |action| app.action_applies(action))
+                    {
+                        Some(Action::Quit) if app.request_quit() => {
+                            return save_preferences(app);
+                        }
+                        Some(Action::Quit) => {}
+                        Some(action) => app.handle_action(action),
+                        None => {}
+                    },
+                },
+                // Redraw at the new size straight away rather than on the next tick
+                Event::Resize(_, _) => terminal.autoresize()?,
+                _ => {}
+            }
+        }
+
+        if last_tick.elapsed() >= app.tick_rate {
+            let status = app.status.clone();
+            app.check_reminders();
+            if app
+                .status
+                .as_ref()
+                .is_some_and(|(_, shown_at)| shown_at.elapsed() >= STATUS_DURATION)
+            {
+                app.status = None;
+            }
+            needs_redraw |= app.status != status;
+            last_tick = Instant::now();
+        }
+    }
+}
+
+/// Time left until the clock shows the next second.
+fn until_next_second(now: DateTime<Local>) -> Duration {
+    // A leap second is reported as nanoseconds past 1_000_000_000
+    let nanos = now.timestamp_subsec_nanos().min(999_999_999);
+    Duration::from_nanos(u64::from(1_000_000_000 - nanos))
+}
+
+fn save_preferences(app: &App) -> Result<(), AppError> {
+    app.preferences().save().map_err(AppError::SavePreferences)
+}
+
+/// How `render_month_grid` lays out and decorates a month.
+struct MonthGridOptions<'a> {
+    year: i32,
+    month: u32,
+    /// Width of each day column; `None` spreads the columns over the whole area
+    /// with tall rows and full weekday names, as in the main month view.
+    cell_width: Option<u16>,
+    show_selection: bool,
+    /// Defaults to the month and year.
+    title: Option<String>,
+    /// Events per day of the month, shown as busy days and, in wide cells, badges.
+    event_counts: Option<&'a HashMap<u32, usize>>,
+    /// Booked minutes per day of the month; when set, days are shaded by it.
+    busy_minutes: Option<&'a HashMap<u32, u32>>,
+    /// Adds a leading column with the ISO week number of each row.
+    show_week_numbers: bool,
+    /// Row heights and first week shown of a grid with full-size cells.
+    rows: MonthRows,
+}
+
+/// How the rows of the main month grid fit its area: full height when there
+/// is room, then shorter rows, then one line per week scrolled to the
+/// selected week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct MonthRows {
+    header_height: u16,
+    row_height: u16,
+    /// Weeks before this one are scrolled out of view.
+    first_week: usize,
+}
+
+impl Default for MonthRows {
+    fn default() -> Self {
+        Self {
+            header_height: MONTH_HEADER_HEIGHT,
+            row_height: MONTH_ROW_HEIGHT,
+            first_week: 0,
+        }
+    }
+}
+
+impl MonthRows {
+    /// Rows for `weeks` weeks inside a grid `inner_height` lines tall,
+    /// keeping `selected_week` visible.
+    fn fit(inner_height: u16, weeks: usize, selected_week: usize) -> Self {
+        let weeks_height = inner_height.saturating_sub(MONTH_HEADER_HEIGHT);
+        if let Some(row_height) = (1..=MONTH_ROW_HEIGHT)
+            .rev()
+            .find(|row_height| weeks as u16 * row_height <= weeks_height)
+        {
+            return Self {
+                row_height,
+                ..Self::default()
+            };
+        }
+
+        let visible_weeks = usize::from(inner_height.saturating_sub(1).max(1));
+        Self {
+            header_height: 1,
+            row_height: 1,
+            first_week: (selected_week + 1)
+                .saturating_sub(visible_weeks)
+                .min(weeks.saturating_sub(visible_weeks)),
+        }
+    }
+}
+
+impl MonthGridOptions<'_> {
+    /// A compact one-line-per-week calendar, as in the sidebar and year view.
+    fn mini(year: i32, month: u32) -> Self {
+        Self {
+            year,
+            month,
+            cell_width: Some(2),
+            show_selection: true,
+            title: None,
+            event_counts: None,
+            busy_minutes: None,
+            show_week_numbers: false,
+            rows: MonthRows::default(),
+        }
+    }
+}
+
+/// Builds the weekday header and day cells of a month, shared by every view
+/// that shows one so they stay styled alike.
+fn render_month_grid(
+    calendar: &Calendar,
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    months: &MonthLabels,
+    options: &MonthGridOptions,
+) -> Table<'static> {
+    let (year, month) = (options.year, options.month);
+    let compact = options.cell_width.is_some();
+
+    let header_cells = calendar.weekdays().map(|day| {
+        let name = if compact {
+            labels.narrow(day).to_string()
+        } else {
+            labels.short(day).to_uppercase()
+        };
+        Cell::from(name).style(theme.fg(theme.muted))
+    });
+    let week_number_header = options
+        .show_week_numbers
+        .then(|| Cell::from("Wk").style(theme.fg(theme.muted)));
+    let header = Row::new(week_number_header.into_iter().chain(header_cells))
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .height(if compact {
+            1
+        } else {
+            options.rows.header_height
+        });
+
+    let today = Local::now().date_naive();
+    let selected = calendar.selected_naive();
+    let rows: Vec<Row> = calendar
+        .get_month_grid_for(year, month)
+        .iter()
+        .skip(if compact { 0 } else { options.rows.first_week })
+        .map(|week| {
+            let cells = week.iter().map(|day| {
+                let Some(d) = day else {
+                    return Cell::from(if compact { "  " } else { "   " });
+                };
+                let date = NaiveDate::from_ymd_opt(year, month, *d).unwrap();
+                let is_current_day = date == today;
+                let is_selected = options.show_selection && date == selected;
+                let event_count = options
+                    .event_counts
+                    .and_then(|counts| counts.get(d).copied())
+                    .unwrap_or(0);
+                let heat = options
+                    .busy_minutes
+                    .and_then(|minutes| minutes.get(d).copied())
+                    .filter(|_| theme.color_enabled)
+                    .and_then(heatmap_style);
+
+                let style = match (is_current_day, is_selected) {
+                    (true, true) => theme
+                        .fg(theme.today)
+                        .add_modifier(Modifier::BOLD | Modifier::REVERSED),
+                    (true, false) => theme.fg(theme.today).add_modifier(Modifier::BOLD),
+                    (false, true) => theme.fg(theme.selected).add_modifier(Modifier::REVERSED),
+                    (false, false) => match heat {
+                        Some(style) => style,
+                        None if event_count > 0 => {
+                            theme.accent(theme.busy_day, Modifier::UNDERLINED)
+                        }
+                        None => Style::default(),
+                    },
+                };
+
+                let text = if compact {
+                    format!("{:2}", d)
+                } else {
+                    format!(" {}{}", d, event_count_badge(event_count))
+                };
+                Cell::from(text).style(style)
+            });
+            let week_number = options.show_week_numbers.then(|| {
+                let number = iso_week_of_row(year, month, week).unwrap_or_default();
+                Cell::from(format!("{:2}", number)).style(theme.fg(theme.muted))
+            });
+            Row::new(week_number.into_iter().chain(cells)).height(if compact {
+                1
+            } else {
+                options.rows.row_height
+            })
+        })
+        .collect();
+
+    let widths = month_grid_widths(options.cell_width, options.show_week_numbers);
+    let title = options
+        .title
+        .clone()
+        .unwrap_or_else(|| format!("{}  {}", months.month_name(month), year));
+
+    Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .column_spacing(1)
+}
+
+/// Renders the twelve months of the displayed year as a 3×4 grid of mini calendars.
+fn draw_year_view(f: &mut Frame, app: &App, area: Rect) {
+    let year = app.calendar.current_date.year();
+    let focused_month = app.calendar.current_date.month();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("Year View - {}", year));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let month_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 3); 3])
+        .split(inner);
+
+    for (row_index, row_area) in month_rows.iter().enumerate() {
+        let month_columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Ratio(1, 4); 4])
+            .split(*row_area);
+
+        for (column_index, month_area) in month_columns.iter().enumerate() {
+            let month = (row_index * 4 + column_index + 1) as u32;
+            let mut mini_calendar = render_month_grid(
+                &app.calendar,
+                &app.theme,
+                &app.weekday_labels,
+                &app.month_labels,
+                &MonthGridOptions {
+                    show_week_numbers: app.show_week_numbers,
+                    ..MonthGridOptions::mini(year, month)
+                },
+            );
+            if month == focused_month {
+                mini_calendar = mini_calendar.block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(app.month_labels.month_name(month).to_string())
+                        .border_style(app.theme.accent(app.theme.focus_border, Modifier::BOLD)),
+                );
+            }
+            f.render_widget(mini_calendar, *month_area);
+        }
+    }
+}
+
+/// Column widths of a month grid, with the week number column first if shown.
+fn month_grid_widths(cell_width: Option<u16>, show_week_numbers: bool) -> Vec<Constraint> {
+    let days = match cell_width {
+        Some(width) => [Constraint::Length(width); 7],
+        None => MONTH_COLUMN_WIDTHS,
+    };
+    show_week_numbers
+        .then_some(Constraint::Length(WEEK_NUMBER_WIDTH))
+        .into_iter()
+        .chain(days)
+        .collect()
+}
+
+/// ISO 8601 week number of a month grid row. Rows starting on Sunday are
+/// numbered by their Monday, so a row spanning New Year gets week 1 or 52/53
+/// just as the ISO calendar does.
+fn iso_week_of_row(year: i32, month: u32, week: &[Option<u32>]) -> Option<u32> {
+    let (column, day) = week
+        .iter()
+        .enumerate()
+        .find_map(|(column, day)| day.map(|day| (column, day)))?;
+    let row_start =
+        NaiveDate::from_ymd_opt(year, month, day)? - chrono::Duration::days(column as i64);
+    let days_to_monday = (7 - row_start.weekday().num_days_from_monday()) % 7;
+    let monday = row_start + chrono::Duration::days(days_to_monday.into());
+    Some(monday.iso_week().week())
+}
+
+/// Rows taken by the main month grid with `weeks` rows, borders included.
+fn month_grid_height(weeks: usize) -> u16 {
+    weeks as u16 * MONTH_ROW_HEIGHT + MONTH_HEADER_HEIGHT + 2
+}
+
+/// Row of the displayed month's grid holding the selected day, or the first
+/// row when another month is selected.
+fn selected_week(calendar: &Calendar) -> usize {
+    let selected = calendar.selected_date;
+    if (selected.year(), selected.month())
+        != (calendar.current_date.year(), calendar.current_date.month())
+    {
+        return 0;
+    }
+    calendar
+        .get_month_grid()
+        .iter()
+        .position(|week| week.contains(&Some(selected.day())))
+        .unwrap_or(0)
+}
+
+/// Maps a terminal position inside the month grid to its (week, weekday) cell,
+/// laying out columns the same way the grid's `Table` does.
+fn month_cell_at(
+    area: Rect,
+    column: u16,
+    row: u16,
+    show_week_numbers: bool,
+    rows: MonthRows,
+) -> Option<(usize, usize)> {
+    let rows_top = area.y + rows.header_height;
+    if row < rows_top || row >= area.bottom() {
+        return None;
+    }
+    let week = rows.first_week + ((row - rows_top) / rows.row_height) as usize;
+
+    let columns = Layout::horizontal(month_grid_widths(None, show_week_numbers))
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(area);
+    let index = columns
+        .iter()
+        .position(|cell| column >= cell.x && column < cell.right())?;
+    // The week number column is not a day
+    let weekday = index.checked_sub(usize::from(show_week_numbers))?;
+    Some((week, weekday))
+}
+
+/// Maps a terminal position inside the week or day view to its (column, hour)
+/// cell, laying out columns the same way the view's `Table` does.
+fn timeline_cell_at(
+    area: Rect,
+    column: u16,
+    row: u16,
+    widths: &[Constraint],
+    scroll: usize,
+) -> Option<(usize, u32)> {
+    let rows_top = area.y + TIMELINE_HEADER_HEIGHT;
+    if row < rows_top || row >= area.bottom() {
+        return None;
+    }
+    let hour = scroll + ((row - rows_top) / TIMELINE_ROW_HEIGHT) as usize;
+    if hour >= (scroll + TIMELINE_VISIBLE_HOURS).min(24) {
+        return None;
+    }
+
+    let columns = Layout::horizontal(widths.iter().copied())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(area);
+    let index = columns
+        .iter()
+        .position(|cell| column >= cell.x && column < cell.right())?;
+    Some((index, hour as u32))
+}
+
+/// Number of events covering each day of the displayed month, keyed by day of month.
+fn day_event_counts(app: &App) -> HashMap<u32, usize> {
+    let (month_start, month_end) = app.calendar.month_bounds();
+    let (month_start, month_end) = (month_start.date_naive(), month_end.date_naive());
+
+    month_start
+        .iter_days()
+        .take_while(|day| *day < month_end)
+        .map(|day| (day.day(), app.event_manager.count_events_for_day(day)))
+        .filter(|(_, count)| *count > 0)
+        .collect()
+}
+
+/// Booked minutes on each day of the displayed month, keyed by day of month.
+fn day_busy_minutes(app: &App) -> HashMap<u32, u32> {
+    let (month_start, month_end) = app.calendar.month_bounds();
+    month_start
+        .date_naive()
+        .iter_days()
+        .take_while(|day| *day < month_end.date_naive())
+        .map(|day| (day.day(), app.event_manager.busy_minutes_for_day(day)))
+        .filter(|(_, minutes)| *minutes > 0)
+        .collect()
+}
+
+/// Heatmap shading for a day with `minutes` booked, from pale green for under
+/// an hour to dark green for a full working day or more.
+fn heatmap_style(minutes: u32) -> Option<Style> {
+    const RAMP: [u8; 6] = [194, 157, 114, 71, 28, 22];
+    if minutes == 0 {
+        return None;
+    }
+    let level = ((minutes / 60) as usize).min(RAMP.len() - 1);
+    let fg = if level < 3 {
+        Color::Black
+    } else {
+        Color::White
+    };
+    Some(Style::default().bg(Color::Indexed(RAMP[level])).fg(fg))
+}
+
+/// Compact superscript event count for a month cell, capped at "⁹⁺".
+fn event_count_badge(count: usize) -> String {
+    const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+    match count {
+        0 => " ".to_string(),
+        1..=9 => SUPERSCRIPT_DIGITS[count].to_string(),
+        _ => "⁹⁺".to_string(),
+    }
+}
+
+/// The saved selected day to reopen on, unless it is before `today`.
+fn date_to_restore(saved: Option<NaiveDate>, today: NaiveDate) -> Option<NaiveDate> {
+    saved.filter(|date| *date >= today)
+}
+
+/// Short reminder of the current view and focused panel, e.g. `[W] Timeline`.
+fn view_indicator(view_mode: &ViewMode, focused_panel: &FocusedPanel) -> String {
+    let letter = match view_mode {
+        ViewMode::Month => 'M',
+        ViewMode::Week => 'W',
+        ViewMode::Day => 'D',
+        ViewMode::Year => 'Y',
+        ViewMode::Agenda => 'A',
+    };
+    let focus = match focused_panel {
+        FocusedPanel::Calendar => "Calendar",
+        FocusedPanel::WeekView => "Timeline",
+        FocusedPanel::Events => "Events",
+    };
+    format!("[{}] {}", letter, focus)
+}
+
+fn create_clock(now: DateTime<Local>, time_format: TimeFormat) -> String {
+    now.format(time_format.pattern_with_seconds()).to_string()
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let area = f.area();
+
+    let screen_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+
+    let main_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+        .split(screen_chunks[0]);
+
+    let content_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(if app.view_mode == ViewMode::Month && app.fit_month_grid {
+            vec![
+                Constraint::Length(3),
+                Constraint::Length(month_grid_height(app.calendar.used_weeks())),
+                Constraint::Min(10),
+            ]
+        } else if app.view_mode == ViewMode::Month {
+            vec![
                 Constraint::Length(3),
                 Constraint::Min(20),
                 Constraint::Length(10),
             ]
-        } else {
-            vec![Constraint::Length(3), Constraint::Min(20)]
-        })
-        .split(main_chunks[1]);
+        } else {
+            vec![Constraint::Length(3), Constraint::Min(20)]
+        })
+        .split(main_chunks[1]);
+
+    let header_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .split(content_chunks[0]);
+
+    let nav_text = format!("← {}   Today   → {} ", "Previous", "Next");
+    let nav_header = Paragraph::new(nav_text)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(view_indicator(&app.view_mode, &app.focused_panel)),
+        );
+
+    let now = Local::now();
+    let clock_text = create_clock(now, app.time_format);
+    let clock = Paragraph::new(clock_text)
+        .style(Style::default().add_modifier(Modifier::BOLD))
+        .alignment(Alignment::Center)
+        .block(Block::default().borders(Borders::ALL));
+
+    f.render_widget(nav_header, header_layout[0]);
+    f.render_widget(clock, header_layout[1]);
+
+    // Count events per day once per frame rather than scanning events for every cell
+    let event_counts = day_event_counts(app);
+    let busy_minutes = app.heatmap.then(|| day_busy_minutes(app));
+    let calendar_area = content_chunks[1];
+    let grid_area = Block::default().borders(Borders::ALL).inner(calendar_area);
+    // Short terminals get shorter rows, and if even that fails, scroll to the selected week
+    app.month_rows = MonthRows::fit(
+        grid_area.height,
+        app.calendar.used_weeks(),
+        selected_week(&app.calendar),
+    );
+    let calendar_table = render_month_grid(
+        &app.calendar,
+        &app.theme,
+        &app.weekday_labels,
+        &app.month_labels,
+        &MonthGridOptions {
+            year: app.calendar.current_date.year(),
+            month: app.calendar.current_date.month(),
+            cell_width: None,
+            show_selection: true,
+            title: None,
+            event_counts: Some(&event_counts),
+            busy_minutes: busy_minutes.as_ref(),
+            show_week_numbers: app.show_week_numbers,
+            rows: app.month_rows,
+        },
+    );
+    app.month_grid_area = (app.view_mode == ViewMode::Month).then_some(grid_area);
+    app.timeline_area =
+        matches!(app.view_mode, ViewMode::Week | ViewMode::Day).then_some(grid_area);
+
+    match app.view_mode {
+        ViewMode::Month => f.render_widget(calendar_table, calendar_area),
+        ViewMode::Week => {
+            let mut week_view = create_week_view(
+                &app.calendar,
+                &app.week_events(),
+                &app.theme,
+                &app.weekday_labels,
+                &Timeline {
+                    scroll: app.week_scroll,
+                    focused_hour: app.focused_hour,
+                    now,
+                    time_format: app.time_format,
+                    work_hours: app.shade_off_hours.then(|| app.work_hours.clone()),
+                },
+            );
+            if app.focused_panel == FocusedPanel::WeekView {
+                week_view = week_view.block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Week View")
+                        .border_style(app.theme.accent(app.theme.focus_border, Modifier::BOLD)),
+                );
+            }
+            f.render_widget(week_view, calendar_area);
+        }
+        ViewMode::Day => {
+            let mut day_view = create_day_view(
+                &app.calendar,
+                &app.day_events(),
+                &app.theme,
+                &app.weekday_labels,
+                &app.month_labels,
+                &Timeline {
+                    scroll: app.day_scroll,
+                    focused_hour: app.focused_hour,
+                    now,
+                    time_format: app.time_format,
+                    work_hours: app.shade_off_hours.then(|| app.work_hours.clone()),
+                },
+            );
+            if app.focused_panel == FocusedPanel::WeekView {
+                day_view = day_view.block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Day View")
+                        .border_style(app.theme.accent(app.theme.focus_border, Modifier::BOLD)),
+                );
+            }
+            f.render_widget(day_view, calendar_area);
+        }
+        ViewMode::Year => draw_year_view(f, app, calendar_area),
+        ViewMode::Agenda => {
+            let agenda = create_agenda_view(
+                &app.agenda_events(),
+                &app.theme,
+                &app.weekday_labels,
+                &app.month_labels,
+                app.agenda_range,
+                app.agenda_scroll,
+                app.time_format,
+            );
+            f.render_widget(agenda, calendar_area);
+        }
+    }
+
+    let sidebar_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(12),
+            Constraint::Length(UPCOMING_EVENTS as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .split(main_chunks[0]);
+
+    let current_date = app.calendar.current_date;
+    let mini_calendar = render_month_grid(
+        &app.calendar,
+        &app.theme,
+        &app.weekday_labels,
+        &app.month_labels,
+        &MonthGridOptions {
+            show_week_numbers: app.show_week_numbers,
+            ..MonthGridOptions::mini(current_date.year(), current_date.month())
+        },
+    );
+    f.render_widget(mini_calendar, sidebar_chunks[0]);
+
+    f.render_widget(
+        create_upcoming_panel(
+            &app.upcoming_events(Local::now()),
+            &app.theme,
+            &app.weekday_labels,
+            app.time_format,
+        ),
+        sidebar_chunks[1],
+    );
+
+    let legend = category_legend(&app.event_manager.categories(), &app.theme);
+    if !legend.is_empty() {
+        let legend_area = Rect {
+            height: (legend.len() as u16 + 2).min(sidebar_chunks[2].height),
+            ..sidebar_chunks[2]
+        };
+        f.render_widget(
+            Paragraph::new(legend)
+                .block(Block::default().borders(Borders::ALL).title("Categories")),
+            legend_area,
+        );
+    }
+
+    if app.view_mode == ViewMode::Month {
+        let events = app.day_events();
+
+        let highlighted = app
+            .highlighted_event()
+            .filter(|_| app.focused_panel == FocusedPanel::Events)
+            .map(|event| event.id);
+        // Lines are wrapped here rather than by the paragraph so that
+        // continuation lines can be indented and each event's height is known
+        let width = content_chunks[2].width.saturating_sub(2) as usize;
+        let event_lines: Vec<Vec<Line>> = events
+            .iter()
+            .map(|e| {
+                let title = format!(
+                    "{} {} ({} - {})",
+                    priority_marker(e.priority),
+                    e.title,
+                    format_time(&e.start_time, app.time_format),
+                    format_time(&e.end_time, app.time_format),
+                );
+                let style = if highlighted == Some(e.id) {
+                    event_style(e, &app.theme).add_modifier(Modifier::REVERSED)
+                } else {
+                    event_style(e, &app.theme)
+                };
+                let title = wrap_indented(&title, width, EVENT_WRAP_INDENT)
+                    .into_iter()
+                    .map(|line| Line::from(line).style(style));
+                let detail = event_detail(e)
+                    .split('\n')
+                    .flat_map(|line| wrap_indented(line, width, EVENT_WRAP_INDENT))
+                    .map(Line::from)
+                    .collect::<Vec<_>>();
+                title.chain(detail).collect()
+            })
+            .collect();
+
+        // The selected day may have changed since the panel was scrolled
+        let first_shown = app.events_scroll.min(events.len().saturating_sub(1));
+        let scroll: usize = event_lines[..first_shown].iter().map(Vec::len).sum();
+        let events_text: Vec<Line> = if events.is_empty() {
+            vec![Line::from("No events scheduled")]
+        } else {
+            event_lines.into_iter().flatten().collect()
+        };
+        let events_widget = Paragraph::new(events_text)
+            .scroll((scroll as u16, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "Events for {} · {}",
+                        app.month_labels.long_date(app.calendar.selected_naive()),
+                        filter_label(app)
+                    ))
+                    .title_bottom(format!(
+                        "Total: {} booked",
+                        format_duration(chrono::Duration::minutes(
+                            app.event_manager
+                                .busy_minutes_for_day(app.calendar.selected_naive())
+                                .into()
+                        ))
+                    ))
+                    .border_style(if app.focused_panel == FocusedPanel::Events {
+                        app.theme.accent(app.theme.focus_border, Modifier::BOLD)
+                    } else {
+                        Style::default()
+                    }),
+            )
+            .style(Style::default())
+            .alignment(Alignment::Left);
+
+        f.render_widget(events_widget, content_chunks[2]);
+    }
+
+    let status_text = match &app.status {
+        Some((message, _)) => message.clone(),
+        None if app.read_only => format!(
+            "{} events · {} · Press ? for help",
+            app.event_manager.len(),
+            READ_ONLY_MESSAGE
+        ),
+        None => format!("{} events · Press ? for help", app.event_manager.len()),
+    };
+    f.render_widget(
+        Paragraph::new(status_text).style(app.theme.fg(app.theme.muted)),
+        screen_chunks[1],
+    );
+
+    draw_event_popup(f, app, area);
+    draw_go_to_date_popup(f, app, area);
+    draw_agenda_range_popup(f, app, area);
+    draw_search_popup(f, app, area);
+    draw_quick_add_popup(f, app, area);
+    draw_import_popup(f, app, area);
+    draw_command_popup(f, app, area);
+    draw_confirm_quit_popup(f, app, area);
+    draw_reminder_popup(f, app, area);
+    draw_help_popup(f, app, area);
+}
+
+/// Column header for `day` in the week and day views, marking the selected
+/// day the same way the month grids do.
+fn day_header(
+    day: NaiveDate,
+    selected: NaiveDate,
+    theme: &Theme,
+    labels: &WeekdayLabels,
+) -> Cell<'static> {
+    let style = if day == selected {
+        theme.fg(theme.selected).add_modifier(Modifier::REVERSED)
+    } else if day == Local::now().date_naive() {
+        theme.accent(theme.today, Modifier::BOLD)
+    } else {
+        theme.fg(theme.muted)
+    };
+    Cell::from(format!("{} {}", labels.short(day.weekday()), day.day())).style(style)
+}
+
+/// Where the timeline of the week and day views is scrolled to, and what it marks.
+#[derive(Default)]
+struct Timeline {
+    /// First hour shown.
+    scroll: usize,
+    focused_hour: Option<u32>,
+    now: DateTime<Local>,
+    time_format: TimeFormat,
+    /// Hours of a weekday that are not shaded; `None` shades nothing.
+    work_hours: Option<std::ops::Range<u32>>,
+}
+
+impl Timeline {
+    /// Whether `hour` is outside the working hours, counting every hour of a
+    /// weekend, or any day when `day` is `None`, as such.
+    fn is_off_hours(&self, day: Option<NaiveDate>, hour: usize) -> bool {
+        self.work_hours.as_ref().is_some_and(|hours| {
+            !hours.contains(&(hour as u32))
+                || day.is_some_and(|day| matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+        })
+    }
+
+    /// Style of the `hour` row of `day`, shaded when it is off hours.
+    fn hour_style(&self, day: Option<NaiveDate>, hour: usize, theme: &Theme) -> Style {
+        if self.is_off_hours(day, hour) {
+            theme.bg(theme.off_hours)
+        } else {
+            Style::default()
+        }
+    }
+}
+
+/// Label for an hour row of the timeline, highlighted when it is the focused hour.
+fn hour_cell(hour: usize, timeline: &Timeline, theme: &Theme) -> Cell<'static> {
+    let label = chrono::NaiveTime::from_hms_opt(hour as u32, 0, 0)
+        .map(|time| time.format(timeline.time_format.hour_pattern()).to_string())
+        .unwrap_or_default();
+    let cell = Cell::from(label).style(timeline.hour_style(None, hour, theme));
+    let focused_hour = timeline.focused_hour;
+    if focused_hour == Some(hour as u32) {
+        cell.style(Style::default().add_modifier(Modifier::REVERSED))
+    } else {
+        cell
+    }
+}
+
+/// Line of an hour row at which `now` falls, if it is during `hour` on `day`.
+fn now_line_index(now: DateTime<Local>, day: NaiveDate, hour: usize) -> Option<usize> {
+    (now.date_naive() == day && now.hour() as usize == hour)
+        .then(|| now.minute() as usize * TIMELINE_ROW_HEIGHT as usize / 60)
+}
+
+fn now_line(timeline: &Timeline, theme: &Theme) -> Line<'static> {
+    Line::from(format!(
+        "━━ {}",
+        format_time(&timeline.now, timeline.time_format)
+    ))
+    .style(theme.accent(theme.now_line, Modifier::BOLD))
+}
+
+/// The selected week as an hourly timeline, with `events` holding the events
+/// of each of its days in turn.
+fn create_week_view<'a>(
+    calendar: &'a Calendar,
+    events: &[Vec<Cow<'_, cal_events::Event>>],
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    timeline: &Timeline,
+) -> Table<'a> {
+    let selected = calendar.selected_naive();
+    let day_headers = calendar
+        .selected_week()
+        .map(|day| day_header(day, selected, theme, labels));
+    let header = Row::new(
+        std::iter::once(Cell::from("Time").style(theme.fg(theme.muted))).chain(day_headers),
+    )
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(TIMELINE_HEADER_HEIGHT);
+
+    let week: Vec<_> = calendar
+        .selected_week()
+        .into_iter()
+        .enumerate()
+        .map(|(index, day)| {
+            let events = events.get(index).map(Vec::as_slice).unwrap_or_default();
+            let conflicts = cal_events::conflicts(events.iter().map(|e| e.as_ref()));
+            (day, events, conflicts)
+        })
+        .collect();
+    let (scroll, now) = (timeline.scroll, timeline.now);
+    let rows = (scroll..scroll + TIMELINE_VISIBLE_HOURS)
+        .map(|hour| {
+            let day_cells = week.iter().map(|(day, events, conflicts)| {
+                let midnight = local_midnight(*day);
+                let mut lines = events
+                    .iter()
+                    .filter(|e| event_hours(e, midnight).contains(&(hour as u32)))
+                    .map(|e| {
+                        let starts_here =
+                            e.start_time.date_naive() == *day && e.start_time.hour() == hour as u32;
+                        let line = if starts_here {
+                            e.title.clone()
+                        } else {
+                            format!("│ {}", e.title)
+                        };
+                        Line::from(line).style(timeline_event_style(e, conflicts, theme))
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(index) = now_line_index(now, *day, hour) {
+                    lines.insert(index.min(lines.len()), now_line(timeline, theme));
+                }
+                Cell::from(lines).style(timeline.hour_style(Some(*day), hour, theme))
+            });
+            let cells = std::iter::once(hour_cell(hour, timeline, theme)).chain(day_cells);
+            Row::new(cells).height(TIMELINE_ROW_HEIGHT)
+        })
+        .collect::<Vec<_>>();
+
+    Table::new(rows, WEEK_VIEW_WIDTHS)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Week View"))
+}
+
+fn create_day_view<'a>(
+    calendar: &'a Calendar,
+    events: &[Cow<'_, cal_events::Event>],
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    months: &MonthLabels,
+    timeline: &Timeline,
+) -> Table<'a> {
+    let selected = calendar.selected_naive();
+    let header = Row::new([
+        Cell::from("Time").style(theme.fg(theme.muted)),
+        day_header(selected, selected, theme, labels),
+    ])
+    .style(Style::default().add_modifier(Modifier::BOLD))
+    .height(TIMELINE_HEADER_HEIGHT);
+
+    let conflicts = cal_events::conflicts(events.iter().map(|e| e.as_ref()));
+    let (scroll, now) = (timeline.scroll, timeline.now);
+    let rows = (scroll..scroll + TIMELINE_VISIBLE_HOURS)
+        .map(|hour| {
+            let mut lines = events
+                .iter()
+                .filter(|e| event_hours(e, calendar.selected_date).contains(&(hour as u32)))
+                .map(|e| {
+                    let line = if e.start_time.hour() == hour as u32 {
+                        let attending = match e.attendees.len() {
+                            0 => String::new(),
+                            count => format!(" · {} attending", count),
+                        };
+                        format!(
+                            "{} ({} - {}){}",
+                            e.title,
+                            format_time(&e.start_time, timeline.time_format),
+                            format_time(&e.end_time, timeline.time_format),
+                            attending
+                        )
+                    } else {
+                        format!("│ {}", e.title)
+                    };
+                    Line::from(line).style(timeline_event_style(e, &conflicts, theme))
+                })
+                .collect::<Vec<_>>();
+            if let Some(index) = now_line_index(now, calendar.selected_naive(), hour) {
+                lines.insert(index.min(lines.len()), now_line(timeline, theme));
+            }
+            let day_style = timeline.hour_style(Some(calendar.selected_naive()), hour, theme);
+            Row::new(vec![
+                hour_cell(hour, timeline, theme),
+                Cell::from(lines).style(day_style),
+            ])
+            .height(TIMELINE_ROW_HEIGHT)
+        })
+        .collect::<Vec<_>>();
+
+    Table::new(rows, DAY_VIEW_WIDTHS).header(header).block(
+        Block::default().borders(Borders::ALL).title(format!(
+            "Day View - {}",
+            months.long_date(calendar.selected_naive())
+        )),
+    )
+}
+
+/// How an event is drawn in a timeline, standing out if it is in `conflicts`.
+fn timeline_event_style(
+    event: &cal_events::Event,
+    conflicts: &HashSet<Uuid>,
+    theme: &Theme,
+) -> Style {
+    if conflicts.contains(&event.id) {
+        event_style(event, theme).patch(theme.accent(theme.conflict, Modifier::REVERSED))
+    } else {
+        event_style(event, theme)
+    }
+}
+
+/// The sidebar's short list of what is next, one line per event.
+fn create_upcoming_panel(
+    events: &[Cow<'_, cal_events::Event>],
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    time_format: TimeFormat,
+) -> Paragraph<'static> {
+    let lines = if events.is_empty() {
+        vec![Line::from("Nothing coming up").italic()]
+    } else {
+        events
+            .iter()
+            .map(|event| {
+                let start = event.start_time;
+                Line::from(format!(
+                    "{} {} {} {}",
+                    labels.short(start.weekday()),
+                    start.day(),
+                    format_time(&start, time_format),
+                    event.title
+                ))
+                .style(event_style(event, theme))
+            })
+            .collect()
+    };
+    Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Upcoming"))
+}
+
+/// Upcoming events as a flat list, grouped under a header for each day that
+/// has any. A `range` entered by the user is shown in the title.
+fn create_agenda_view(
+    events: &[Cow<'_, cal_events::Event>],
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    months: &MonthLabels,
+    range: Option<(NaiveDate, NaiveDate)>,
+    scroll: usize,
+    time_format: TimeFormat,
+) -> Paragraph<'static> {
+    let lines = match (events.is_empty(), range) {
+        (true, None) => vec![Line::from("No upcoming events")],
+        (true, Some(_)) => vec![Line::from("No events in this range")],
+        (false, _) => agenda_lines(events, theme, labels, months, time_format),
+    };
+    let title = match range {
+        Some((first, last)) => format!(
+            "Agenda - {} to {}",
+            months.long_date(first),
+            months.long_date(last)
+        ),
+        None => "Agenda".to_string(),
+    };
+    let max_scroll = lines.len().saturating_sub(1);
+    Paragraph::new(lines)
+        .scroll((scroll.min(max_scroll) as u16, 0))
+        .block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn default_agenda_range(today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    let last = today
+        .checked_add_signed(chrono::Duration::days(AGENDA_DAYS))
+        .unwrap_or(NaiveDate::MAX);
+    (today, last)
+}
+
+/// Reads `<from> to <to>`, each in any form `goto` accepts. An empty input
+/// goes back to the default range.
+fn parse_agenda_range(input: &str, today: NaiveDate) -> Result<Option<(NaiveDate, NaiveDate)>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let (first, last) = input
+        .split_once(" to ")
+        .ok_or_else(|| anyhow!("Enter two dates, e.g. 2025-04-01 to 2025-06-30"))?;
+    let parse = |date: &str| {
+        let date = date.trim();
+        parse_relative_date(date, today).ok_or_else(|| {
+            anyhow!(
+                "Invalid date '{}', try YYYY-MM-DD, tomorrow, next fri or +3d",
+                date
+            )
+        })
+    };
+    let (first, last) = (parse(first)?, parse(last)?);
+    if last < first {
+        return Err(anyhow!("The end date must not be before the start date"));
+    }
+    Ok(Some((first, last)))
+}
+
+fn agenda_lines(
+    events: &[Cow<'_, cal_events::Event>],
+    theme: &Theme,
+    labels: &WeekdayLabels,
+    months: &MonthLabels,
+    time_format: TimeFormat,
+) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut current_day = None;
+    for event in events {
+        let day = event.start_time.date_naive();
+        if current_day != Some(day) {
+            if current_day.is_some() {
+                lines.push(Line::default());
+            }
+            let header = format!(
+                "{}, {}",
+                labels.full(day.weekday()),
+                months.day_and_month(day)
+            );
+            lines.push(Line::from(header).bold());
+            current_day = Some(day);
+        }
+        let end = if event.end_time.date_naive() == day {
+            format_time(&event.end_time, time_format)
+        } else {
+            format!(
+                "{} {}",
+                event.end_time.format("%b %d"),
+                format_time(&event.end_time, time_format)
+            )
+        };
+        let location = event
+            .location
+            .as_deref()
+            .map(|location| format!(" @ {}", truncate(location, AGENDA_LOCATION_LEN)))
+            .unwrap_or_default();
+        lines.push(
+            Line::from(format!(
+                "{} {} - {}  {}{}",
+                priority_marker(event.priority),
+                format_time(&event.start_time, time_format),
+                end,
+                event.title,
+                location
+            ))
+            .style(event_style(event, theme)),
+        );
+    }
+    lines
+}
+
+/// Moves a timeline scroll position by `hours`, keeping the last hour on screen.
+fn clamp_timeline_scroll(scroll: usize, hours: isize) -> usize {
+    scroll.saturating_add_signed(hours).min(MAX_TIMELINE_SCROLL)
+}
+
+/// Hours of `date` occupied by an event, so multi-hour events fill every row they span.
+fn event_hours(event: &cal_events::Event, date: DateTime<Local>) -> std::ops::Range<u32> {
+    let start = if event.start_time.date_naive() < date.date_naive() {
+        0
+    } else {
+        event.start_time.hour()
+    };
+    let end = if event.end_time.date_naive() > date.date_naive() {
+        24
+    } else if event.end_time.minute() > 0 {
+        event.end_time.hour() + 1
+    } else {
+        event.end_time.hour()
+    };
+    start..end.max(start + 1)
+}
+
+fn draw_event_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::CreateEvent {
+        title,
+        description,
+        start_time,
+        end_time,
+        reminder,
+        priority,
+        attendees,
+        location,
+        tags,
+        status,
+        recurrence,
+        weekdays,
+        focused_field,
+        error,
+        ..
+    } = &app.popup
+    {
+        // Create a clear overlay
+        f.render_widget(Clear, area);
+
+        // Create a smaller popup area
+        let popup_area = centered_rect(60, 38 + DESCRIPTION_VISIBLE_LINES - 1, area);
+
+        // Render popup background with default theme
+        let popup_block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Create New Event in '{}'", app.target_calendar))
+            .title_alignment(Alignment::Center);
+
+        f.render_widget(popup_block, popup_area);
+
+        // Create layout for input fields
+        let inner = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(3),                             // Title
+                Constraint::Length(DESCRIPTION_VISIBLE_LINES + 2), // Description
+                Constraint::Length(3),                             // Start time
+                Constraint::Length(3),                             // End time
+                Constraint::Length(3),                             // Reminder
+                Constraint::Length(3),                             // Priority
+                Constraint::Length(3),                             // Attendees
+                Constraint::Length(3),                             // Location
+                Constraint::Length(3),                             // Tags
+                Constraint::Length(3),                             // Status
+                Constraint::Length(3),                             // Repeats
+                Constraint::Length(2),                             // Controls
+            ])
+            .split(popup_area);
+
+        // Render input fields
+        let priority = format!("◀ {} ▶", priority);
+        let status = format!("◀ {} ▶", status);
+        let recurrence = recurrence_input_label(*recurrence, weekdays);
+        let fields = [
+            (title.as_str(), "Title"),
+            (description.as_str(), "Description"),
+            (start_time.as_str(), "Start Time (YYYY-MM-DD HH:MM)"),
+            (
+                end_time.as_str(),
+                "End Time (YYYY-MM-DD HH:MM) or Duration (e.g. 1h30m)",
+            ),
+            (
+                reminder.as_str(),
+                "Remind Before (e.g. 15m, 1h; empty for none)",
+            ),
+            (priority.as_str(), "Priority (←/→ to change)"),
+            (attendees.as_str(), "Attendees (comma-separated)"),
+            (location.as_str(), "Location"),
+            (tags.as_str(), "Tags (space or comma-separated)"),
+            (status.as_str(), "Status (←/→ to change)"),
+            (
+                recurrence.as_str(),
+                "Repeats (←/→ to change, 1-7 toggle weekly days)",
+            ),
+        ];
+
+        for (i, (content, title)) in fields.iter().enumerate() {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(*title)
+                .border_style(if *focused_field == i {
+                    Style::default().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::default()
+                });
+
+            // Keep the end of a long description, where typing happens, in view
+            let hidden_lines = content
+                .lines()
+                .count()
+                .saturating_sub(usize::from(DESCRIPTION_VISIBLE_LINES));
+            f.render_widget(
+                Paragraph::new(*content)
+                    .scroll((hidden_lines as u16, 0))
+                    .block(block),
+                inner[i],
+            );
+        }
+
+        // Render controls, or the validation error if there is one
+        let controls = match error {
+            Some(message) => Paragraph::new(message.as_str())
+                .style(app.theme.accent(app.theme.error, Modifier::BOLD)),
+            None if *focused_field == DESCRIPTION_FIELD => {
+                Paragraph::new("Alt+Enter: New Line | Enter: Save | Esc: Cancel")
+            }
+            None => Paragraph::new("Tab: Next Field | Enter: Save | Esc: Cancel"),
+        };
+        f.render_widget(controls.alignment(Alignment::Center), inner[11]);
+    }
+}
+
+fn draw_go_to_date_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::GoToDate { input, error } = &app.popup {
+        draw_input_popup(
+            f,
+            area,
+            &app.theme,
+            ("Go To Date", "Date (YYYY-MM-DD, tomorrow, next fri, +3d)"),
+            input,
+            error.as_deref(),
+            "Enter: Go | Esc: Cancel",
+        );
+    }
+}
+
+fn draw_agenda_range_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::AgendaRange { input, error } = &app.popup {
+        draw_input_popup(
+            f,
+            area,
+            &app.theme,
+            (
+                "Agenda Range",
+                "From and to, e.g. today to +7d; empty for default",
+            ),
+            input,
+            error.as_deref(),
+            "Enter: Apply | Esc: Cancel",
+        );
+    }
+}
+
+fn draw_quick_add_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::QuickAdd { input, error, .. } = &app.popup {
+        draw_input_popup(
+            f,
+            area,
+            &app.theme,
+            ("Quick Add", "Title and time, e.g. Dentist 15:00 1h"),
+            input,
+            error.as_deref(),
+            "Enter: Add | Esc: Cancel",
+        );
+    }
+}
+
+fn draw_command_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::Command { input } = &app.popup {
+        draw_input_popup(
+            f,
+            area,
+            &app.theme,
+            (
+                "Command",
+                "delete <title>, goto <date>, export ics|csv, week, ...",
+            ),
+            input,
+            None,
+            "Enter: Run | Esc: Cancel",
+        );
+    }
+}
+
+fn draw_confirm_quit_popup(f: &mut Frame, app: &App, area: Rect) {
+    if app.popup != PopupState::ConfirmQuit {
+        return;
+    }
+    let popup_area = centered_rect(40, 3, area);
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Paragraph::new("Save before quitting? (y/n/esc)")
+            .alignment(Alignment::Center)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Unsaved Changes")
+                    .border_style(app.theme.accent(app.theme.focus_border, Modifier::BOLD)),
+            ),
+        popup_area,
+    );
+}
+
+fn draw_reminder_popup(f: &mut Frame, app: &App, area: Rect) {
+    let PopupState::Reminder { title, body, .. } = &app.popup else {
+        return;
+    };
+    let snooze = SNOOZE_MINUTES
+        .iter()
+        .enumerate()
+        .map(|(index, minutes)| format!("{}: {}m", index + 1, minutes))
+        .collect::<Vec<_>>()
+        .join("  ");
+    let lines = vec![
+        Line::from(title.as_str()).style(Style::default().add_modifier(Modifier::BOLD)),
+        Line::from(body.as_str()),
+        Line::from(""),
+        Line::from(format!("Snooze {snooze} | Enter: Dismiss"))
+            .style(app.theme.fg(app.theme.muted)),
+    ];
+    let popup_area = centered_rect(50, 6, area);
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Reminder")
+                .border_style(app.theme.accent(app.theme.focus_border, Modifier::BOLD)),
+        ),
+        popup_area,
+    );
+}
+
+fn draw_import_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::ImportFile { path, error } = &app.popup {
+        draw_input_popup(
+            f,
+            area,
+            &app.theme,
+            ("Import Events", "Path to .ics file"),
+            path,
+            error.as_deref(),
+            "Enter: Import | Esc: Cancel",
+        );
+    }
+}
+
+fn draw_help_popup(f: &mut Frame, app: &App, area: Rect) {
+    if app.popup != PopupState::Help {
+        return;
+    }
+
+    let mut groups: Vec<(&str, Vec<(String, &str)>)> = Vec::new();
+    for info in ACTIONS {
+        let binding = (app.keys.binding(info.action).to_string(), info.description);
+        match groups
+            .iter_mut()
+            .find(|(context, _)| *context == info.context)
+        {
+            Some((_, bindings)) => bindings.push(binding),
+            None => groups.push((info.context, vec![binding])),
+        }
+    }
+    groups.push((
+        "Popup",
+        POPUP_KEYBINDINGS
+            .iter()
+            .map(|(key, description)| (key.to_string(), *description))
+            .collect(),
+    ));
+
+    let mut lines = Vec::new();
+    for (context, bindings) in groups {
+        if !lines.is_empty() {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(
+            context,
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        for (key, description) in bindings {
+            lines.push(Line::from(format!("  {:<10} {}", key, description)));
+        }
+    }
+
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let popup_area = centered_rect(60.min(area.width), height, area);
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Keybindings (press any key to close)")
+                .title_alignment(Alignment::Center),
+        ),
+        popup_area,
+    );
+}
+
+/// Draws a single-line text prompt with an error or controls line beneath it.
+fn draw_input_popup(
+    f: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    (title, field_title): (&str, &str),
+    input: &str,
+    error: Option<&str>,
+    controls: &str,
+) {
+    let popup_area = centered_rect(50, 7, area);
+    f.render_widget(Clear, popup_area);
+
+    let popup_block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_alignment(Alignment::Center);
+    f.render_widget(popup_block, popup_area);
+
+    let inner = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Length(2)])
+        .split(popup_area);
+
+    f.render_widget(
+        Paragraph::new(input).block(Block::default().borders(Borders::ALL).title(field_title)),
+        inner[0],
+    );
+
+    let controls = match error {
+        Some(message) => Paragraph::new(message).style(theme.accent(theme.error, Modifier::BOLD)),
+        None => Paragraph::new(controls),
+    };
+    f.render_widget(controls.alignment(Alignment::Center), inner[1]);
+}
+
+fn draw_search_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let PopupState::Search { query, selected } = &app.popup {
+        let popup_area = centered_rect(60, 20, area);
+        f.render_widget(Clear, popup_area);
+
+        let popup_block = Block::default()
+            .borders(Borders::ALL)
+            .title("Search Events")
+            .title_alignment(Alignment::Center);
+        f.render_widget(popup_block, popup_area);
+
+        let inner = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(3), // Query
+                Constraint::Min(0),    // Results
+                Constraint::Length(1), // Controls
+            ])
+            .split(popup_area);
+
+        f.render_widget(
+            Paragraph::new(query.as_str())
+                .block(Block::default().borders(Borders::ALL).title("Query")),
+            inner[0],
+        );
+
+        let results = app.event_manager.search(query);
+        let items: Vec<ListItem> = if results.is_empty() {
+            vec![ListItem::new("No matching events")]
+        } else {
+            results
+                .iter()
+                .enumerate()
+                .map(|(i, e)| {
+                    let style = if i == *selected {
+                        Style::default().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::default()
+                    };
+                    ListItem::new(format!(
+                        "{} {}  {}",
+                        e.start_time.format("%Y-%m-%d"),
+                        format_time(&e.start_time, app.time_format),
+                        e.title
+                    ))
+                    .style(style)
+                })
+                .collect()
+        };
+        f.render_widget(
+            List::new(items).block(Block::default().borders(Borders::ALL).title("Results")),
+            inner[1],
+        );
+
+        f.render_widget(
+            Paragraph::new("↑/↓: Select | Enter: Jump to Day | Esc: Cancel")
+                .alignment(Alignment::Center),
+            inner[2],
+        );
+    }
+}
+
+fn push_datetime_digit(input: &mut String, c: char) {
+    if !c.is_ascii_digit() || input.len() >= DATETIME_INPUT_LEN {
+        return;
+    }
+    // Insert the separators of `YYYY-MM-DD HH:MM` as the digits are typed
+    match input.len() {
+        4 | 7 => input.push('-'),
+        10 => input.push(' '),
+        13 => input.push(':'),
+        _ => {}
+    }
+    input.push(c);
+}
+
+/// Whether the go-to prompt holds the start of a `YYYY-MM-DD` date rather
+/// than a phrase such as `next fri` or `+3d`.
+fn is_absolute_date_input(input: &str) -> bool {
+    input.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Types into the go-to prompt, filling in the dashes of absolute dates.
+fn push_go_to_char(input: &mut String, c: char) {
+    if input.is_empty() && c.is_ascii_digit() || is_absolute_date_input(input) {
+        if input.len() < DATE_INPUT_LEN {
+            push_datetime_digit(input, c);
+        }
+    } else if input.len() < GO_TO_INPUT_LEN {
+        input.push(c);
+    }
+}
+
+fn pop_datetime_char(input: &mut String) {
+    input.pop();
+    if input.ends_with(['-', ' ', ':']) {
+        input.pop();
+    }
+}
+
+fn parse_datetime_input(input: &str) -> Result<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(input, DATETIME_INPUT_FORMAT)
+        .map_err(|_| anyhow!("Invalid date/time '{}', expected YYYY-MM-DD HH:MM", input))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| anyhow!("'{}' does not exist in the local timezone", input))
+}
+
+/// The end field holds either a `YYYY-MM-DD HH:MM` timestamp or a duration
+/// such as `1h30m`; a letter typed before any separator switches to a duration.
+fn push_end_time_char(input: &mut String, c: char) {
+    if is_duration_input(input) || (c.is_ascii_alphabetic() && !input.contains('-')) {
+        if c.is_ascii_alphanumeric() {
+            input.push(c);
+        }
+    } else {
+        push_datetime_digit(input, c);
+    }
+}
+
+fn is_duration_input(input: &str) -> bool {
+    input.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+fn resolve_end_input(start: DateTime<Local>, input: &str) -> Result<DateTime<Local>> {
+    if is_duration_input(input) {
+        start
+            .checked_add_signed(parse_duration(input)?)
+            .ok_or_else(|| anyhow!("Duration '{}' is too long", input.trim()))
+    } else {
+        parse_datetime_input(input)
+    }
+}
+
+/// Status bar summary of an import, with the first failure if any.
+fn import_status(report: &ImportReport) -> String {
+    match report.errors.first() {
+        Some(error) => format!("Import: {report} ({error})"),
+        None => format!("Import: {report}"),
+    }
+}
+
+/// One line per category with a swatch in its theme color, which is left
+/// uncolored for categories the theme does not mention.
+fn category_legend(categories: &[&str], theme: &Theme) -> Vec<Line<'static>> {
+    categories
+        .iter()
+        .map(|category| {
+            let swatch_style = theme
+                .category_color(category)
+                .map_or_else(Style::default, |color| theme.fg(color));
+            Line::from(vec![
+                Span::styled("■ ", swatch_style),
+                Span::raw(category.to_string()),
+            ])
+        })
+        .collect()
+}
+
+/// "all events" or the category and tag being shown, for panel titles.
+fn filter_label(app: &App) -> String {
+    match (&app.category_filter, &app.tag_filter) {
+        (Some(category), Some(tag)) => format!("category '{}' tagged '{}'", category, tag),
+        (Some(category), None) => format!("category '{}'", category),
+        (None, Some(tag)) => format!("tag '{}'", tag),
+        (None, None) => "all events".to_string(),
+    }
+}
+
+/// Second sidebar line for an event: its description, place and who is
+/// attending, one line per line of the description.
+fn event_detail(event: &cal_events::Event) -> String {
+    let description = event.description.as_deref().unwrap_or("-");
+    let mut detail = format!("  {}", description.replace('\n', "\n  "));
+    if let Some(location) = &event.location {
+        detail.push_str(&format!(" @ {}", location));
+    }
+    if !event.attendees.is_empty() {
+        detail.push_str(&format!(" · with {}", event.attendees.join(", ")));
+    }
+    if !event.tags.is_empty() {
+        let tags: Vec<String> = event.tags.iter().map(|tag| format!("#{}", tag)).collect();
+        detail.push_str(&format!(" · {}", tags.join(" ")));
+    }
+    detail
+}
+
+/// Plain-text summary of `event` to paste elsewhere: its title, when it
+/// happens, where and the description.
+fn format_event_for_clipboard(event: &cal_events::Event) -> String {
+    const DATE_TIME: &str = "%A, %B %d, %Y %H:%M";
+    let start = event.start_time.with_timezone(&Local);
+    let end = event.end_time.with_timezone(&Local);
+    let end_format = if end.date_naive() == start.date_naive() {
+        "%H:%M"
+    } else {
+        DATE_TIME
+    };
+    let mut text = format!(
+        "{}\n{} - {}",
+        event.title,
+        start.format(DATE_TIME),
+        end.format(end_format)
+    );
+    if let Some(location) = &event.location {
+        text.push_str(&format!("\nLocation: {}", location));
+    }
+    if let Some(description) = &event.description {
+        text.push_str(&format!("\n\n{}", description));
+    }
+    text
+}
+
+/// `time` moved `days` days at the same local time of day, which may be a
+/// different number of hours away across a daylight saving change.
+fn shift_days(time: DateTime<FixedOffset>, days: i64) -> Option<DateTime<FixedOffset>> {
+    let naive = time.with_timezone(&Local).naive_local() + chrono::Duration::days(days);
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.fixed_offset())
+}
+
+/// Time of day of `time` on the chosen clock, e.g. `14:30` or `02:30 PM`.
+fn format_time<Tz: TimeZone>(time: &DateTime<Tz>, time_format: TimeFormat) -> String
+where
+    Tz::Offset: std::fmt::Display,
+{
+    time.format(time_format.pattern()).to_string()
+}
+
+/// A length of time such as "45m", "2h" or "1h 30m".
+fn format_duration(duration: chrono::Duration) -> String {
+    match (duration.num_hours(), duration.num_minutes() % 60) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+/// Breaks `text` between words into lines at most `width` characters wide,
+/// starting each line after the first with `indent`. Words too long for a
+/// line are split.
+fn wrap_indented(text: &str, width: usize, indent: &str) -> Vec<String> {
+    let indent_len = indent.chars().count();
+    if width <= indent_len {
+        return vec![text.to_string()];
+    }
+
+    // Leading spaces of the first line are kept, e.g. those of a detail line
+    let words = text.trim_start();
+    let mut line = text[..text.len() - words.len()].to_string();
+    let mut line_len = line.chars().count();
+    let mut line_has_word = false;
+    let mut lines = Vec::new();
+    for word in words.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        loop {
+            let gap = usize::from(line_has_word);
+            if line_len + gap + word.len() <= width {
+                if line_has_word {
+                    line.push(' ');
+                }
+                line.extend(&word);
+                line_len += gap + word.len();
+                line_has_word = true;
+                break;
+            }
+            if !line_has_word {
+                let room = width.saturating_sub(line_len).max(1);
+                line.extend(word.drain(..room));
+            }
+            lines.push(std::mem::replace(&mut line, indent.to_string()));
+            line_len = indent_len;
+            line_has_word = false;
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Shortens `text` to at most `max` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(max.saturating_sub(1)).collect();
+        format!("{}…", kept)
+    }
+}
+
+/// Splits a comma-separated attendee list, dropping blank entries.
+fn parse_attendees_input(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(str::trim)
+        .filter(|attendee| !attendee.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Sidebar and agenda bullet, making high-priority events easy to spot.
+fn priority_marker(priority: Priority) -> &'static str {
+    match priority {
+        Priority::High => "!",
+        Priority::Normal | Priority::Low => "•",
+    }
+}
+
+fn priority_style(priority: Priority) -> Style {
+    match priority {
+        Priority::High => Style::default().add_modifier(Modifier::BOLD),
+        Priority::Normal => Style::default(),
+        Priority::Low => Style::default().add_modifier(Modifier::DIM),
+    }
+}
+
+/// Category color and priority styling, crossed out and dimmed when the
+/// event is cancelled.
+fn event_style(event: &cal_events::Event, theme: &Theme) -> Style {
+    let style = match event
+        .category
+        .as_deref()
+        .and_then(|category| theme.category_color(category))
+    {
+        Some(color) => priority_style(event.priority).patch(theme.fg(color)),
+        None => priority_style(event.priority),
+    };
+    match event.status {
+        EventStatus::Cancelled => style.add_modifier(Modifier::CROSSED_OUT | Modifier::DIM),
+        EventStatus::Tentative | EventStatus::Confirmed => style,
+    }
+}
+
+fn cycle_status(status: EventStatus, forward: bool) -> EventStatus {
+    match (status, forward) {
+        (EventStatus::Tentative, true) | (EventStatus::Cancelled, false) => EventStatus::Confirmed,
+        (EventStatus::Confirmed, true) | (EventStatus::Tentative, false) => EventStatus::Cancelled,
+        (EventStatus::Cancelled, true) | (EventStatus::Confirmed, false) => EventStatus::Tentative,
+    }
+}
+
+/// Steps through no repetition, daily, weekly and monthly.
+fn cycle_recurrence(frequency: Option<Frequency>, forward: bool) -> Option<Frequency> {
+    const ORDER: [Option<Frequency>; 4] = [
+        None,
+        Some(Frequency::Daily),
+        Some(Frequency::Weekly),
+        Some(Frequency::Monthly),
+    ];
+    let index = ORDER.iter().position(|f| *f == frequency).unwrap_or(0);
+    let next = if forward {
+        index + 1
+    } else {
+        index + ORDER.len() - 1
+    };
+    ORDER[next % ORDER.len()]
+}
+
+/// Adds or removes the weekday typed as a digit, 1 for Monday to 7 for Sunday.
+fn toggle_weekday(weekdays: &mut Vec<Weekday>, digit: char) {
+    let Some(day) = digit
+        .to_digit(10)
+        .filter(|day| (1..=7).contains(day))
+        .and_then(|day| Weekday::try_from(day as u8 - 1).ok())
+    else {
+        return;
+    };
+    match weekdays.iter().position(|weekday| *weekday == day) {
+        Some(index) => {
+            weekdays.remove(index);
+        }
+        None => {
+            weekdays.push(day);
+            weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+        }
+    }
+}
+
+/// The repeat field's text, listing the chosen days for weekly events.
+fn recurrence_input_label(frequency: Option<Frequency>, weekdays: &[Weekday]) -> String {
+    let name = match frequency {
+        None => "Never",
+        Some(Frequency::Daily) => "Daily",
+        Some(Frequency::Weekly) => "Weekly",
+        Some(Frequency::Monthly) => "Monthly",
+    };
+    if frequency != Some(Frequency::Weekly) {
+        return format!("◀ {} ▶", name);
+    }
+    let days: Vec<String> = [
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+        Weekday::Sun,
+    ]
+    .iter()
+    .map(|day| {
+        if weekdays.contains(day) {
+            format!("[{}]", day)
+        } else {
+            day.to_string()
+        }
+    })
+    .collect();
+    format!("◀ {} ▶ {}", name, days.join(" "))
+}
+
+fn cycle_priority(priority: Priority, forward: bool) -> Priority {
+    match (priority, forward) {
+        (Priority::Low, true) | (Priority::High, false) => Priority::Normal,
+        (Priority::Normal, true) => Priority::High,
+        (Priority::Normal, false) => Priority::Low,
+        (Priority::High, true) => Priority::Low,
+        (Priority::Low, false) => Priority::High,
+    }
+}
+
+/// Validates the focused time or reminder field before focus leaves it.
+fn check_time_field(popup: &mut PopupState) {
+    if let PopupState::CreateEvent {
+        start_time,
+        end_time,
+        reminder,
+        focused_field,
+        error,
+        ..
+    } = popup
+    {
+        *error = match focused_field {
+            2 => parse_datetime_input(start_time).err(),
+            3 if is_duration_input(end_time) => {
+                // Show the absolute end time the duration resolves to
+                match parse_datetime_input(start_time)
+                    .and_then(|start| resolve_end_input(start, end_time))
+                {
+                    Ok(end) => {
+                        *end_time = end.format(DATETIME_INPUT_FORMAT).to_string();
+                        None
+                    }
+                    Err(err) => Some(err),
+                }
+            }
+            3 => parse_datetime_input(end_time).err(),
+            4 if !reminder.trim().is_empty() => parse_duration(reminder).err(),
+            _ => None,
+        }
+        .map(|err| err.to_string());
+    }
+}
+
+/// A `width` x `height` rect centred in `r`, shrunk to fit when `r` is smaller.
+fn centered_rect(width: u16, height: u16, r: Rect) -> Rect {
+    let width = width.min(r.width);
+    let height = height.min(r.height);
+    Rect::new(
+        r.x + (r.width - width) / 2,
+        r.y + (r.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn render_to_string(widget: impl Widget) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(widget, f.area()))
+            .unwrap();
+        buffer_text(terminal.backend().buffer(), |_| true)
+    }
+
+    /// Draws the whole app, as `run` would, into a `width` x `height` buffer.
+    fn render_to_buffer(app: &mut App, width: u16, height: u16) -> Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app)).unwrap();
+        terminal.backend().buffer().clone()
+    }
+
+    /// Text of the cells in `buffer` matching `filter`, row after row.
+    fn buffer_text(buffer: &Buffer, filter: impl Fn(&ratatui::buffer::Cell) -> bool) -> String {
+        buffer
+            .content()
+            .iter()
+            .filter(|cell| filter(cell))
+            .map(|cell| cell.symbol())
+            .collect()
+    }
+
+    fn event_at(calendar: &Calendar, start_hour: u32, end_hour: u32) -> cal_events::Event {
+        let date = calendar.selected_naive();
+        let at = |hour| {
+            Local
+                .from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap())
+                .unwrap()
+        };
+        cal_events::Event::new("Standup".to_string(), None, at(start_hour), at(end_hour)).unwrap()
+    }
+
+    #[test]
+    fn test_datetime_input_editing() {
+        let mut input = String::new();
+        for c in "202503141530".chars() {
+            push_datetime_digit(&mut input, c);
+        }
+        assert_eq!(input, "2025-03-14 15:30");
+
+        // Further digits and non-digits are ignored
+        push_datetime_digit(&mut input, '9');
+        push_datetime_digit(&mut input, 'x');
+        assert_eq!(input, "2025-03-14 15:30");
+
+        pop_datetime_char(&mut input);
+        pop_datetime_char(&mut input);
+        assert_eq!(input, "2025-03-14 15");
+    }
+
+    #[test]
+    fn test_parse_datetime_input() {
+        let parsed = parse_datetime_input("2025-03-14 15:30").unwrap();
+        assert_eq!(
+            parsed.format(DATETIME_INPUT_FORMAT).to_string(),
+            "2025-03-14 15:30"
+        );
+
+        assert!(parse_datetime_input("2025-03-14 15").is_err());
+        assert!(parse_datetime_input("2025-02-30 10:00").is_err());
+        assert!(parse_datetime_input("").is_err());
+    }
+
+    #[test]
+    fn test_day_view_shows_event_when_scrolled_into_view() {
+        let calendar = Calendar::new();
+        let mut event_manager = EventManager::new();
+        event_manager
+            .add_event(event_at(&calendar, 14, 16))
+            .unwrap();
+
+        let hidden = render_to_string(create_day_view(
+            &calendar,
+            &event_manager.list_events_for_day(calendar.selected_date),
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &MonthLabels::default(),
+            &Timeline::default(),
+        ));
+        assert!(!hidden.contains("Standup"));
+
+        let visible = render_to_string(create_day_view(
+            &calendar,
+            &event_manager.list_events_for_day(calendar.selected_date),
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &MonthLabels::default(),
+            &Timeline {
+                scroll: 10,
+                ..Timeline::default()
+            },
+        ));
+        assert!(visible.contains("Standup (14:00 - 16:00)"));
+        assert!(visible.contains("│ Standup"));
+    }
+
+    #[test]
+    fn test_event_hours_cover_multi_hour_events() {
+        let calendar = Calendar::new();
+        let event = event_at(&calendar, 9, 12);
+        assert_eq!(event_hours(&event, calendar.selected_date), 9..12);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(
+            parse_duration("15m").unwrap(),
+            chrono::Duration::minutes(15)
+        );
+        assert_eq!(
+            parse_duration("1h30m").unwrap(),
+            chrono::Duration::minutes(90)
+        );
+        assert_eq!(parse_duration("2H").unwrap(), chrono::Duration::hours(2));
+        assert!(parse_duration("15").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("soon").is_err());
+        assert!(resolve_end_input(Local::now(), "99999999d").is_err());
+    }
+
+    #[test]
+    fn test_end_time_accepts_duration() {
+        let mut input = String::new();
+        for c in "1h15m".chars() {
+            push_end_time_char(&mut input, c);
+        }
+        assert_eq!(input, "1h15m");
+
+        let start = parse_datetime_input("2025-03-14 23:30").unwrap();
+        assert_eq!(
+            resolve_end_input(start, &input)
+                .unwrap()
+                .format(DATETIME_INPUT_FORMAT)
+                .to_string(),
+            "2025-03-15 00:45"
+        );
+        assert!(resolve_end_input(start, "1x").is_err());
+
+        // Digits alone still build up a timestamp
+        let mut input = String::new();
+        for c in "202503141600".chars() {
+            push_end_time_char(&mut input, c);
+        }
+        assert_eq!(input, "2025-03-14 16:00");
+        push_end_time_char(&mut input, 'h');
+        assert_eq!(input, "2025-03-14 16:00");
+    }
+
+    #[test]
+    fn test_click_selects_month_day() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        app.calendar.set_week_start(Weekday::Sun);
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap());
+        app.month_grid_area = Some(Rect::new(1, 1, 70, 20));
+
+        // The weekday header rows are not part of any cell
+        let rows = MonthRows::default();
+        assert_eq!(
+            month_cell_at(Rect::new(1, 1, 70, 20), 1, 2, false, rows),
+            None
+        );
+        assert_eq!(
+            month_cell_at(Rect::new(1, 1, 70, 20), 1, 3, false, rows),
+            Some((0, 0))
+        );
+
+        // January 2025 starts on a Wednesday, so the second row begins with Sunday the 5th
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        app.handle_mouse(click(2, 6));
+        assert_eq!(
+            app.calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()
+        );
+
+        // Clicking a padding cell before the 1st leaves the selection alone
+        app.handle_mouse(click(2, 3));
+        assert_eq!(app.calendar.selected_date.day(), 5);
+    }
+
+    #[test]
+    fn test_mouse_scroll_moves_timeline() {
+        let mut app = App::new();
+        let scroll = |kind| MouseEvent {
+            kind,
+            column: 0,
+            row: 0,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        app.view_mode = ViewMode::Day;
+        (app.day_scroll, app.week_scroll) = (0, 0);
+        app.handle_mouse(scroll(MouseEventKind::ScrollDown));
+        app.handle_mouse(scroll(MouseEventKind::ScrollDown));
+        assert_eq!(app.day_scroll, 2);
+        app.view_mode = ViewMode::Week;
+        app.handle_mouse(scroll(MouseEventKind::ScrollUp));
+        assert_eq!(app.week_scroll, 0);
+        for _ in 0..20 {
+            app.handle_mouse(scroll(MouseEventKind::ScrollDown));
+        }
+        assert_eq!(app.week_scroll, MAX_TIMELINE_SCROLL);
+    }
+
+    #[test]
+    fn test_half_page_scroll_clamps_at_both_ends() {
+        assert_eq!(clamp_timeline_scroll(8, TIMELINE_PAGE_HOURS), 12);
+        assert_eq!(clamp_timeline_scroll(2, -TIMELINE_PAGE_HOURS), 0);
+        assert_eq!(clamp_timeline_scroll(0, -1), 0);
+        assert_eq!(
+            clamp_timeline_scroll(MAX_TIMELINE_SCROLL - 1, TIMELINE_PAGE_HOURS),
+            MAX_TIMELINE_SCROLL
+        );
+
+        let mut app = App::new();
+        app.view_mode = ViewMode::Day;
+        app.day_scroll = 13;
+        app.handle_action(Action::ScrollTimelineDown);
+        assert_eq!(app.day_scroll, MAX_TIMELINE_SCROLL);
+        app.handle_action(Action::ScrollTimelineDown);
+        assert_eq!(app.day_scroll, MAX_TIMELINE_SCROLL);
+
+        app.view_mode = ViewMode::Week;
+        app.week_scroll = 3;
+        app.handle_action(Action::ScrollTimelineUp);
+        assert_eq!(app.week_scroll, 0);
+        app.handle_action(Action::ScrollTimelineUp);
+        assert_eq!(app.week_scroll, 0);
+        assert_eq!(app.day_scroll, MAX_TIMELINE_SCROLL);
+    }
+
+    #[test]
+    fn test_view_indicator_follows_mode_keys() {
+        let mut app = App::new();
+        app.focused_panel = FocusedPanel::Calendar;
+        app.handle_action(Action::ViewWeek);
+        assert_eq!(
+            view_indicator(&app.view_mode, &app.focused_panel),
+            "[W] Calendar"
+        );
+        app.handle_action(Action::ViewDay);
+        app.focused_panel = FocusedPanel::WeekView;
+        assert_eq!(
+            view_indicator(&app.view_mode, &app.focused_panel),
+            "[D] Timeline"
+        );
+        app.handle_action(Action::ViewAgenda);
+        app.focused_panel = FocusedPanel::Events;
+        assert_eq!(
+            view_indicator(&app.view_mode, &app.focused_panel),
+            "[A] Events"
+        );
+    }
+
+    #[test]
+    fn test_read_only_mode_refuses_changes() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        app.read_only = true;
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+        app.event_manager
+            .add_event(event_at(&app.calendar, 9, 10))
+            .unwrap();
+        app.focused_panel = FocusedPanel::Events;
+
+        for action in [Action::AddEvent, Action::QuickAdd, Action::Duplicate] {
+            app.handle_action(action);
+            assert_eq!(app.popup, PopupState::Hidden);
+            assert_eq!(app.status.as_ref().unwrap().0, READ_ONLY_MESSAGE);
+        }
+        app.handle_action(Action::DeleteOccurrence);
+        app.handle_action(Action::ExtendEvent);
+        assert_eq!(app.event_manager.len(), 1);
+        assert_eq!(app.day_events()[0].end_time.hour(), 10);
+        assert_eq!(
+            app.run_command(Command::Delete("Standup".to_string()))
+                .unwrap_err()
+                .to_string(),
+            READ_ONLY_MESSAGE
+        );
+        assert!(!app.dirty);
+
+        // Looking around still works
+        app.handle_action(Action::ViewWeek);
+        app.handle_action(Action::MoveRight);
+        assert_eq!(app.view_mode, ViewMode::Week);
+        assert!(app
+            .run_command(Command::GoTo(app.calendar.selected_naive()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_event_count_badge() {
+        assert_eq!(event_count_badge(0), " ");
+        assert_eq!(event_count_badge(3), "³");
+        assert_eq!(event_count_badge(9), "⁹");
+        assert_eq!(event_count_badge(12), "⁹⁺");
+    }
+
+    #[test]
+    fn test_agenda_groups_events_by_day() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let at = |date: NaiveDate, hour| local_midnight(date) + chrono::Duration::hours(hour);
+        let next_day = day.succ_opt().unwrap();
+        let events = [
+            cal_events::Event::new("Standup".to_string(), None, at(day, 9), at(day, 10)).unwrap(),
+            cal_events::Event::new("Review".to_string(), None, at(day, 14), at(day, 15)).unwrap(),
+            cal_events::Event::new("Trip".to_string(), None, at(next_day, 8), at(next_day, 30))
+                .unwrap(),
+        ];
+        let mut events = events;
+        events[1].location = Some("Conference room on the third floor".to_string());
+        let events: Vec<Cow<cal_events::Event>> = events.iter().map(Cow::Borrowed).collect();
+
+        let lines: Vec<String> = agenda_lines(
+            &events,
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &MonthLabels::default(),
+            TimeFormat::TwentyFourHour,
+        )
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+        assert_eq!(
+            lines,
+            [
+                "Friday, March 14",
+                "• 09:00 - 10:00  Standup",
+                "• 14:00 - 15:00  Review @ Conference room on the …",
+                "",
+                "Saturday, March 15",
+                "• 08:00 - Mar 16 06:00  Trip",
+            ]
+        );
+
+        let rendered = render_to_string(create_agenda_view(
+            &[],
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &MonthLabels::default(),
+            None,
+            0,
+            TimeFormat::TwentyFourHour,
+        ));
+        assert!(rendered.contains("No upcoming events"));
+    }
+
+    #[test]
+    fn test_upcoming_panel_lists_next_events() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let now = local_midnight(NaiveDate::from_ymd_opt(2030, 5, 10).unwrap())
+            + chrono::Duration::hours(12);
+        for (title, hours) in [
+            ("Earlier", -1),
+            ("Lunch", 1),
+            ("Dinner", 7),
+            ("Far off", 24 * 40),
+            ("Standup", 21),
+            ("Review", 26),
+            ("Retro", 50),
+            ("Demo", 74),
+        ] {
+            let start = now + chrono::Duration::hours(hours);
+            let event = cal_events::Event::new(
+                title.to_string(),
+                None,
+                start,
+                start + chrono::Duration::minutes(30),
+            );
+            app.event_manager.add_event(event.unwrap()).unwrap();
+        }
+
+        let upcoming = app.upcoming_events(now);
+        let titles: Vec<_> = upcoming.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, ["Lunch", "Dinner", "Standup", "Review", "Retro"]);
+        // Once lunch has started it makes way for the next event
+        let later = app.upcoming_events(now + chrono::Duration::hours(2));
+        assert_eq!(later[0].title, "Dinner");
+        assert_eq!(later[4].title, "Demo");
+
+        let rendered = render_to_string(create_upcoming_panel(
+            &upcoming,
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            TimeFormat::TwentyFourHour,
+        ));
+        assert!(rendered.contains("Fri 10 13:00 Lunch"));
+        assert!(rendered.contains("Sat 11 14:00 Review"));
+        let rendered = render_to_string(create_upcoming_panel(
+            &[],
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            TimeFormat::TwentyFourHour,
+        ));
+        assert!(rendered.contains("Nothing coming up"));
+    }
+
+    #[test]
+    fn test_parse_agenda_range() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2025, month, day).unwrap();
+        assert_eq!(
+            parse_agenda_range("2025-04-01 to 2025-06-30", today).unwrap(),
+            Some((date(4, 1), date(6, 30)))
+        );
+        assert_eq!(
+            parse_agenda_range(" today to +7d ", today).unwrap(),
+            Some((today, date(3, 21)))
+        );
+        assert_eq!(
+            parse_agenda_range("today to today", today).unwrap(),
+            Some((today, today))
+        );
+        assert_eq!(parse_agenda_range("", today).unwrap(), None);
+        assert_eq!(default_agenda_range(today), (today, date(4, 13)));
+
+        assert_eq!(
+            parse_agenda_range("2025-04-01 to 2025-03-01", today)
+                .unwrap_err()
+                .to_string(),
+            "The end date must not be before the start date"
+        );
+        assert!(parse_agenda_range("2025-04-01", today).is_err());
+        assert!(parse_agenda_range("someday to today", today).is_err());
+    }
+
+    #[test]
+    fn test_agenda_shows_only_the_entered_range() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let day = NaiveDate::from_ymd_opt(2030, 5, 10).unwrap();
+        for (title, date) in [
+            ("Before", day.pred_opt().unwrap()),
+            ("First", day),
+            ("Last", day + chrono::Duration::days(2)),
+            ("After", day + chrono::Duration::days(3)),
+        ] {
+            let start = local_midnight(date) + chrono::Duration::hours(23);
+            let event = cal_events::Event::new(
+                title.to_string(),
+                None,
+                start,
+                start + chrono::Duration::minutes(30),
+            );
+            app.event_manager.add_event(event.unwrap()).unwrap();
+        }
+
+        app.agenda_range = Some((day, day + chrono::Duration::days(2)));
+        let titles: Vec<_> = app
+            .agenda_events()
+            .iter()
+            .map(|e| e.title.clone())
+            .collect();
+        assert_eq!(titles, ["First", "Last"]);
+    }
+
+    #[test]
+    fn test_duplicate_prefills_create_popup() {
+        let mut app = App::new();
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+        let mut event = event_at(&app.calendar, 9, 11);
+        event.description = Some("Room 4".to_string());
+        event.priority = Priority::High;
+        event.attendees = vec!["a@x.com".to_string(), "b@y.com".to_string()];
+        app.event_manager.add_event(event.clone()).unwrap();
+
+        // Only the Events panel has a highlighted event to copy
+        app.handle_action(Action::Duplicate);
+        assert_eq!(app.popup, PopupState::Hidden);
+
+        app.focused_panel = FocusedPanel::Events;
+        assert_eq!(app.highlighted_event().as_deref(), Some(&event));
+        app.handle_action(Action::Duplicate);
+        match &app.popup {
+            PopupState::CreateEvent {
+                title,
+                description,
+                start_time,
+                end_time,
+                priority,
+                attendees,
+                ..
+            } => {
+                assert_eq!(title, "Standup");
+                assert_eq!(description, "Room 4");
+                assert_eq!(start_time, "2025-03-14 09:00");
+                assert_eq!(end_time, "2025-03-14 11:00");
+                assert_eq!(*priority, Priority::High);
+                assert_eq!(parse_attendees_input(attendees), event.attendees);
+            }
+            other => panic!("expected the create popup, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_week_view_headers_follow_selected_week() {
+        let mut calendar = Calendar::new();
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 4, 2).unwrap());
+
+        let rendered = render_to_string(create_week_view(
+            &calendar,
+            &[],
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &Timeline::default(),
+        ));
+        assert!(rendered.contains("Mon 31"));
+        assert!(rendered.contains("Sun 6"));
+        assert!(!rendered.contains("Mon 7"));
+    }
+
+    #[test]
+    fn test_week_view_highlights_conflicting_events() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        app.category_filter = None;
+        app.tag_filter = None;
+        app.calendar.set_week_start(Weekday::Mon);
+        let monday = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        app.calendar.go_to_date(monday);
+        let tuesday = monday.succ_opt().unwrap();
+        for (title, day, hour, minutes) in [
+            ("Clash A", monday, 10, 60),
+            ("Clash B", monday, 10, 30),
+            ("Solo", tuesday, 10, 60),
+        ] {
+            let start = local_midnight(day) + chrono::Duration::hours(hour);
+            let event = cal_events::Event::new(
+                title.to_string(),
+                None,
+                start,
+                start + chrono::Duration::minutes(minutes),
+            )
+            .unwrap();
+            app.event_manager.add_event(event).unwrap();
+        }
+
+        let timeline = Timeline {
+            scroll: 9,
+            ..Timeline::default()
+        };
+        let theme = Theme::default();
+        let week_events = app.week_events();
+        assert_eq!(week_events[0].len(), 2);
+        assert_eq!(week_events[1].len(), 1);
+        let week = create_week_view(
+            &app.calendar,
+            &week_events,
+            &theme,
+            &WeekdayLabels::default(),
+            &timeline,
+        );
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|f| f.render_widget(week, f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        assert!(buffer_text(buffer, |_| true).contains("Solo"));
+        let warned = buffer_text(buffer, |cell| cell.fg == theme.conflict);
+        assert!(warned.contains("Clash A"));
+        assert!(warned.contains("Clash B"));
+        assert!(!warned.contains("Solo"));
+    }
+
+    #[test]
+    fn test_cycle_priority() {
+        let mut priority = Priority::Normal;
+        for expected in [Priority::High, Priority::Low, Priority::Normal] {
+            priority = cycle_priority(priority, true);
+            assert_eq!(priority, expected);
+        }
+        assert_eq!(cycle_priority(Priority::Normal, false), Priority::Low);
+        assert_eq!(cycle_priority(Priority::Low, false), Priority::High);
+    }
+
+    #[test]
+    fn test_cycle_recurrence_and_weekdays() {
+        let mut frequency = None;
+        for expected in [
+            Some(Frequency::Daily),
+            Some(Frequency::Weekly),
+            Some(Frequency::Monthly),
+            None,
+        ] {
+            frequency = cycle_recurrence(frequency, true);
+            assert_eq!(frequency, expected);
+        }
+        assert_eq!(cycle_recurrence(None, false), Some(Frequency::Monthly));
+
+        let mut weekdays = Vec::new();
+        for digit in ['3', '1', '8', 'x', '3', '7'] {
+            toggle_weekday(&mut weekdays, digit);
+        }
+        assert_eq!(weekdays, [Weekday::Mon, Weekday::Sun]);
+        assert_eq!(
+            recurrence_input_label(Some(Frequency::Weekly), &weekdays),
+            "◀ Weekly ▶ [Mon] Tue Wed Thu Fri Sat [Sun]"
+        );
+        assert_eq!(recurrence_input_label(None, &weekdays), "◀ Never ▶");
+    }
+
+    #[test]
+    fn test_create_popup_saves_daily_event() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let start = Local::now() + chrono::Duration::days(1);
+        app.popup = PopupState::create_event(start, start + chrono::Duration::hours(1), None);
+        if let PopupState::CreateEvent {
+            title, recurrence, ..
+        } = &mut app.popup
+        {
+            *title = "Stretch".to_string();
+            *recurrence = cycle_recurrence(None, true);
+        }
+        app.submit_event_popup();
+        assert_eq!(app.popup, PopupState::Hidden);
+
+        let first = start.date_naive();
+        for day in first.iter_days().take(3) {
+            assert_eq!(app.event_manager.count_events_for_day(day), 1, "{day}");
+        }
+        assert_eq!(
+            app.event_manager
+                .count_events_for_day(first.pred_opt().unwrap()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_editing_after_past_warning_asks_again() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let start = Local::now() - chrono::Duration::days(2);
+        app.popup = PopupState::create_event(start, start + chrono::Duration::hours(1), None);
+        if let PopupState::CreateEvent { title, .. } = &mut app.popup {
+            *title = "Retro".to_string();
+        }
+        let warned = |app: &App| {
+            matches!(
+                &app.popup,
+                PopupState::CreateEvent {
+                    error: Some(_),
+                    confirm_past: true,
+                    ..
+                }
+            )
+        };
+
+        app.submit_event_popup();
+        assert!(warned(&app));
+        if let PopupState::CreateEvent { title, .. } = &mut app.popup {
+            title.push('!');
+        }
+        app.popup.forget_past_warning();
+        assert!(!warned(&app));
+
+        // The changed event is warned about again before it is added
+        app.submit_event_popup();
+        assert!(warned(&app));
+        assert!(app.event_manager.is_empty());
+        app.submit_event_popup();
+        assert_eq!(app.popup, PopupState::Hidden);
+        assert_eq!(app.event_manager.list_events()[0].title, "Retro!");
+
+        let mut quick_add = PopupState::QuickAdd {
+            input: "Lunch 12:00".to_string(),
+            error: None,
+            confirm_past: true,
+        };
+        quick_add.forget_past_warning();
+        assert!(matches!(
+            quick_add,
+            PopupState::QuickAdd {
+                confirm_past: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_attendees_input() {
+        assert_eq!(
+            parse_attendees_input("a@x.com, b@y.com"),
+            ["a@x.com", "b@y.com"]
+        );
+        assert_eq!(parse_attendees_input(" Ann ,, Bob,"), ["Ann", "Bob"]);
+        assert!(parse_attendees_input("  ").is_empty());
+    }
+
+    #[test]
+    fn test_centered_rect_fits_small_areas() {
+        let area = Rect::new(0, 0, 100, 40);
+        assert_eq!(centered_rect(60, 20, area), Rect::new(20, 10, 60, 20));
+
+        let small = Rect::new(5, 3, 30, 10);
+        let popup = centered_rect(60, 32, small);
+        assert_eq!(popup, small);
+        assert_eq!(small.intersection(popup), popup);
+
+        // Drawing a popup into a tiny terminal must not panic
+        let mut app = App::new();
+        app.popup = PopupState::create_event(Local::now(), Local::now(), None);
+        let mut terminal = Terminal::new(TestBackend::new(20, 6)).unwrap();
+        terminal.draw(|f| ui(f, &mut app)).unwrap();
+    }
+
+    #[test]
+    fn test_new_event_starts_at_focused_hour() {
+        let mut app = App::new();
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+        let start_hour = |app: &App| match &app.popup {
+            PopupState::CreateEvent { start_time, .. } => start_time.clone(),
+            popup => panic!("unexpected popup {:?}", popup),
+        };
+
+        app.view_mode = ViewMode::Day;
+        app.day_scroll = 0;
+        app.focused_panel = FocusedPanel::WeekView;
+        app.handle_action(Action::AddEvent);
+        assert_eq!(start_hour(&app), "2025-03-14 09:00");
+
+        // The first Down focuses the default hour, later ones move and scroll
+        app.popup = PopupState::Hidden;
+        for _ in 0..6 {
+            app.handle_action(Action::MoveDown);
+        }
+        assert_eq!(app.focused_hour, Some(14));
+        assert_eq!(app.day_scroll, 14 + 1 - TIMELINE_VISIBLE_HOURS);
+        app.handle_action(Action::AddEvent);
+        assert_eq!(start_hour(&app), "2025-03-14 14:00");
+
+        // Month view has no timeline, so it uses the default hour
+        app.popup = PopupState::Hidden;
+        app.view_mode = ViewMode::Month;
+        app.focused_panel = FocusedPanel::Events;
+        app.handle_action(Action::AddEvent);
+        assert_eq!(start_hour(&app), "2025-03-14 09:00");
+    }
+
+    #[test]
+    fn test_delete_day_needs_confirmation() {
+        let mut app = App::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        app.calendar.go_to_date(day);
+        for hour in [9, 13] {
+            let start = local_midnight(day) + chrono::Duration::hours(hour);
+            let event = cal_events::Event::new(
+                "Talk".to_string(),
+                None,
+                start,
+                start + chrono::Duration::hours(1),
+            )
+            .unwrap();
+            app.event_manager.add_event(event).unwrap();
+        }
+
+        app.handle_action(Action::DeleteDay);
+        assert_eq!(app.event_manager.count_events_for_day(day), 2);
+        assert!(app
+            .status
+            .as_ref()
+            .unwrap()
+            .0
+            .starts_with("Delete 2 events starting on March 14?"));
+
+        // Doing anything else in between cancels the delete
+        app.handle_action(Action::NextPanel);
+        app.handle_action(Action::DeleteDay);
+        assert_eq!(app.event_manager.count_events_for_day(day), 2);
+
+        app.handle_action(Action::DeleteDay);
+        assert_eq!(app.event_manager.count_events_for_day(day), 0);
+    }
+
+    #[test]
+    fn test_month_grid_matches_across_sizes() {
+        let mut calendar = Calendar::new();
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 2, 10).unwrap());
+        let counts = HashMap::from([(10, 3)]);
+        let render = |options: &MonthGridOptions| {
+            let mut terminal = Terminal::new(TestBackend::new(80, 40)).unwrap();
+            terminal
+                .draw(|f| {
+                    f.render_widget(
+                        render_month_grid(
+                            &calendar,
+                            &Theme::default(),
+                            &WeekdayLabels::default(),
+                            &MonthLabels::default(),
+                            options,
+                        ),
+                        f.area(),
+                    )
+                })
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+        let day_numbers = |buffer: &ratatui::buffer::Buffer| -> Vec<u32> {
+            let text: String = buffer.content().iter().map(|cell| cell.symbol()).collect();
+            text.split(|c: char| !c.is_ascii_digit())
+                .filter_map(|number| number.parse().ok())
+                .collect()
+        };
+
+        let mini = MonthGridOptions {
+            title: Some(String::new()),
+            ..MonthGridOptions::mini(2025, 2)
+        };
+        let wide = MonthGridOptions {
+            cell_width: None,
+            title: Some(String::new()),
+            event_counts: Some(&counts),
+            ..MonthGridOptions::mini(2025, 2)
+        };
+        let mini_buffer = render(&mini);
+        let wide_buffer = render(&wide);
+        let days: Vec<u32> = (1..=28).collect();
+        assert_eq!(day_numbers(&mini_buffer), days);
+        assert_eq!(day_numbers(&wide_buffer), days);
+
+        let text: String = wide_buffer
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(text.contains("MON"));
+        assert!(text.contains(" 10³"));
+
+        // Only grids that show the selection highlight the selected day
+        let reversed = |buffer: &ratatui::buffer::Buffer| {
+            buffer
+                .content()
+                .iter()
+                .any(|cell| cell.modifier.contains(Modifier::REVERSED))
+        };
+        assert!(reversed(&mini_buffer));
+        let unselected = MonthGridOptions {
+            show_selection: false,
+            ..MonthGridOptions::mini(2025, 2)
+        };
+        assert!(!reversed(&render(&unselected)));
+    }
+
+    #[test]
+    fn test_week_and_day_views_mark_selected_day() {
+        let mut calendar = Calendar::new();
+        calendar.set_week_start(Weekday::Mon);
+        calendar.go_to_date(NaiveDate::from_ymd_opt(2025, 4, 2).unwrap());
+        let reversed_text = |widget: Table| {
+            let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+            terminal
+                .draw(|f| f.render_widget(widget, f.area()))
+                .unwrap();
+            terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .filter(|cell| cell.modifier.contains(Modifier::REVERSED))
+                .map(|cell| cell.symbol())
+                .collect::<String>()
+        };
+
+        let week = create_week_view(
+            &calendar,
+            &[],
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &Timeline::default(),
+        );
+        assert!(reversed_text(week).starts_with("Wed 2"));
+
+        let event_manager = EventManager::new();
+        let day = create_day_view(
+            &calendar,
+            &event_manager.list_events_for_day(calendar.selected_date),
+            &Theme::default(),
+            &WeekdayLabels::default(),
+            &MonthLabels::default(),
+            &Timeline::default(),
+        );
+        assert!(reversed_text(day).starts_with("Wed 2"));
+    }
+
+    #[test]
+    fn test_delete_command_by_title() {
+        let mut app = App::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        for date in [day, day.succ_opt().unwrap()] {
+            let start = local_midnight(date) + chrono::Duration::hours(9);
+            let event = cal_events::Event::new(
+                "Standup".to_string(),
+                None,
+                start,
+                start + chrono::Duration::minutes(15),
+            )
+            .unwrap();
+            app.event_manager.add_event(event).unwrap();
+        }
+
+        let run =
+            |app: &mut App, input: &str| Command::parse(input).and_then(|c| app.run_command(c));
+        assert!(run(&mut app, "delete Lunch").is_err());
+        app.calendar.go_to_date(day.pred_opt().unwrap());
+        assert!(run(&mut app, "delete standup").is_err());
+
+        run(&mut app, "goto 2025-03-14").unwrap();
+        run(&mut app, "delete standup").unwrap();
+        assert!(!app.event_manager.has_events_on(day));
+        assert_eq!(app.event_manager.len(), 1);
+
+        // Only one is left, so it is found from any day
+        run(&mut app, "delete Standup").unwrap();
+        assert!(app.event_manager.is_empty());
+    }
+
+    #[test]
+    fn test_quit_asks_to_save_changes() {
+        let mut app = App::new();
+        assert!(app.request_quit());
+
+        let start = Local::now() + chrono::Duration::days(1);
+        let event = cal_events::Event::new(
+            "Call".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        assert_eq!(app.add_popup_event(event, false), None);
+        assert!(!app.request_quit());
+        assert_eq!(app.popup, PopupState::ConfirmQuit);
+
+        let path = std::env::temp_dir()
+            .join(format!("cal-rs-test-{}", Uuid::new_v4()))
+            .join("events.json");
+        app.events_path = Some(path.clone());
+        app.save_events().unwrap();
+        assert!(app.request_quit());
+
+        let mut reopened = App::new();
+        reopened.open_events(path.clone()).unwrap();
+        assert_eq!(reopened.event_manager.len(), 1);
+        assert_eq!(reopened.status, None);
+
+        // A second save leaves a backup to fall back on
+        app.save_events().unwrap();
+        std::fs::write(&path, "[{ not json").unwrap();
+        let mut recovered = App::new();
+        recovered.open_events(path.clone()).unwrap();
+        assert_eq!(recovered.event_manager.len(), 1);
+        assert!(recovered
+            .status
+            .unwrap()
+            .0
+            .ends_with("loaded the backup instead"));
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_heatmap_ramp_darkens_with_busier_days() {
+        assert_eq!(heatmap_style(0), None);
+        let background = |minutes| heatmap_style(minutes).unwrap().bg.unwrap();
+        assert_eq!(background(30), background(59));
+        assert_ne!(background(60), background(6 * 60));
+        // Anything past the end of the ramp uses the darkest shade
+        assert_eq!(background(6 * 60), background(12 * 60));
+    }
+
+    #[test]
+    fn test_timeline_opens_at_business_hours() {
+        let mut app = App::new();
+        app.apply_preferences(Preferences::default());
+        assert_eq!((app.week_scroll, app.day_scroll), (8, 8));
+
+        // Late start hours still leave a full screen of hours to show
+        app.apply_preferences(Preferences {
+            day_view_start_hour: 22,
+            ..Preferences::default()
+        });
+        assert_eq!(app.day_scroll, MAX_TIMELINE_SCROLL);
+        assert_eq!(app.preferences().day_view_start_hour, 22);
+    }
+
+    #[test]
+    fn test_restores_selected_date_unless_past() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let date = |day| NaiveDate::from_ymd_opt(2025, 3, day);
+        assert_eq!(date_to_restore(date(20), today), date(20));
+        assert_eq!(date_to_restore(date(14), today), date(14));
+        assert_eq!(date_to_restore(date(13), today), None);
+        assert_eq!(date_to_restore(None, today), None);
+
+        let mut app = App::new();
+        let later = Local::now().date_naive() + chrono::Duration::days(40);
+        app.apply_preferences(Preferences {
+            selected_date: Some(later),
+            ..Preferences::default()
+        });
+        assert_eq!(app.calendar.selected_naive(), later);
+        assert_eq!(app.preferences().selected_date, Some(later));
+
+        app.calendar.go_to_today();
+        app.apply_preferences(Preferences {
+            selected_date: Some(NaiveDate::from_ymd_opt(2001, 1, 1).unwrap()),
+            ..Preferences::default()
+        });
+        assert_eq!(app.calendar.selected_naive(), Local::now().date_naive());
+    }
+
+    #[test]
+    fn test_close_popup_hides_every_popup() {
+        let mut app = App::new();
+        let now = Local::now();
+        for popup in [
+            PopupState::create_event(now, now, None),
+            PopupState::Command {
+                input: "goto".to_string(),
+            },
+            PopupState::AgendaRange {
+                input: "today to +7d".to_string(),
+                error: None,
+            },
+            PopupState::ConfirmQuit,
+            PopupState::Reminder {
+                id: Uuid::new_v4(),
+                start: now.fixed_offset(),
+                title: "Standup".to_string(),
+                body: String::new(),
+            },
+            PopupState::Help,
+        ] {
+            app.popup = popup;
+            app.close_popup();
+            assert_eq!(app.popup, PopupState::Hidden);
+        }
+    }
+
+    #[test]
+    fn test_iso_week_numbers_of_month_rows() {
+        let week_numbers = |calendar: &Calendar, year, month| -> Vec<u32> {
+            calendar
+                .get_month_grid_for(year, month)
+                .iter()
+                .map(|week| iso_week_of_row(year, month, week).unwrap())
+                .collect()
+        };
+        let mut calendar = Calendar::new();
+
+        // December 2025 ends in week 1 of 2026
+        calendar.set_week_start(Weekday::Mon);
+        assert_eq!(week_numbers(&calendar, 2025, 12), [49, 50, 51, 52, 1]);
+        // January 2021 starts in week 53 of 2020
+        assert_eq!(week_numbers(&calendar, 2021, 1), [53, 1, 2, 3, 4]);
+
+        // With Sunday first, each row takes the week of its Monday
+        calendar.set_week_start(Weekday::Sun);
+        assert_eq!(week_numbers(&calendar, 2025, 12), [49, 50, 51, 52, 1]);
+
+        // The week number column is not a clickable day
+        let area = Rect::new(0, 0, 70, 20);
+        let rows = MonthRows::default();
+        assert_eq!(month_cell_at(area, 0, 3, true, rows), None);
+        assert_eq!(month_cell_at(area, 4, 3, true, rows), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_step_time_fields() {
+        let start = parse_datetime_input("2025-03-14 23:50").unwrap();
+        let mut popup = PopupState::create_event(start, start + chrono::Duration::hours(1), None);
+        let times = |popup: &PopupState| match popup {
+            PopupState::CreateEvent {
+                start_time,
+                end_time,
+                ..
+            } => (start_time.clone(), end_time.clone()),
+            _ => unreachable!(),
+        };
+        let focus = |popup: &mut PopupState, field| {
+            if let PopupState::CreateEvent { focused_field, .. } = popup {
+                *focused_field = field;
+            }
+        };
+
+        // Off-grid times snap to the next quarter hour, past midnight if need be
+        focus(&mut popup, START_TIME_FIELD);
+        popup.step_time(true);
+        assert_eq!(times(&popup).0, "2025-03-15 00:00");
+        popup.step_time(false);
+        assert_eq!(times(&popup).0, "2025-03-14 23:45");
+
+        // A duration in the end field becomes the stepped absolute time
+        if let PopupState::CreateEvent { end_time, .. } = &mut popup {
+            *end_time = "1h".to_string();
+        }
+        focus(&mut popup, END_TIME_FIELD);
+        popup.step_time(true);
+        assert_eq!(times(&popup).1, "2025-03-15 01:00");
+
+        // Other fields are left alone
+        focus(&mut popup, 0);
+        assert!(!popup.time_field_focused());
+        popup.step_time(true);
+        assert_eq!(times(&popup).1, "2025-03-15 01:00");
+    }
+
+    #[test]
+    fn test_now_line_only_when_visible() {
+        let calendar = Calendar::new();
+        let today = calendar.selected_naive();
+        let now = Local
+            .from_local_datetime(&today.and_hms_opt(14, 40, 0).unwrap())
+            .unwrap();
+        assert_eq!(now_line_index(now, today, 14), Some(2));
+        assert_eq!(now_line_index(now, today, 13), None);
+        assert_eq!(now_line_index(now, today.succ_opt().unwrap(), 14), None);
+
+        let event_manager = EventManager::new();
+        let theme = Theme::default();
+        let labels = WeekdayLabels::default();
+        let timeline = |scroll| Timeline {
+            scroll,
+            now,
+            ..Timeline::default()
+        };
+        let week = |scroll| create_week_view(&calendar, &[], &theme, &labels, &timeline(scroll));
+        let day = |scroll| {
+            create_day_view(
+                &calendar,
+                &event_manager.list_events_for_day(calendar.selected_date),
+                &theme,
+                &labels,
+                &MonthLabels::default(),
+                &timeline(scroll),
+            )
+        };
+        assert!(render_to_string(week(10)).contains("━━ 14:40"));
+        assert!(render_to_string(day(10)).contains("━━ 14:40"));
+        assert!(!render_to_string(week(0)).contains("━━"));
+        assert!(!render_to_string(day(0)).contains("━━"));
+
+        let tomorrow = now + chrono::Duration::days(1);
+        let other_day = create_day_view(
+            &calendar,
+            &event_manager.list_events_for_day(calendar.selected_date),
+            &theme,
+            &labels,
+            &MonthLabels::default(),
+            &Timeline {
+                now: tomorrow,
+                ..timeline(10)
+            },
+        );
+        assert!(!render_to_string(other_day).contains("━━"));
+    }
+
+    #[test]
+    fn test_tag_filter_narrows_day_and_agenda() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let start = local_midnight(Local::now().date_naive() + chrono::Duration::days(1))
+            + chrono::Duration::hours(9);
+        for (title, tags) in [
+            ("Flight", "travel urgent"),
+            ("Taxes", "urgent"),
+            ("Nap", ""),
+        ] {
+            let mut event = cal_events::Event::new(
+                title.to_string(),
+                None,
+                start,
+                start + chrono::Duration::hours(1),
+            )
+            .unwrap();
+            event.tags = parse_tags(tags);
+            app.event_manager.add_event(event).unwrap();
+        }
+        app.calendar.go_to_date(start.date_naive());
+        let titles = |events: Vec<Cow<cal_events::Event>>| {
+            events
+                .iter()
+                .map(|event| event.title.clone())
+                .collect::<Vec<_>>()
+        };
+
+        app.handle_action(Action::CycleTag);
+        assert_eq!(app.tag_filter.as_deref(), Some("travel"));
+        assert_eq!(titles(app.agenda_events()), ["Flight"]);
+        app.handle_action(Action::CycleTag);
+        assert_eq!(filter_label(&app), "tag 'urgent'");
+        let day: Vec<_> = app.day_events().iter().map(|e| e.title.clone()).collect();
+        assert_eq!(day, ["Flight", "Taxes"]);
+        app.handle_action(Action::CycleTag);
+        assert_eq!(app.tag_filter, None);
+        assert_eq!(app.day_events().len(), 3);
+    }
+
+    #[test]
+    fn test_category_filter_cycles_and_filters_day_events() {
+        let mut app = App::new();
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        for (title, category) in [
+            ("Standup", Some("work")),
+            ("Gym", Some("health")),
+            ("Nap", None),
+        ] {
+            let mut builder = cal_events::EventBuilder::new()
+                .title(title)
+                .start(start)
+                .end(start + chrono::Duration::hours(1));
+            if let Some(category) = category {
+                builder = builder.category(category);
+            }
+            app.event_manager
+                .add_event(builder.build().unwrap())
+                .unwrap();
+        }
+        let titles = |app: &App| {
+            app.day_events()
+                .iter()
+                .map(|event| event.title.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(titles(&app).len(), 3);
+
+        app.handle_action(Action::CycleCategory);
+        assert_eq!(app.category_filter.as_deref(), Some("health"));
+        assert_eq!(titles(&app), ["Gym"]);
+        app.handle_action(Action::CycleCategory);
+        assert_eq!(titles(&app), ["Standup"]);
+        app.handle_action(Action::CycleCategory);
+        assert_eq!(app.category_filter, None);
+        assert_eq!(titles(&app).len(), 3);
+    }
+
+    #[test]
+    fn test_app_renders_selected_day_reversed() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+
+        let buffer = render_to_buffer(&mut app, 120, 40);
+        assert!(buffer_text(&buffer, |_| true).contains("March  2025"));
+        let reversed = buffer_text(&buffer, |cell| cell.modifier.contains(Modifier::REVERSED));
+        assert!(reversed.contains("14"));
+        assert!(!reversed.contains("13"));
+        assert!(!reversed.contains("15"));
+    }
+
+    #[test]
+    fn test_every_view_renders_at_any_size() {
+        let mut app = App::new();
+        for view_mode in [
+            ViewMode::Month,
+            ViewMode::Week,
+            ViewMode::Day,
+            ViewMode::Year,
+            ViewMode::Agenda,
+        ] {
+            app.view_mode = view_mode.clone();
+            for (width, height) in [(120, 40), (60, 20), (20, 8)] {
+                let buffer = render_to_buffer(&mut app, width, height);
+                assert_eq!(buffer.area, Rect::new(0, 0, width, height));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cancelled_events_dimmed_and_hideable() {
+        let mut app = App::new();
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        for (title, status) in [
+            ("Standup", EventStatus::Confirmed),
+            ("Offsite", EventStatus::Cancelled),
+        ] {
+            let event = cal_events::EventBuilder::new()
+                .title(title)
+                .status(status)
+                .start(start)
+                .end(start + chrono::Duration::hours(1))
+                .build()
+                .unwrap();
+            app.event_manager.add_event(event).unwrap();
+        }
+
+        let events = app.day_events();
+        assert_eq!(events.len(), 2);
+        for event in events {
+            let crossed_out = event_style(&event, &app.theme)
+                .add_modifier
+                .contains(Modifier::CROSSED_OUT);
+            assert_eq!(crossed_out, event.status == EventStatus::Cancelled);
+        }
+
+        app.handle_action(Action::ToggleCancelled);
+        let titles: Vec<_> = app.day_events().iter().map(|e| e.title.clone()).collect();
+        assert_eq!(titles, ["Standup"]);
+        app.handle_action(Action::ToggleCancelled);
+        assert_eq!(app.day_events().len(), 2);
+    }
 
-    let header_layout = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
-        .split(content_chunks[0]);
+    #[test]
+    fn test_cycle_status() {
+        let mut status = EventStatus::Confirmed;
+        for expected in [
+            EventStatus::Cancelled,
+            EventStatus::Tentative,
+            EventStatus::Confirmed,
+        ] {
+            status = cycle_status(status, true);
+            assert_eq!(status, expected);
+            assert_eq!(
+                cycle_status(status, false),
+                cycle_status(cycle_status(status, true), true)
+            );
+        }
+    }
 
-    let nav_text = format!("← {}   Today   → {} ", "Previous", "Next");
-    let nav_header = Paragraph::new(nav_text)
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+    #[test]
+    fn test_extend_and_shrink_highlighted_event() {
+        let mut app = App::new();
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        let event = cal_events::Event::new(
+            "Standup".to_string(),
+            None,
+            start,
+            start + chrono::Duration::minutes(30),
+        )
+        .unwrap();
+        let id = event.id;
+        app.event_manager.add_event(event).unwrap();
+        app.focused_panel = FocusedPanel::Events;
+        let length = |app: &App| {
+            let event = app.event_manager.get_event(id).unwrap();
+            (event.end_time - event.start_time).num_minutes()
+        };
 
-    let clock_text = create_clock();
-    let clock = Paragraph::new(clock_text)
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .alignment(Alignment::Center)
-        .block(Block::default().borders(Borders::ALL));
+        app.handle_action(Action::ExtendEvent);
+        assert_eq!(length(&app), 45);
+        assert_eq!(app.status.as_ref().unwrap().0, "'Standup' now lasts 45m");
 
-    f.render_widget(nav_header, header_layout[0]);
-    f.render_widget(clock, header_layout[1]);
+        for _ in 0..2 {
+            app.handle_action(Action::ShrinkEvent);
+        }
+        assert_eq!(length(&app), 15);
 
-    let weekdays = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
-    let header_cells = weekdays
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Gray)));
-    let header = Row::new(header_cells)
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .height(2);
+        // Ending at the start time would leave an empty event
+        app.handle_action(Action::ShrinkEvent);
+        assert_eq!(length(&app), 15);
+        assert_eq!(
+            app.status.as_ref().unwrap().0,
+            "'Standup' is already as short as it can be"
+        );
 
-    let grid = app.calendar.get_month_grid();
-    let rows: Vec<Row> = grid
-        .iter()
-        .map(|week| {
-            let cells = week.iter().map(|day| match day {
-                Some(d) => {
-                    let now = Local::now();
-                    let is_current_day = d == &now.day()
-                        && app.calendar.current_date.month() == now.month()
-                        && app.calendar.current_date.year() == now.year();
-                    let is_selected = d == &app.calendar.selected_date.day()
-                        && app.calendar.current_date.month() == app.calendar.selected_date.month()
-                        && app.calendar.current_date.year() == app.calendar.selected_date.year();
-
-                    let style = match (is_current_day, is_selected) {
-                        (true, true) => Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::BOLD | Modifier::REVERSED),
-                        (true, false) => Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::BOLD),
-                        (false, true) => Style::default().add_modifier(Modifier::REVERSED),
-                        (false, false) => Style::default(),
-                    };
+        app.event_manager.undo().unwrap();
+        assert_eq!(length(&app), 30);
+    }
 
-                    Cell::from(format!(" {} ", d)).style(style)
-                }
-                None => Cell::from("   "),
-            });
-            Row::new(cells).height(3)
-        })
-        .collect();
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(chrono::Duration::minutes(45)), "45m");
+        assert_eq!(format_duration(chrono::Duration::minutes(120)), "2h");
+        assert_eq!(format_duration(chrono::Duration::minutes(90)), "1h 30m");
+    }
 
-    let widths = [
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-        Constraint::Percentage(14),
-    ];
+    #[test]
+    fn test_delete_highlighted_occurrence() {
+        let mut app = App::new();
+        let day = app.calendar.selected_naive();
+        let start = local_midnight(day) + chrono::Duration::hours(9);
+        let event = cal_events::EventBuilder::new()
+            .title("Standup")
+            .start(start - chrono::Duration::days(7))
+            .end(start - chrono::Duration::days(7) + chrono::Duration::minutes(15))
+            .recurrence(cal_events::Recurrence::new(cal_events::Frequency::Weekly))
+            .build()
+            .unwrap();
+        let id = app.event_manager.add_event(event).unwrap();
+        app.focused_panel = FocusedPanel::Events;
 
-    let calendar_table = Table::new(rows, widths)
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!(
-            "{}  {}",
-            app.calendar.current_date.format("%B"),
-            app.calendar.current_date.format("%Y")
-        )))
-        .column_spacing(1);
-
-    let calendar_widget = match app.view_mode {
-        ViewMode::Month => calendar_table,
-        ViewMode::Week => {
-            let mut week_view = create_week_view(&app.calendar, app.week_scroll);
-            if app.focused_panel == FocusedPanel::WeekView {
-                week_view = week_view.block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Week View")
-                        .border_style(Style::default().fg(Color::Cyan)),
-                );
-            }
-            week_view
+        let highlighted = app.highlighted_event().unwrap();
+        assert_eq!(
+            (highlighted.id, highlighted.start_time),
+            (id, start.fixed_offset())
+        );
+
+        app.handle_action(Action::DeleteOccurrence);
+        assert!(app.day_events().is_empty());
+        assert!(app
+            .event_manager
+            .has_events_on(day + chrono::Duration::days(7)));
+        assert!(app.dirty);
+    }
+
+    #[test]
+    fn test_tick_rate_preference() {
+        let mut app = App::new();
+        app.apply_preferences(Preferences {
+            tick_rate_ms: 2000,
+            ..Preferences::default()
+        });
+        assert_eq!(app.tick_rate, Duration::from_secs(2));
+        assert_eq!(app.preferences().tick_rate_ms, 2000);
+
+        app.apply_preferences(Preferences {
+            tick_rate_ms: 0,
+            ..Preferences::default()
+        });
+        assert_eq!(app.tick_rate, MIN_TICK_RATE);
+    }
+
+    #[test]
+    fn test_work_hours_preference() {
+        let mut app = App::new();
+        app.apply_preferences(Preferences {
+            work_start_hour: 7,
+            work_end_hour: 15,
+            ..Preferences::default()
+        });
+        assert_eq!(app.work_hours, 7..15);
+
+        // An empty or impossible window falls back to the default one
+        for (start, end) in [(18, 8), (9, 9), (8, 25)] {
+            app.apply_preferences(Preferences {
+                work_start_hour: start,
+                work_end_hour: end,
+                ..Preferences::default()
+            });
+            assert_eq!(app.work_hours, 8..18);
         }
-        ViewMode::Day => {
-            let mut day_view = create_day_view(&app.calendar, app.day_scroll);
-            if app.focused_panel == FocusedPanel::WeekView {
-                day_view = day_view.block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Day View")
-                        .border_style(Style::default().fg(Color::Cyan)),
-                );
-            }
-            day_view
+    }
+
+    #[test]
+    fn test_until_next_second() {
+        let at = |nanos| Local.timestamp_opt(1_700_000_000, nanos).single().unwrap();
+        assert_eq!(until_next_second(at(0)), Duration::from_secs(1));
+        assert_eq!(
+            until_next_second(at(750_000_000)),
+            Duration::from_millis(250)
+        );
+    }
+
+    #[test]
+    fn test_jump_between_days_with_events() {
+        let mut app = App::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let later = NaiveDate::from_ymd_opt(2025, 4, 2).unwrap();
+        let start = local_midnight(later) + chrono::Duration::hours(9);
+        let event = cal_events::Event::new(
+            "Review".to_string(),
+            None,
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        app.event_manager.add_event(event).unwrap();
+        app.calendar.go_to_date(day);
+
+        app.handle_action(Action::NextEventDay);
+        assert_eq!(app.calendar.selected_naive(), later);
+        app.handle_action(Action::NextEventDay);
+        assert_eq!(app.calendar.selected_naive(), later);
+        assert_eq!(app.status.as_ref().unwrap().0, "No more events");
+
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 5, 1).unwrap());
+        app.handle_action(Action::PreviousEventDay);
+        assert_eq!(app.calendar.selected_naive(), later);
+    }
+
+    #[test]
+    fn test_wrap_indented() {
+        assert_eq!(
+            wrap_indented("• Planning session (09:00 - 10:00)", 20, "  "),
+            ["• Planning session", "  (09:00 - 10:00)"]
+        );
+        assert_eq!(
+            wrap_indented("  one two three four", 10, "  "),
+            ["  one two", "  three", "  four"]
+        );
+        assert_eq!(
+            wrap_indented("abcdefghijkl", 6, "  "),
+            ["abcdef", "  ghij", "  kl"]
+        );
+        assert_eq!(wrap_indented("short", 40, "  "), ["short"]);
+    }
+
+    #[test]
+    fn test_long_description_wraps_in_events_panel() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        let words: Vec<String> = (0..30).map(|n| format!("word{:02}", n)).collect();
+        let description = words.join(" ");
+        assert!(description.chars().count() > 200);
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        let event = cal_events::Event::new(
+            "Planning".to_string(),
+            Some(description),
+            start,
+            start + chrono::Duration::hours(1),
+        )
+        .unwrap();
+        app.event_manager.add_event(event).unwrap();
+
+        let buffer = render_to_buffer(&mut app, 120, 40);
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+        let description_rows = rows.iter().filter(|row| row.contains("word")).count();
+        assert!(description_rows >= 3, "{description_rows}");
+        let text = rows.concat();
+        assert!(words.iter().all(|word| text.contains(word.as_str())));
+    }
+
+    #[test]
+    fn test_calendars_target_new_events_and_hide() {
+        let mut app = App::new();
+        app.run_command(Command::Calendar("work".to_string()))
+            .unwrap();
+        assert_eq!(app.target_calendar, "work");
+
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        let event = cal_events::EventBuilder::new()
+            .title("Standup")
+            .start(start)
+            .end(start + chrono::Duration::hours(1))
+            .build()
+            .unwrap();
+        app.add_popup_event(event, true);
+        assert_eq!(app.day_events()[0].calendar, "work");
+
+        app.handle_action(Action::ToggleCalendar);
+        assert!(app.day_events().is_empty());
+        app.handle_action(Action::CycleCalendar);
+        assert_eq!(app.target_calendar, cal_events::DEFAULT_CALENDAR);
+        app.handle_action(Action::CycleCalendar);
+        app.handle_action(Action::ToggleCalendar);
+        assert_eq!(app.day_events().len(), 1);
+    }
+
+    #[test]
+    fn test_go_to_prompt_fills_dashes_only_for_absolute_dates() {
+        let typed = |keys: &str| {
+            let mut input = String::new();
+            keys.chars().for_each(|c| push_go_to_char(&mut input, c));
+            input
+        };
+        assert_eq!(typed("20250314"), "2025-03-14");
+        assert_eq!(typed("2025031499"), "2025-03-14");
+        assert_eq!(typed("+1000d"), "+1000d");
+        assert_eq!(typed("next wednesday!!!"), "next wednesday!!");
+    }
+
+    #[test]
+    fn test_events_panel_shows_total_booked_time() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        for (offset, minutes) in [(0, 120), (60, 90)] {
+            let start = start + chrono::Duration::minutes(offset);
+            let event = cal_events::EventBuilder::new()
+                .title("Busy")
+                .start(start)
+                .end(start + chrono::Duration::minutes(minutes))
+                .build()
+                .unwrap();
+            app.event_manager.add_event(event).unwrap();
         }
-    };
 
-    let calendar_chunk_index = 1;
-    f.render_widget(calendar_widget, content_chunks[calendar_chunk_index]);
+        let buffer = render_to_buffer(&mut app, 120, 40);
+        assert!(buffer_text(&buffer, |_| true).contains("Total: 2h 30m booked"));
+    }
 
-    let sidebar_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(12), Constraint::Min(0)])
-        .split(main_chunks[0]);
+    #[test]
+    fn test_fit_month_grid_drops_empty_rows() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        app.calendar.set_week_start(Weekday::Sun);
+        // February 2026 starts on a Sunday and spans exactly four weeks
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2026, 2, 10).unwrap());
+        assert_eq!(app.calendar.used_weeks(), 4);
 
-    let mini_calendar = create_mini_calendar(app);
-    f.render_widget(mini_calendar, sidebar_chunks[0]);
+        let events_title_row = |app: &mut App| {
+            let buffer = render_to_buffer(app, 120, 40);
+            (0..buffer.area.height).find(|&y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+                    .contains("Events for")
+            })
+        };
+        assert_eq!(events_title_row(&mut app), Some(29));
+        app.handle_action(Action::ToggleFitMonth);
+        assert_eq!(events_title_row(&mut app), Some(3 + month_grid_height(4)));
+        // The grid still ends right after its last week
+        assert_eq!(
+            app.month_grid_area.unwrap().bottom(),
+            3 + month_grid_height(4) - 1
+        );
+    }
 
-    if app.view_mode == ViewMode::Month {
-        let events = app
-            .event_manager
-            .list_events_for_day(app.calendar.selected_date);
+    #[test]
+    fn test_twelve_hour_clock() {
+        let at = Local.with_ymd_and_hms(2025, 3, 14, 14, 5, 9).unwrap();
+        assert_eq!(format_time(&at, TimeFormat::TwentyFourHour), "14:05");
+        assert_eq!(format_time(&at, TimeFormat::TwelveHour), "02:05 PM");
+        assert_eq!(create_clock(at, TimeFormat::TwelveHour), "02:05:09 PM");
 
-        let events_text = if events.is_empty() {
-            "No events scheduled".to_string()
-        } else {
-            events
-                .iter()
-                .map(|e| {
-                    format!(
-                        "• {} ({} - {})\n  {}",
-                        e.title,
-                        e.start_time.format("%H:%M"),
-                        e.end_time.format("%H:%M"),
-                        e.description.as_deref().unwrap_or("-")
-                    )
-                })
-                .collect::<Vec<_>>()
-                .join("\n")
+        let mut app = App::new();
+        app.apply_preferences(Preferences::default());
+        app.handle_action(Action::ToggleTimeFormat);
+        assert_eq!(app.preferences().time_format, TimeFormat::TwelveHour);
+
+        let timeline = Timeline {
+            scroll: 11,
+            time_format: app.time_format,
+            ..Timeline::default()
         };
+        let week = render_to_string(create_week_view(
+            &app.calendar,
+            &[],
+            &app.theme,
+            &app.weekday_labels,
+            &timeline,
+        ));
+        assert!(week.contains("11 AM"));
+        assert!(week.contains("12 PM"));
+    }
 
-        let events_widget = Paragraph::new(events_text)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(format!(
-                        "Events for {}",
-                        app.calendar.selected_date.format("%B %d, %Y")
-                    ))
-                    .border_style(if app.focused_panel == FocusedPanel::Events {
-                        Style::default().fg(Color::Cyan)
-                    } else {
-                        Style::default()
-                    }),
-            )
-            .style(Style::default())
-            .alignment(Alignment::Left);
+    #[test]
+    fn test_category_legend_follows_categories_in_use() {
+        let mut app = App::new();
+        app.theme
+            .categories
+            .insert("work".to_string(), ratatui::style::Color::Blue);
+        let legend_text = |app: &mut App| {
+            let buffer = render_to_buffer(app, 120, 40);
+            buffer_text(&buffer, |_| true)
+        };
+        assert!(!legend_text(&mut app).contains("Categories"));
 
-        f.render_widget(events_widget, content_chunks[2]);
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        for category in ["work", "home"] {
+            let event = cal_events::EventBuilder::new()
+                .title("Errand")
+                .category(category)
+                .start(start)
+                .end(start + chrono::Duration::hours(1))
+                .build()
+                .unwrap();
+            app.event_manager.add_event(event).unwrap();
+        }
+        assert!(legend_text(&mut app).contains("Categories"));
+
+        let legend = category_legend(&app.event_manager.categories(), &app.theme);
+        let lines: Vec<String> = legend.iter().map(|line| line.to_string()).collect();
+        assert_eq!(lines, ["■ home", "■ work"]);
+        assert_eq!(legend[0].spans[0].style.fg, None);
+        assert_eq!(
+            legend[1].spans[0].style.fg,
+            Some(ratatui::style::Color::Blue)
+        );
+
+        // Events are drawn in the color of their category
+        let colors: Vec<_> = app
+            .day_events()
+            .iter()
+            .map(|event| (event.category.clone(), event_style(event, &app.theme).fg))
+            .collect();
+        assert!(colors.contains(&(Some("work".to_string()), Some(ratatui::style::Color::Blue))));
+        assert!(colors.contains(&(Some("home".to_string()), None)));
+        let buffer = render_to_buffer(&mut app, 120, 40);
+        assert!(
+            buffer_text(&buffer, |cell| cell.fg == ratatui::style::Color::Blue).contains("Errand")
+        );
     }
 
-    draw_event_popup(f, app, area);
-}
+    #[test]
+    fn test_month_rows_shrink_then_scroll() {
+        assert_eq!(MonthRows::fit(20, 6, 0), MonthRows::default());
+        assert_eq!(MonthRows::fit(13, 5, 0).row_height, 2);
+        assert_eq!(MonthRows::fit(8, 6, 0).row_height, 1);
 
-fn create_month_view(calendar: &Calendar) -> Table {
-    let weekdays = ["S", "M", "T", "W", "T", "F", "S"];
-    let header_cells = weekdays
-        .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Gray)));
-    let header = Row::new(header_cells)
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .height(1);
+        // Too short for a line per week: the selected week stays in view
+        let rows = MonthRows::fit(4, 6, 5);
+        assert_eq!((rows.header_height, rows.row_height), (1, 1));
+        assert_eq!(rows.first_week, 3);
+        assert_eq!(MonthRows::fit(4, 6, 1).first_week, 0);
+        assert_eq!(
+            month_cell_at(Rect::new(0, 0, 70, 4), 1, 1, false, rows),
+            Some((3, 0))
+        );
+    }
 
-    let grid = calendar.get_month_grid();
-    let rows: Vec<Row> = grid
-        .iter()
-        .map(|week| {
-            let cells = week.iter().map(|day| match day {
-                Some(d) => {
-                    let now = Local::now();
-                    let is_current_day = d == &now.day()
-                        && calendar.current_date.month() == now.month()
-                        && calendar.current_date.year() == now.year();
-
-                    let style = if is_current_day {
-                        Style::default()
-                            .fg(Color::Blue)
-                            .add_modifier(Modifier::BOLD)
-                    } else {
-                        Style::default()
-                    };
+    #[test]
+    fn test_short_terminal_keeps_selected_day_visible() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        app.calendar.set_week_start(Weekday::Sun);
+        // March 2025 spans six weeks; the 31st is alone in the last one
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 31).unwrap());
 
-                    Cell::from(format!("{:2}", d)).style(style)
-                }
-                None => Cell::from("  "),
-            });
-            Row::new(cells).height(1)
-        })
-        .collect();
+        let buffer = render_to_buffer(&mut app, 120, 8);
+        let reversed = buffer_text(&buffer, |cell| cell.modifier.contains(Modifier::REVERSED));
+        assert!(reversed.contains("31"), "{reversed}");
+        assert_eq!(app.month_rows.row_height, 1);
+        assert!(app.month_rows.first_week > 0);
+    }
 
-    let widths = [
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-        Constraint::Length(2),
-    ];
+    #[test]
+    fn test_import_status() {
+        let mut report = ImportReport {
+            added: 2,
+            skipped: 1,
+            ..ImportReport::default()
+        };
+        assert_eq!(
+            import_status(&report),
+            "Import: 2 added, 0 updated, 1 skipped, 0 failed"
+        );
+        report.errors = vec!["Invalid VEVENT #4: Missing DTEND".to_string()];
+        assert_eq!(
+            import_status(&report),
+            "Import: 2 added, 0 updated, 1 skipped, 1 failed (Invalid VEVENT #4: Missing DTEND)"
+        );
+    }
 
-    Table::new(rows, widths)
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!(
-            "{}  {}",
-            calendar.current_date.format("%B"),
-            calendar.current_date.format("%Y")
-        )))
-        .column_spacing(1)
-}
+    #[test]
+    fn test_off_hours_shading() {
+        let timeline = Timeline {
+            work_hours: Some(8..18),
+            ..Timeline::default()
+        };
+        // A Friday and the Saturday after it
+        let friday = NaiveDate::from_ymd_opt(2025, 3, 14);
+        let saturday = NaiveDate::from_ymd_opt(2025, 3, 15);
+        assert!(timeline.is_off_hours(friday, 7));
+        assert!(!timeline.is_off_hours(friday, 8));
+        assert!(!timeline.is_off_hours(friday, 17));
+        assert!(timeline.is_off_hours(friday, 18));
+        assert!(timeline.is_off_hours(saturday, 12));
+        assert!(!timeline.is_off_hours(None, 12));
+        assert!(!Timeline::default().is_off_hours(saturday, 3));
 
-fn create_week_view(calendar: &Calendar, scroll: usize) -> Table {
-    let header = Row::new(
-        ["Time", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        let mut calendar = Calendar::new();
+        calendar.go_to_date(friday.unwrap());
+        let theme = Theme::default();
+        let shaded_rows = |timeline: &Timeline| {
+            let events = EventManager::new();
+            let mut terminal = Terminal::new(TestBackend::new(60, 30)).unwrap();
+            terminal
+                .draw(|f| {
+                    f.render_widget(
+                        create_day_view(
+                            &calendar,
+                            &events.list_events_for_day(calendar.selected_date),
+                            &theme,
+                            &WeekdayLabels::default(),
+                            &MonthLabels::default(),
+                            timeline,
+                        ),
+                        f.area(),
+                    )
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer().clone();
+            (0..buffer.area.height)
+                .filter(|&y| buffer[(30, y)].bg == theme.off_hours)
+                .count()
+        };
+        // Hours 6 and 7 are shaded, 8 and later are not
+        let scrolled = |work_hours| Timeline {
+            scroll: 6,
+            work_hours,
+            ..Timeline::default()
+        };
+        assert_eq!(
+            shaded_rows(&scrolled(Some(8..18))),
+            2 * TIMELINE_ROW_HEIGHT as usize
+        );
+        assert_eq!(shaded_rows(&scrolled(None)), 0);
+    }
+
+    #[test]
+    fn test_multiline_description_in_events_panel() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        let event = cal_events::EventBuilder::new()
+            .title("Planning")
+            .description("Agenda first\nThen budget")
+            .location("Room 4")
+            .start(start)
+            .end(start + chrono::Duration::hours(1))
+            .build()
+            .unwrap();
+        assert_eq!(
+            event_detail(&event),
+            "  Agenda first\n  Then budget @ Room 4"
+        );
+        app.event_manager.add_event(event).unwrap();
+
+        let buffer = render_to_buffer(&mut app, 120, 40);
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect();
+        let first = rows.iter().position(|row| row.contains("Agenda first"));
+        let second = rows
             .iter()
-            .map(|h| Cell::from(*h).style(Style::default().fg(Color::Gray))),
-    )
-    .style(Style::default().add_modifier(Modifier::BOLD))
-    .height(2);
+            .position(|row| row.contains("Then budget @ Room 4"));
+        assert_eq!(first.map(|row| row + 1), second);
+    }
 
-    let visible_hours = 8;
-    let rows = (scroll..scroll + visible_hours)
-        .map(|hour| {
-            let cells = std::iter::once(Cell::from(format!("{:02}:00", hour)))
-                .chain((0..7).map(|_| Cell::from("")));
-            Row::new(cells).height(3)
-        })
-        .collect::<Vec<_>>();
+    #[test]
+    fn test_move_event_across_month_end() {
+        let mut app = App::new();
+        app.view_mode = ViewMode::Month;
+        let march_31 = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        app.calendar.go_to_date(march_31);
+        let start = local_midnight(march_31) + chrono::Duration::hours(10);
+        let event = cal_events::EventBuilder::new()
+            .title("Review")
+            .start(start)
+            .end(start + chrono::Duration::minutes(90))
+            .build()
+            .unwrap();
+        let id = app.event_manager.add_event(event).unwrap();
+        app.focused_panel = FocusedPanel::Events;
 
-    let widths = [
-        Constraint::Length(6),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-        Constraint::Percentage(13),
-    ];
+        app.handle_action(Action::MoveEventLater);
+        let moved = app.event_manager.get_event(id).unwrap();
+        let april_1 = NaiveDate::from_ymd_opt(2025, 4, 1).unwrap();
+        assert_eq!(moved.start_time.with_timezone(&Local).date_naive(), april_1);
+        assert_eq!(moved.start_time.with_timezone(&Local).hour(), 10);
+        assert_eq!(
+            moved.end_time - moved.start_time,
+            chrono::Duration::minutes(90)
+        );
+        assert_eq!(app.calendar.selected_naive(), april_1);
+        assert_eq!(app.highlighted_event().unwrap().id, id);
+        assert!(app.dirty);
 
-    Table::new(rows, widths)
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title("Week View"))
-}
+        app.handle_action(Action::MoveEventEarlier);
+        app.handle_action(Action::MoveEventEarlier);
+        let moved = app.event_manager.get_event(id).unwrap();
+        assert_eq!(
+            moved.start_time.with_timezone(&Local).date_naive(),
+            NaiveDate::from_ymd_opt(2025, 3, 30).unwrap()
+        );
+    }
 
-fn create_day_view(calendar: &Calendar, scroll: usize) -> Table {
-    let header = Row::new(["Time", "Events"])
-        .style(Style::default().add_modifier(Modifier::BOLD))
-        .height(2);
+    #[test]
+    fn test_color_choice_survives_theme_changes() {
+        let mut app = App::new();
+        app.theme = Theme::default().with_color(false);
+        app.apply_preferences(Preferences::default());
+        assert!(!app.theme.color_enabled);
+        app.handle_action(Action::CycleTheme);
+        assert_eq!(app.theme, Theme::high_contrast().with_color(false));
+    }
 
-    let visible_hours = 8;
-    let rows = (scroll..scroll + visible_hours)
-        .map(|hour| Row::new(vec![Cell::from(format!("{:02}:00", hour)), Cell::from("")]).height(3))
-        .collect::<Vec<_>>();
+    #[test]
+    fn test_format_event_for_clipboard() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let start = local_midnight(day) + chrono::Duration::hours(15);
+        let mut event = cal_events::EventBuilder::new()
+            .title("Dentist")
+            .start(start)
+            .end(start + chrono::Duration::minutes(45))
+            .build()
+            .unwrap();
+        assert_eq!(
+            format_event_for_clipboard(&event),
+            "Dentist\nFriday, March 14, 2025 15:00 - 15:45"
+        );
 
-    let widths = [Constraint::Length(6), Constraint::Percentage(94)];
+        event.end_time = (start + chrono::Duration::hours(10)).fixed_offset();
+        event.location = Some("Main St 4".to_string());
+        event.description = Some("Bring the forms\nArrive early".to_string());
+        assert_eq!(
+            format_event_for_clipboard(&event),
+            "Dentist\nFriday, March 14, 2025 15:00 - Saturday, March 15, 2025 01:00\n\
+             Location: Main St 4\n\nBring the forms\nArrive early"
+        );
+    }
 
-    Table::new(rows, widths)
-        .header(header)
-        .block(Block::default().borders(Borders::ALL).title(format!(
-            "Day View - {}",
-            calendar.selected_date.format("%B %d, %Y")
-        )))
-}
+    #[test]
+    fn test_copy_key_only_applies_to_a_highlighted_event() {
+        let mut app = App::new();
+        app.focused_panel = FocusedPanel::Events;
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 14).unwrap());
+        assert!(!app.action_applies(Action::CopyEvent));
 
-fn draw_event_popup(f: &mut Frame, app: &App, area: Rect) {
-    if let PopupState::CreateEvent {
-        title,
-        description,
-        start_time,
-        end_time,
-        focused_field,
-    } = &app.popup
-    {
-        // Create a clear overlay
-        f.render_widget(Clear, area);
+        let start = local_midnight(app.calendar.selected_naive()) + chrono::Duration::hours(9);
+        let event = cal_events::Event::new(
+            "Standup".to_string(),
+            None,
+            start,
+            start + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        app.event_manager.add_event(event).unwrap();
+        assert!(app.action_applies(Action::CopyEvent));
+        app.focused_panel = FocusedPanel::Calendar;
+        assert!(!app.action_applies(Action::CopyEvent));
+    }
 
-        // Create a smaller popup area
-        let popup_area = centered_rect(60, 20, area);
+    #[test]
+    fn test_weekday_keys_only_apply_to_week_and_day_views() {
+        let mut app = App::new();
+        app.calendar.set_week_start(Weekday::Mon);
+        app.calendar
+            .go_to_date(NaiveDate::from_ymd_opt(2025, 3, 11).unwrap());
+        let friday = crossterm::event::KeyEvent::new(
+            KeyCode::Char('5'),
+            crossterm::event::KeyModifiers::NONE,
+        );
+        let action = |app: &App| {
+            app.keys
+                .action_for_where(&friday, |action| app.action_applies(action))
+        };
 
-        // Render popup background with default theme
-        let popup_block = Block::default()
-            .borders(Borders::ALL)
-            .title("Create New Event")
-            .title_alignment(Alignment::Center);
+        app.view_mode = ViewMode::Month;
+        assert_eq!(action(&app), None);
+        app.view_mode = ViewMode::Week;
+        assert_eq!(action(&app), Some(Action::SelectWeekday(Weekday::Fri)));
+        app.handle_action(Action::SelectWeekday(Weekday::Fri));
+        assert_eq!(
+            app.calendar.selected_naive(),
+            NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()
+        );
+    }
 
-        f.render_widget(popup_block, popup_area);
+    #[test]
+    fn test_drag_on_timeline_creates_event() {
+        let mut app = App::new();
+        let day = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        app.calendar.go_to_date(day);
+        app.view_mode = ViewMode::Day;
+        app.day_scroll = 8;
+        app.timeline_area = Some(Rect::new(1, 1, 70, 26));
+        let mouse = |kind, row| MouseEvent {
+            kind,
+            column: 20,
+            row,
+            modifiers: crossterm::event::KeyModifiers::NONE,
+        };
+        let press = MouseEventKind::Down(MouseButton::Left);
+        let release = MouseEventKind::Up(MouseButton::Left);
 
-        // Create layout for input fields
-        let inner = Layout::default()
-            .direction(Direction::Vertical)
-            .margin(1)
-            .constraints([
-                Constraint::Length(3), // Title
-                Constraint::Length(3), // Description
-                Constraint::Length(3), // Start time
-                Constraint::Length(3), // End time
-                Constraint::Length(2), // Controls
-            ])
-            .split(popup_area);
+        // Rows start below the two header lines, three lines to an hour
+        app.handle_mouse(mouse(press, 3 + 3 * 2 + 1));
+        app.handle_mouse(mouse(MouseEventKind::Drag(MouseButton::Left), 3 + 3 * 3));
+        assert_eq!(app.focused_hour, Some(11));
+        app.handle_mouse(mouse(release, 3 + 3 * 4 + 2));
+        let PopupState::CreateEvent {
+            start_time,
+            end_time,
+            ..
+        } = &app.popup
+        else {
+            panic!("expected the create popup, got {:?}", app.popup);
+        };
+        assert_eq!(start_time, "2025-03-12 10:00");
+        assert_eq!(end_time, "2025-03-12 13:00");
 
-        // Render input fields
-        let fields = [
-            (title.as_str(), "Title"),
-            (description.as_str(), "Description"),
-            (
-                &start_time.format("%Y-%m-%d %H:%M").to_string(),
-                "Start Time",
-            ),
-            (&end_time.format("%Y-%m-%d %H:%M").to_string(), "End Time"),
-        ];
+        // Dragging upwards gives the same span
+        app.close_popup();
+        app.handle_mouse(mouse(press, 3 + 3 * 4));
+        app.handle_mouse(mouse(release, 3 + 3 * 2));
+        assert!(matches!(
+            &app.popup,
+            PopupState::CreateEvent { start_time, .. } if start_time == "2025-03-12 10:00"
+        ));
 
-        for (i, (content, title)) in fields.iter().enumerate() {
-            let block = Block::default()
-                .borders(Borders::ALL)
-                .title(*title)
-                .border_style(if *focused_field == i {
-                    Style::default().add_modifier(Modifier::REVERSED)
-                } else {
-                    Style::default()
-                });
+        // A press and release within one hour just focuses it
+        app.close_popup();
+        app.handle_mouse(mouse(press, 3 + 3 * 5));
+        app.handle_mouse(mouse(release, 3 + 3 * 5 + 1));
+        assert_eq!(app.popup, PopupState::Hidden);
+        assert_eq!(app.focused_hour, Some(13));
+        assert_eq!(app.focused_panel, FocusedPanel::WeekView);
 
-            f.render_widget(Paragraph::new(*content).block(block), inner[i]);
-        }
+        // Releasing on the header cancels the drag
+        app.handle_mouse(mouse(press, 3 + 3 * 2));
+        app.handle_mouse(mouse(release, 1));
+        assert_eq!(app.popup, PopupState::Hidden);
+        assert_eq!(app.drag_start, None);
+    }
 
-        // Render controls
-        f.render_widget(
-            Paragraph::new("Tab: Next Field | Enter: Save | Esc: Cancel")
-                .alignment(Alignment::Center),
-            inner[4],
+    #[test]
+    fn test_timeline_cell_at_week_columns() {
+        let area = Rect::new(1, 1, 70, 26);
+        assert_eq!(timeline_cell_at(area, 1, 2, &WEEK_VIEW_WIDTHS, 8), None);
+        assert_eq!(
+            timeline_cell_at(area, 1, 3, &WEEK_VIEW_WIDTHS, 8),
+            Some((0, 8))
         );
+        // The first day column starts after the time labels and a space
+        assert_eq!(
+            timeline_cell_at(area, 8, 6, &WEEK_VIEW_WIDTHS, 8),
+            Some((1, 9))
+        );
+        assert_eq!(timeline_cell_at(area, 8, 27, &WEEK_VIEW_WIDTHS, 16), None);
     }
-}
 
-fn centered_rect(width: u16, height: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length((r.height - height) / 2),
-            Constraint::Length(height),
-            Constraint::Length((r.height - height) / 2),
-        ])
-        .split(r);
+    #[test]
+    fn test_snoozed_reminder_fires_again() {
+        let mut app = App::new();
+        let now = Local::now();
+        let mut event = cal_events::Event::new(
+            "Standup".to_string(),
+            None,
+            now + chrono::Duration::minutes(30),
+            now + chrono::Duration::minutes(45),
+        )
+        .unwrap();
+        event.reminder = Some(chrono::Duration::minutes(10));
+        let start = event.start_time;
+        let id = app.event_manager.add_event(event).unwrap();
+        let key = (id, start);
+        app.last_reminder_check = now;
+        let at = |minutes| now + chrono::Duration::minutes(minutes);
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Length((r.width - width) / 2),
-            Constraint::Length(width),
-            Constraint::Length((r.width - width) / 2),
-        ])
-        .split(popup_layout[1])[1]
+        assert!(app.fire_due_reminders(at(19)).is_empty());
+        assert_eq!(app.fire_due_reminders(at(20)).len(), 1);
+        assert!(matches!(app.popup, PopupState::Reminder { id: shown, .. } if shown == id));
+        assert_eq!(app.reminders[&key], ReminderState::Fired);
+
+        app.snooze_reminder(5, at(20));
+        assert_eq!(app.popup, PopupState::Hidden);
+        assert_eq!(app.reminders[&key], ReminderState::Snoozed(at(25)));
+        assert!(app.fire_due_reminders(at(24)).is_empty());
+
+        let fired = app.fire_due_reminders(at(25));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].0, "Standup");
+        assert!(matches!(app.popup, PopupState::Reminder { .. }));
+        assert_eq!(app.reminders[&key], ReminderState::Fired);
+
+        // Dismissed reminders stay quiet
+        app.close_popup();
+        assert!(app.fire_due_reminders(at(40)).is_empty());
+    }
+
+    #[test]
+    fn test_each_occurrence_gets_a_reminder() {
+        let mut app = App::new();
+        app.event_manager = EventManager::new();
+        let first = Local.with_ymd_and_hms(2030, 5, 6, 9, 0, 0).unwrap();
+        let mut event = cal_events::Event::new(
+            "Standup".to_string(),
+            None,
+            first,
+            first + chrono::Duration::minutes(15),
+        )
+        .unwrap();
+        event.reminder = Some(chrono::Duration::minutes(10));
+        event.recurrence = Some(Recurrence::new(Frequency::Daily));
+        app.event_manager.add_event(event).unwrap();
+        app.last_reminder_check = first - chrono::Duration::hours(1);
+
+        let fired = app.fire_due_reminders(first - chrono::Duration::minutes(5));
+        assert_eq!(fired.len(), 1);
+        app.close_popup();
+
+        let next = first + chrono::Duration::days(1);
+        app.last_reminder_check = next - chrono::Duration::hours(1);
+        let fired = app.fire_due_reminders(next - chrono::Duration::minutes(5));
+        assert_eq!(fired.len(), 1);
+        assert!(
+            matches!(app.popup, PopupState::Reminder { start, .. } if start == next.fixed_offset())
+        );
+    }
 }
@@ -0,0 +1,157 @@
+use crate::ViewMode;
+use anyhow::{Context, Result};
+use chrono::{NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// View choices remembered between sessions, kept apart from `config.toml`
+/// so the app can rewrite them freely.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    pub view_mode: ViewMode,
+    pub week_start: Weekday,
+    /// Name of the theme preset, see `Theme::PRESETS`.
+    pub theme: String,
+    /// Hour shown at the top of the week and day views when the app starts.
+    pub day_view_start_hour: u32,
+    /// How often reminders and status messages are checked while idle.
+    pub tick_rate_ms: u64,
+    pub time_format: TimeFormat,
+    /// Working day as the hours `[work_start_hour, work_end_hour)`; hours
+    /// outside it are shaded in the week and day views.
+    pub work_start_hour: u32,
+    pub work_end_hour: u32,
+    pub shade_off_hours: bool,
+    /// Day selected when the app was last closed, reopened unless it has passed.
+    pub selected_date: Option<NaiveDate>,
+}
+
+/// Whether times are shown on a 24-hour or a 12-hour clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeFormat {
+    #[default]
+    #[serde(rename = "24h")]
+    TwentyFourHour,
+    #[serde(rename = "12h")]
+    TwelveHour,
+}
+
+impl TimeFormat {
+    /// `strftime` pattern for a time of day, e.g. `14:30` or `02:30 PM`.
+    pub fn pattern(self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:%M",
+            TimeFormat::TwelveHour => "%I:%M %p",
+        }
+    }
+
+    /// Like [`TimeFormat::pattern`] with seconds, for the clock.
+    pub fn pattern_with_seconds(self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:%M:%S",
+            TimeFormat::TwelveHour => "%I:%M:%S %p",
+        }
+    }
+
+    /// `strftime` pattern labelling a whole hour of the timeline.
+    pub fn hour_pattern(self) -> &'static str {
+        match self {
+            TimeFormat::TwentyFourHour => "%H:00",
+            TimeFormat::TwelveHour => "%I %p",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            TimeFormat::TwentyFourHour => TimeFormat::TwelveHour,
+            TimeFormat::TwelveHour => TimeFormat::TwentyFourHour,
+        }
+    }
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            view_mode: ViewMode::Month,
+            week_start: Weekday::Sun,
+            theme: "default".to_string(),
+            day_view_start_hour: 8,
+            tick_rate_ms: 1000,
+            time_format: TimeFormat::default(),
+            work_start_hour: 8,
+            work_end_hour: 18,
+            shade_off_hours: true,
+            selected_date: None,
+        }
+    }
+}
+
+impl Preferences {
+    /// `~/.config/cal-rs/prefs.json`, or the platform equivalent.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("cal-rs").join("prefs.json"))
+    }
+
+    /// Loads the saved preferences, falling back to the defaults when the
+    /// file is missing or unreadable.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .map(|content| Self::from_json(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn from_json(content: &str) -> Self {
+        serde_json::from_str(content).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("No config directory to save preferences in")?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preferences_round_trip() {
+        let prefs = Preferences {
+            view_mode: ViewMode::Week,
+            week_start: Weekday::Mon,
+            theme: "high-contrast".to_string(),
+            day_view_start_hour: 7,
+            tick_rate_ms: 500,
+            time_format: TimeFormat::TwelveHour,
+            work_start_hour: 9,
+            work_end_hour: 17,
+            shade_off_hours: false,
+            selected_date: NaiveDate::from_ymd_opt(2025, 3, 14),
+        };
+        let json = serde_json::to_string(&prefs).unwrap();
+        assert_eq!(Preferences::from_json(&json), prefs);
+    }
+
+    #[test]
+    fn test_corrupt_preferences_use_defaults() {
+        assert_eq!(Preferences::from_json("{ not json"), Preferences::default());
+
+        // Fields missing from an older file keep their defaults
+        let prefs = Preferences::from_json(r#"{ "view_mode": "day" }"#);
+        assert_eq!(prefs.view_mode, ViewMode::Day);
+        assert_eq!(prefs.week_start, Weekday::Sun);
+        assert_eq!(prefs.day_view_start_hour, 8);
+        assert_eq!(prefs.tick_rate_ms, 1000);
+        assert_eq!(prefs.time_format, TimeFormat::TwentyFourHour);
+
+        let prefs = Preferences::from_json(r#"{ "time_format": "12h" }"#);
+        assert_eq!(prefs.time_format, TimeFormat::TwelveHour);
+    }
+}
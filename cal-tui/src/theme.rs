@@ -0,0 +1,217 @@
+use anyhow::{anyhow, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The `[theme]` table of `config.toml`. Every field is optional and
+/// overrides the chosen preset.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeFile {
+    preset: Option<String>,
+    today: Option<String>,
+    selected: Option<String>,
+    focus_border: Option<String>,
+    busy_day: Option<String>,
+    muted: Option<String>,
+    error: Option<String>,
+    now_line: Option<String>,
+    off_hours: Option<String>,
+    conflict: Option<String>,
+    categories: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub today: Color,
+    pub selected: Color,
+    pub focus_border: Color,
+    pub busy_day: Color,
+    pub muted: Color,
+    pub error: Color,
+    /// Marker for the current time in the week and day views.
+    pub now_line: Color,
+    /// Background of hours outside the working day and of weekends in the
+    /// week and day views.
+    pub off_hours: Color,
+    /// Events in the day view that overlap another event.
+    pub conflict: Color,
+    pub categories: HashMap<String, Color>,
+    /// Whether the colors are used at all. Without them, emphasis falls back
+    /// to bold, underline and reverse video.
+    pub color_enabled: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            today: Color::Blue,
+            selected: Color::Reset,
+            focus_border: Color::Cyan,
+            busy_day: Color::Yellow,
+            muted: Color::Gray,
+            error: Color::Red,
+            now_line: Color::Red,
+            off_hours: Color::Black,
+            conflict: Color::Red,
+            categories: HashMap::new(),
+            color_enabled: true,
+        }
+    }
+}
+
+impl Theme {
+    /// Bright colors that stay readable on both light and dark backgrounds.
+    pub fn high_contrast() -> Self {
+        Self {
+            today: Color::LightRed,
+            selected: Color::LightYellow,
+            focus_border: Color::LightMagenta,
+            busy_day: Color::LightGreen,
+            muted: Color::Reset,
+            error: Color::LightRed,
+            now_line: Color::LightRed,
+            off_hours: Color::DarkGray,
+            conflict: Color::LightRed,
+            categories: HashMap::new(),
+            color_enabled: true,
+        }
+    }
+
+    pub const PRESETS: [&'static str; 2] = ["default", "high-contrast"];
+
+    pub fn preset(name: &str) -> Result<Self> {
+        match name {
+            "default" => Ok(Self::default()),
+            "high-contrast" => Ok(Self::high_contrast()),
+            _ => Err(anyhow!("Unknown theme preset '{}'", name)),
+        }
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self> {
+        Self::from_file(toml::from_str(content)?)
+    }
+
+    pub fn from_file(file: ThemeFile) -> Result<Self> {
+        let mut theme = match &file.preset {
+            Some(name) => Self::preset(name)?,
+            None => Self::default(),
+        };
+
+        for (field, value) in [
+            (&mut theme.today, file.today),
+            (&mut theme.selected, file.selected),
+            (&mut theme.focus_border, file.focus_border),
+            (&mut theme.busy_day, file.busy_day),
+            (&mut theme.muted, file.muted),
+            (&mut theme.error, file.error),
+            (&mut theme.now_line, file.now_line),
+            (&mut theme.off_hours, file.off_hours),
+            (&mut theme.conflict, file.conflict),
+        ] {
+            if let Some(value) = value {
+                *field = parse_color(&value)?;
+            }
+        }
+        for (category, value) in file.categories {
+            theme.categories.insert(category, parse_color(&value)?);
+        }
+        Ok(theme)
+    }
+
+    pub fn category_color(&self, category: &str) -> Option<Color> {
+        self.categories.get(category).copied()
+    }
+
+    pub fn with_color(mut self, enabled: bool) -> Self {
+        self.color_enabled = enabled;
+        self
+    }
+
+    /// Text in `color`, or plain text when color is off.
+    pub fn fg(&self, color: Color) -> Style {
+        if self.color_enabled {
+            Style::default().fg(color)
+        } else {
+            Style::default()
+        }
+    }
+
+    /// A background of `color`, or none when color is off.
+    pub fn bg(&self, color: Color) -> Style {
+        if self.color_enabled {
+            Style::default().bg(color)
+        } else {
+            Style::default()
+        }
+    }
+
+    /// Text in `color`, or with `fallback` when color is off so that it
+    /// still stands out.
+    pub fn accent(&self, color: Color, fallback: Modifier) -> Style {
+        if self.color_enabled {
+            Style::default().fg(color)
+        } else {
+            Style::default().add_modifier(fallback)
+        }
+    }
+}
+
+/// Whether the terminal should get colors: not when `NO_COLOR` is set to
+/// anything (see <https://no-color.org>) or `TERM` is `dumb`.
+pub fn color_supported() -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let dumb = std::env::var_os("TERM").is_some_and(|term| term == "dumb");
+    !no_color && !dumb
+}
+
+fn parse_color(value: &str) -> Result<Color> {
+    Color::from_str(value).map_err(|_| anyhow!("Invalid color '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_from_toml() {
+        let theme = Theme::from_toml(
+            r##"
+            preset = "high-contrast"
+            today = "#00ff00"
+
+            [categories]
+            work = "blue"
+            "##,
+        )
+        .unwrap();
+
+        assert_eq!(theme.today, Color::Rgb(0, 255, 0));
+        assert_eq!(theme.focus_border, Theme::high_contrast().focus_border);
+        assert_eq!(theme.category_color("work"), Some(Color::Blue));
+        assert_eq!(theme.category_color("home"), None);
+
+        assert!(Theme::from_toml("today = \"not-a-color\"").is_err());
+        assert!(Theme::from_toml("preset = \"neon\"").is_err());
+        assert_eq!(Theme::from_toml("").unwrap(), Theme::default());
+    }
+
+    #[test]
+    fn test_styles_without_color() {
+        let theme = Theme::default();
+        assert_eq!(theme.fg(theme.muted), Style::default().fg(Color::Gray));
+        assert_eq!(
+            theme.accent(theme.error, Modifier::BOLD),
+            Style::default().fg(Color::Red)
+        );
+
+        let theme = theme.with_color(false);
+        assert_eq!(theme.fg(theme.muted), Style::default());
+        assert_eq!(theme.bg(theme.off_hours), Style::default());
+        assert_eq!(
+            theme.accent(theme.error, Modifier::BOLD),
+            Style::default().add_modifier(Modifier::BOLD)
+        );
+    }
+}